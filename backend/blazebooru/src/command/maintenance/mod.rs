@@ -0,0 +1,30 @@
+mod regenerate_thumbnails;
+
+use blazebooru_core::BlazeBooruCore;
+use blazebooru_models::local as lm;
+
+use crate::MaintenanceCommand;
+
+pub(crate) async fn maintenance(core: BlazeBooruCore, command: MaintenanceCommand) -> Result<(), anyhow::Error> {
+    match command {
+        MaintenanceCommand::RegenerateThumbnails {
+            tags,
+            exts,
+            min_id,
+            max_id,
+            jobs,
+            restart,
+        } => {
+            let filter = lm::PostFilter {
+                tags,
+                exts,
+                min_id,
+                max_id,
+            };
+
+            regenerate_thumbnails::regenerate_thumbnails(core, filter, jobs, restart).await?
+        }
+    };
+
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+
+use anyhow::{Context, anyhow};
+use futures::{StreamExt, stream};
+use serde::{Deserialize, Serialize};
+use tokio::runtime::Handle;
+use tracing::{error, info};
+
+use blazebooru_core::BlazeBooruCore;
+use blazebooru_models::local as lm;
+
+/// File in the temp directory containing the progress of an interrupted run
+const PROGRESS_FILENAME: &str = "regenerate-thumbnails.progress";
+
+/// Progress of a run, which is only resumed with the same filter
+#[derive(Serialize, Deserialize)]
+struct Progress {
+    filter: lm::PostFilter,
+    /// ID of the last processed post
+    last_id: Option<i32>,
+    /// IDs of posts that failed, which are retried when resuming
+    failed_ids: Vec<i32>,
+}
+
+pub async fn regenerate_thumbnails(
+    core: BlazeBooruCore,
+    filter: lm::PostFilter,
+    jobs: Option<usize>,
+    restart: bool,
+) -> Result<(), anyhow::Error> {
+    let progress_path = core.temp_path.join(PROGRESS_FILENAME);
+
+    let mut progress = Progress {
+        filter: filter.clone(),
+        last_id: None,
+        failed_ids: Vec::new(),
+    };
+
+    // Continue after the last processed post of an interrupted run
+    if !restart && let Some(previous) = read_progress(&progress_path).await? {
+        if previous.filter != filter {
+            return Err(anyhow!(
+                "An interrupted run with a different filter exists, use the same filter to resume it or --restart to start over"
+            ));
+        }
+
+        progress = previous;
+    }
+
+    // Failed posts stay in the progress until they have been retried successfully
+    let mut post_ids = progress.failed_ids.clone();
+
+    let mut remaining_filter = filter;
+    if let Some(last_id) = progress.last_id {
+        info!(
+            "Resuming after post ID {last_id} and retrying {} failed posts, use --restart to start over...",
+            post_ids.len()
+        );
+        remaining_filter.min_id = Some(remaining_filter.min_id.map_or(last_id + 1, |id| id.max(last_id + 1)));
+    }
+
+    post_ids.extend(core.get_post_ids_by_filter(&remaining_filter).await?);
+
+    let jobs = jobs
+        .or_else(|| thread::available_parallelism().ok().map(|n| n.get()))
+        .unwrap_or(1)
+        .max(1);
+
+    info!(
+        "Regenerating thumbnails of {} posts using {jobs} parallel jobs...",
+        post_ids.len()
+    );
+
+    let core = Arc::new(core);
+    let handle = Handle::current();
+
+    // Thumbnail generation blocks, so each post is processed on a blocking thread
+    let mut results = stream::iter(post_ids)
+        .map(|id| {
+            let core = core.clone();
+            let handle = handle.clone();
+            tokio::task::spawn_blocking(move || (id, handle.block_on(core.regenerate_post_thumbnail(id))))
+        })
+        .buffered(jobs);
+
+    let mut regenerated_count = 0;
+
+    while let Some(result) = results.next().await {
+        let (id, result) = result.context("Error joining thumbnail regeneration task")?;
+
+        match result {
+            Ok(success) => {
+                if success {
+                    regenerated_count += 1;
+                }
+
+                progress.failed_ids.retain(|&failed_id| failed_id != id);
+            }
+            Err(err) => {
+                if !progress.failed_ids.contains(&id) {
+                    progress.failed_ids.push(id);
+                }
+
+                error!("Error regenerating thumbnail of post {id}: {err:#}");
+            }
+        }
+
+        // Results are returned in order, so all posts up to this one have been processed.
+        // Retried posts have lower IDs than the remaining ones, so the last ID never decreases.
+        progress.last_id = progress.last_id.max(Some(id));
+        write_progress(&progress_path, &progress).await?;
+    }
+
+    let failed_count = progress.failed_ids.len();
+
+    // Failed posts are kept in the progress file, so that they can be retried
+    if failed_count == 0 {
+        match tokio::fs::remove_file(&progress_path).await {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err).context("Error removing progress file"),
+        }
+    }
+
+    info!("Regenerated thumbnails of {regenerated_count} posts, {failed_count} failed.");
+
+    Ok(())
+}
+
+async fn read_progress(path: &Path) -> Result<Option<Progress>, anyhow::Error> {
+    match tokio::fs::read(path).await {
+        Ok(data) => Ok(Some(
+            serde_json::from_slice(&data).context("Error parsing progress file")?,
+        )),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err).context("Error reading progress file"),
+    }
+}
+
+async fn write_progress(path: &Path, progress: &Progress) -> Result<(), anyhow::Error> {
+    let data = serde_json::to_vec(progress).context("Error serializing progress")?;
+
+    tokio::fs::write(path, data)
+        .await
+        .context("Error writing progress file")
+}
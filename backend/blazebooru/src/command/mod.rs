@@ -1,7 +1,11 @@
 mod export;
 mod import;
+mod maintenance;
+mod purge;
 mod server;
 
 pub(crate) use self::export::*;
 pub(crate) use self::import::*;
+pub(crate) use self::maintenance::*;
+pub(crate) use self::purge::*;
 pub(crate) use self::server::*;
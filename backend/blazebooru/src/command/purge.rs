@@ -0,0 +1,19 @@
+use chrono::{TimeDelta, Utc};
+use tracing::info;
+
+use blazebooru_core::BlazeBooruCore;
+
+pub async fn purge(core: BlazeBooruCore, retention_days: u32) -> Result<(), anyhow::Error> {
+    let deleted_before = Utc::now() - TimeDelta::days(retention_days.into());
+
+    info!("Purging posts deleted before {deleted_before}...");
+
+    let result = core.purge_deleted_posts(deleted_before).await?;
+
+    info!(
+        "Purged {} posts and removed {} files.",
+        result.purged_post_count, result.removed_file_count
+    );
+
+    Ok(())
+}
@@ -35,6 +35,22 @@ enum Command {
         command: ImportCommand,
     },
 
+    #[clap(about = "Run maintenance tasks")]
+    Maintenance {
+        #[clap(subcommand)]
+        command: MaintenanceCommand,
+    },
+
+    #[clap(about = "Permanently delete posts that have been deleted for longer than the retention period")]
+    Purge {
+        #[clap(
+            long = "retention-days",
+            default_value_t = 30,
+            help = "Number of days to keep deleted posts"
+        )]
+        retention_days: u32,
+    },
+
     #[clap(about = "Run BlazeBooru server")]
     Server {
         #[clap(long = "serve-files", help = "Serve public files (recommended only for development)")]
@@ -62,6 +78,33 @@ enum ImportCommand {
     },
 }
 
+#[derive(Debug, Parser)]
+enum MaintenanceCommand {
+    #[clap(about = "Regenerate post thumbnails, file dimensions and perceptual hashes, resuming an interrupted run")]
+    RegenerateThumbnails {
+        #[clap(long = "tag", short = 't', help = "Only posts with this tag (can be repeated)")]
+        tags: Vec<String>,
+        #[clap(
+            long = "ext",
+            short = 'e',
+            help = "Only posts with this file extension (can be repeated)"
+        )]
+        exts: Vec<String>,
+        #[clap(long = "min-id", help = "Only posts with at least this ID")]
+        min_id: Option<i32>,
+        #[clap(long = "max-id", help = "Only posts with at most this ID")]
+        max_id: Option<i32>,
+        #[clap(
+            long = "jobs",
+            short = 'j',
+            help = "Number of posts to process in parallel [default: number of CPUs]"
+        )]
+        jobs: Option<usize>,
+        #[clap(long = "restart", help = "Ignore the progress of an interrupted run")]
+        restart: bool,
+    },
+}
+
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
     let opt = Opt::parse();
@@ -84,6 +127,8 @@ async fn main() -> Result<(), anyhow::Error> {
     match opt.command {
         Command::Export { command } => command::export(core, command).await?,
         Command::Import { command } => command::import(core, command).await?,
+        Command::Maintenance { command } => command::maintenance(core, command).await?,
+        Command::Purge { retention_days } => command::purge(core, retention_days).await?,
         Command::Server { serve_files } => command::server(config, core, serve_files).await?,
     };
 
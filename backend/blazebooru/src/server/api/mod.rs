@@ -1,5 +1,8 @@
 mod auth;
+mod note;
+mod pool;
 mod post;
+mod report;
 mod sys;
 mod tag;
 mod user;
@@ -33,7 +36,10 @@ struct Authorized {
 
 pub fn router(config: &BlazeBooruConfig) -> Router<Arc<BlazeBooruServer>> {
     let auth = auth::router();
+    let note = note::router();
+    let pool = pool::router();
     let post = post::router(config);
+    let report = report::router();
     let sys = sys::router();
     let user = user::router();
     let tag = tag::router();
@@ -42,7 +48,10 @@ pub fn router(config: &BlazeBooruConfig) -> Router<Arc<BlazeBooruServer>> {
     Router::new()
         .nest("/auth", auth)
         .nest("/sys", sys)
+        .nest("/note", note)
+        .nest("/pool", pool)
         .nest("/post", post)
+        .nest("/report", report)
         .nest("/user", user)
         .nest("/tag", tag)
         .nest("/wiki", wiki)
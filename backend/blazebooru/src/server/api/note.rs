@@ -0,0 +1,95 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::extract::{Path, State};
+use axum::routing::get;
+use axum::{Json, Router};
+
+use blazebooru_models::view as vm;
+
+use crate::server::api::Authorized;
+use crate::server::{ApiError, BlazeBooruServer};
+
+pub fn router() -> Router<Arc<BlazeBooruServer>> {
+    Router::new()
+        .route("/{id}", get(get_note).post(update_note).delete(delete_note))
+        .route("/{id}/history", get(get_note_history))
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn get_note(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Option<Authorized>,
+    Path(id): Path<i32>,
+) -> Result<Json<vm::PostNote>, ApiError> {
+    if server.config.require_login && auth.is_none() {
+        return Err(ApiError::Unauthorized);
+    }
+
+    let note = server.core.get_post_note(id).await.context("Error getting post note")?;
+
+    Ok(Json(note.ok_or(ApiError::NotFound)?))
+}
+
+/// Get all versions of a note, newest first
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn get_note_history(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Option<Authorized>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<vm::PostNoteVersion>>, ApiError> {
+    if server.config.require_login && auth.is_none() {
+        return Err(ApiError::Unauthorized);
+    }
+
+    let versions = server
+        .core
+        .get_post_note_versions(id)
+        .await
+        .context("Error getting post note history")?;
+
+    Ok(Json(versions))
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn update_note(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Path(id): Path<i32>,
+    Json(req): Json<vm::PostNoteContent>,
+) -> Result<(), ApiError> {
+    if !req.is_valid() {
+        return Err(ApiError::BadRequest);
+    }
+
+    let success = server
+        .core
+        .update_post_note(id, req, auth.claims.user_id)
+        .await
+        .context("Error updating post note")?;
+
+    if !success {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(())
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn delete_note(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Path(id): Path<i32>,
+) -> Result<(), ApiError> {
+    let success = server
+        .core
+        .delete_post_note(id, auth.claims.user_id)
+        .await
+        .context("Error deleting post note")?;
+
+    if !success {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(())
+}
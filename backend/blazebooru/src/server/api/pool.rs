@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::extract::{Path, State};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+
+use blazebooru_core::DuplicatePoolNameError;
+use blazebooru_models::view as vm;
+
+use crate::server::api::Authorized;
+use crate::server::{ApiError, BlazeBooruServer};
+
+pub fn router() -> Router<Arc<BlazeBooruServer>> {
+    Router::new()
+        .route("/{id}", get(get_pool).post(update_pool).delete(delete_pool))
+        .route("/{id}/posts", get(get_pool_posts).post(set_pool_posts))
+        .route("/list", get(list_pools))
+        .route("/new", post(create_pool))
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn create_pool(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Json(req): Json<vm::NewPool>,
+) -> Result<Json<vm::Pool>, ApiError> {
+    if req.name.trim().is_empty() {
+        return Err(ApiError::BadRequest);
+    }
+
+    let id = server
+        .core
+        .create_pool(req, auth.claims.user_id)
+        .await
+        .map_err(|err| pool_error(err, "Error creating pool"))?;
+
+    let pool = server.core.get_pool(id).await.context("Error getting pool")?;
+
+    Ok(Json(pool.ok_or(ApiError::NotFound)?))
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn get_pool(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Option<Authorized>,
+    Path(id): Path<i32>,
+) -> Result<Json<vm::Pool>, ApiError> {
+    if server.config.require_login && auth.is_none() {
+        return Err(ApiError::Unauthorized);
+    }
+
+    let pool = server.core.get_pool(id).await.context("Error getting pool")?;
+
+    Ok(Json(pool.ok_or(ApiError::NotFound)?))
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn list_pools(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Option<Authorized>,
+) -> Result<Json<Vec<vm::Pool>>, ApiError> {
+    if server.config.require_login && auth.is_none() {
+        return Err(ApiError::Unauthorized);
+    }
+
+    let pools = server.core.get_all_pools().await.context("Error getting pools")?;
+
+    Ok(Json(pools))
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn get_pool_posts(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Option<Authorized>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<vm::Post>>, ApiError> {
+    if server.config.require_login && auth.is_none() {
+        return Err(ApiError::Unauthorized);
+    }
+
+    let posts = server
+        .core
+        .get_pool_posts(id, auth.map(|a| a.claims.user_id))
+        .await
+        .context("Error getting pool posts")?;
+
+    Ok(Json(posts.ok_or(ApiError::NotFound)?))
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn update_pool(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Path(id): Path<i32>,
+    Json(req): Json<vm::UpdatePool>,
+) -> Result<(), ApiError> {
+    if req.name.as_ref().is_some_and(|name| name.trim().is_empty()) {
+        return Err(ApiError::BadRequest);
+    }
+
+    let success = server
+        .core
+        .update_pool(id, req, auth.claims.user_id)
+        .await
+        .map_err(|err| pool_error(err, "Error updating pool"))?;
+
+    if !success {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// Replace the posts of a pool, which is also used to reorder them
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn set_pool_posts(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Path(id): Path<i32>,
+    Json(req): Json<vm::UpdatePoolPosts>,
+) -> Result<(), ApiError> {
+    let success = server
+        .core
+        .set_pool_posts(id, &req.post_ids, auth.claims.user_id)
+        .await
+        .context("Error setting pool posts")?;
+
+    if !success {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(())
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn delete_pool(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Path(id): Path<i32>,
+) -> Result<(), ApiError> {
+    let success = server
+        .core
+        .delete_pool(id, auth.claims.user_id)
+        .await
+        .context("Error deleting pool")?;
+
+    if !success {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// Convert a pool error, responding with a conflict if the pool name is already taken
+fn pool_error(err: anyhow::Error, context: &'static str) -> ApiError {
+    if err.is::<DuplicatePoolNameError>() {
+        return ApiError::Conflict;
+    }
+
+    err.context(context).into()
+}
@@ -13,14 +13,27 @@ use axum::routing::{get, post};
 use serde::Deserialize;
 
 use blazebooru_core::config::BlazeBooruConfig;
+use blazebooru_core::search::{SearchQueryError, build_search_query, page_cursor};
+use blazebooru_core::{ArchiveKind, DuplicatePostError, ExtractedFile, FileTooLargeError, PostParentError};
 use blazebooru_models::local as lm;
 use blazebooru_models::local::HashedFile;
+use blazebooru_models::search as sm;
 use blazebooru_models::view as vm;
 
 use crate::server::ApiError;
 use crate::server::BlazeBooruServer;
 use crate::server::api::Authorized;
 
+const DEFAULT_SEARCH_TAGS_LIMIT: i32 = 25;
+const MAX_SEARCH_TAGS_LIMIT: i32 = 100;
+const MAX_SIMILAR_POST_DISTANCE: u32 = 32;
+const DEFAULT_POPULAR_POSTS_LIMIT: i32 = 20;
+const MAX_POPULAR_POSTS_LIMIT: i32 = 100;
+const DEFAULT_DELETED_POSTS_LIMIT: i32 = 50;
+const MAX_DELETED_POSTS_LIMIT: i32 = 200;
+const DEFAULT_PENDING_POSTS_LIMIT: i32 = 50;
+const MAX_PENDING_POSTS_LIMIT: i32 = 200;
+
 #[derive(Deserialize)]
 struct CalculatePagesQuery {
     #[serde(rename = "pc")]
@@ -29,6 +42,8 @@ struct CalculatePagesQuery {
     origin_page_no: Option<i32>,
     #[serde(rename = "opsid")]
     origin_page_start_id: Option<i32>,
+    #[serde(rename = "opskey")]
+    origin_page_start_key: Option<i64>,
 }
 
 #[derive(Deserialize)]
@@ -37,7 +52,7 @@ struct CalculatePageQuery {
     posts_per_page: i32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 struct PostInfo {
     title: Option<String>,
     description: Option<String>,
@@ -45,16 +60,58 @@ struct PostInfo {
 
     #[serde(default)]
     tags: Vec<String>,
+
+    #[serde(default)]
+    rating: vm::Rating,
+}
+
+#[derive(Debug, Deserialize)]
+struct UrlUploadRequest {
+    url: String,
+    #[serde(flatten)]
+    info: PostInfo,
 }
 
 #[derive(Deserialize)]
 struct PaginatedQuery {
-    #[serde(default)]
     #[serde(rename = "sid")]
-    start_id: i32,
+    start_id: Option<i32>,
+    #[serde(rename = "skey")]
+    start_key: Option<i64>,
     limit: i32,
 }
 
+#[derive(Deserialize)]
+struct SimilarPostsQuery {
+    distance: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct PopularPostsQuery {
+    #[serde(default)]
+    period: vm::PopularPeriod,
+    limit: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct DeletedPostsQuery {
+    #[serde(rename = "sid")]
+    start_id: Option<i32>,
+    limit: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct PendingPostsQuery {
+    #[serde(rename = "sid")]
+    start_id: Option<i32>,
+    limit: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct SearchTagsQuery {
+    limit: Option<i32>,
+}
+
 #[derive(Deserialize)]
 struct PostSearchQuery {
     #[serde(rename = "t")]
@@ -65,21 +122,76 @@ struct PostSearchQuery {
     #[serde(default)]
     #[serde(deserialize_with = "crate::deserialize::comma_separated")]
     exclude_tags: Vec<String>,
+    #[serde(rename = "q")]
+    query: Option<String>,
+}
+
+impl PostSearchQuery {
+    fn into_search_query(self) -> Result<sm::SearchQuery, SearchQueryError> {
+        build_search_query(self.include_tags, self.exclude_tags, self.query.as_deref())
+    }
+
+    /// Build the search query, with the default rating filter of the user applied
+    async fn into_filtered_search_query(
+        self,
+        server: &BlazeBooruServer,
+        auth: Option<&Authorized>,
+    ) -> Result<sm::SearchQuery, ApiError> {
+        let query = self.into_search_query()?;
+
+        let query = server
+            .core
+            .apply_rating_filter(query, auth.map(|a| a.claims.user_id))
+            .await
+            .context("Error applying rating filter")?;
+
+        Ok(query)
+    }
 }
 
 pub fn router(config: &BlazeBooruConfig) -> Router<Arc<BlazeBooruServer>> {
     Router::new()
         .route("/", get(get_view_posts))
         .route("/{id}", get(get_view_post).delete(delete_post))
+        .route("/{id}/undelete", post(undelete_post))
+        .route("/{id}/approve", post(approve_post))
+        .route("/{id}/reject", post(reject_post))
         .route("/{id}/update", post(update_post))
+        .route("/{id}/history", get(get_post_history))
+        .route("/{id}/history/{change_id}/revert", post(revert_post_metadata))
+        .route(
+            "/{id}/file",
+            post(replace_post_file.layer(DefaultBodyLimit::max(config.max_image_size))),
+        )
+        .route("/{id}/file/{replacement_id}/revert", post(revert_post_file_replacement))
+        .route("/{id}/favorite", post(favorite_post).delete(unfavorite_post))
+        .route("/{id}/vote", post(vote_post))
         .route("/{id}/comments", get(get_post_comments))
+        .route("/{id}/similar", get(get_similar_posts))
+        .route("/{id}/neighbors", get(get_post_neighbors))
         .route("/{id}/comments/new", post(post_comment))
+        .route("/{id}/notes", get(get_post_notes))
+        .route("/{id}/notes/new", post(create_post_note))
+        .route("/deleted", get(get_deleted_posts))
+        .route("/pending", get(get_pending_posts))
+        .route("/popular", get(get_popular_posts))
+        .route("/random", get(get_random_post))
         .route("/pages", get(calculate_pages))
         .route("/pages/last", get(calculate_last_page))
+        .route("/tags", get(get_search_tags))
         .route(
             "/upload",
             post(upload_post.layer(DefaultBodyLimit::max(config.max_image_size))),
         )
+        .route(
+            "/upload/batch",
+            post(upload_posts.layer(DefaultBodyLimit::max(config.max_batch_upload_size))),
+        )
+        .route("/upload/url", post(upload_post_from_url))
+        .route(
+            "/similar",
+            post(find_similar_posts.layer(DefaultBodyLimit::max(config.max_image_size))),
+        )
 }
 
 #[axum::debug_handler(state = Arc<BlazeBooruServer>)]
@@ -108,7 +220,7 @@ async fn update_post(
         .core
         .update_post(id, req, auth.claims.user_id)
         .await
-        .context("Error updating post")?;
+        .map_err(|err| post_update_error(err, "Error updating post"))?;
 
     if !post {
         return Err(ApiError::NotFound);
@@ -117,6 +229,63 @@ async fn update_post(
     Ok(())
 }
 
+/// Convert a post update error, responding with a bad request if the parent is invalid
+fn post_update_error(err: anyhow::Error, context: &'static str) -> ApiError {
+    match err.downcast::<PostParentError>() {
+        Ok(err) => err.into(),
+        Err(err) => err.context(context).into(),
+    }
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn get_post_history(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Option<Authorized>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<vm::PostHistoryEntry>>, ApiError> {
+    if server.config.require_login && auth.is_none() {
+        return Err(ApiError::Unauthorized);
+    }
+
+    let history = server
+        .core
+        .get_post_history(id)
+        .await
+        .context("Error getting post history")?;
+
+    Ok(Json(history))
+}
+
+/// Revert the metadata of a post to the version before the specified change
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn revert_post_metadata(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Path((id, change_id)): Path<(i32, i32)>,
+) -> Result<(), ApiError> {
+    let can_edit = server
+        .core
+        .can_user_edit_post(id, auth.claims.user_id)
+        .await
+        .context("Error checking post edit permission")?;
+
+    if !can_edit {
+        return Err(ApiError::Forbidden);
+    }
+
+    let success = server
+        .core
+        .revert_post_metadata(id, change_id, auth.claims.user_id)
+        .await
+        .map_err(|err| post_update_error(err, "Error reverting post metadata"))?;
+
+    if !success {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(())
+}
+
 #[axum::debug_handler(state = Arc<BlazeBooruServer>)]
 async fn delete_post(
     State(server): State<Arc<BlazeBooruServer>>,
@@ -136,55 +305,385 @@ async fn delete_post(
     Ok(())
 }
 
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn get_deleted_posts(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Query(query): Query<DeletedPostsQuery>,
+) -> Result<Json<Vec<vm::DeletedPost>>, ApiError> {
+    let user = server.core.get_user_profile(auth.claims.user_id).await?;
+
+    if user.ok_or(ApiError::Unauthorized)?.rank <= 0 {
+        // TODO define ranks
+        return Err(ApiError::Unauthorized);
+    }
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_DELETED_POSTS_LIMIT)
+        .clamp(1, MAX_DELETED_POSTS_LIMIT);
+
+    let posts = server
+        .core
+        .get_deleted_posts(query.start_id, limit)
+        .await
+        .context("Error getting deleted posts")?;
+
+    Ok(Json(posts))
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn undelete_post(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Path(id): Path<i32>,
+) -> Result<(), ApiError> {
+    let user = server.core.get_user_profile(auth.claims.user_id).await?;
+
+    if user.ok_or(ApiError::Unauthorized)?.rank <= 0 {
+        // TODO define ranks
+        return Err(ApiError::Unauthorized);
+    }
+
+    let success = server.core.undelete_post(id).await.context("Error undeleting post")?;
+
+    if !success {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// Get the review queue of posts awaiting approval
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn get_pending_posts(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Query(query): Query<PendingPostsQuery>,
+) -> Result<Json<Vec<vm::PendingPost>>, ApiError> {
+    let user = server.core.get_user_profile(auth.claims.user_id).await?;
+
+    if user.ok_or(ApiError::Unauthorized)?.rank <= 0 {
+        // TODO define ranks
+        return Err(ApiError::Unauthorized);
+    }
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PENDING_POSTS_LIMIT)
+        .clamp(1, MAX_PENDING_POSTS_LIMIT);
+
+    let posts = server
+        .core
+        .get_pending_posts(query.start_id, limit)
+        .await
+        .context("Error getting pending posts")?;
+
+    Ok(Json(posts))
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn approve_post(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Path(id): Path<i32>,
+) -> Result<(), ApiError> {
+    let user = server.core.get_user_profile(auth.claims.user_id).await?;
+
+    if user.ok_or(ApiError::Unauthorized)?.rank <= 0 {
+        // TODO define ranks
+        return Err(ApiError::Unauthorized);
+    }
+
+    let success = server.core.approve_post(id).await.context("Error approving post")?;
+
+    if !success {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(())
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn reject_post(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Path(id): Path<i32>,
+) -> Result<(), ApiError> {
+    let user = server.core.get_user_profile(auth.claims.user_id).await?;
+
+    if user.ok_or(ApiError::Unauthorized)?.rank <= 0 {
+        // TODO define ranks
+        return Err(ApiError::Unauthorized);
+    }
+
+    let success = server.core.reject_post(id).await.context("Error rejecting post")?;
+
+    if !success {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// Replace the file of a post, keeping its metadata, tags and comments
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn replace_post_file(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Path(id): Path<i32>,
+    mut multipart: Multipart,
+) -> Result<(), ApiError> {
+    let user = server.core.get_user_profile(auth.claims.user_id).await?;
+
+    if user.ok_or(ApiError::Unauthorized)?.rank <= 0 {
+        // TODO define ranks
+        return Err(ApiError::Unauthorized);
+    }
+
+    let mut file: Option<(HashedFile, String)> = None;
+
+    while let Some(mut field) = multipart
+        .next_field()
+        .await
+        .context("Error getting next multipart field")?
+    {
+        if field.name() == Some("file") {
+            let filename = field
+                .file_name()
+                .ok_or_else(|| anyhow!("Image has no filename."))?
+                .to_string();
+
+            let hashed_file = server.core.hash_stream_to_temp_file(&mut field).await?;
+
+            file = Some((hashed_file, filename));
+        }
+    }
+
+    let (file, filename) = file.ok_or(ApiError::BadRequest)?;
+
+    let success = server
+        .core
+        .replace_post_file(id, file, &filename, auth.claims.user_id)
+        .await
+        .context("Error replacing post file")?;
+
+    if !success {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// Restore the file a post had before the specified file replacement
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn revert_post_file_replacement(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Path((id, replacement_id)): Path<(i32, i32)>,
+) -> Result<(), ApiError> {
+    let user = server.core.get_user_profile(auth.claims.user_id).await?;
+
+    if user.ok_or(ApiError::Unauthorized)?.rank <= 0 {
+        // TODO define ranks
+        return Err(ApiError::Unauthorized);
+    }
+
+    let success = server
+        .core
+        .revert_post_file_replacement(id, replacement_id, auth.claims.user_id)
+        .await
+        .context("Error reverting post file replacement")?;
+
+    if !success {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(())
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn favorite_post(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Path(id): Path<i32>,
+) -> Result<(), ApiError> {
+    let success = server
+        .core
+        .favorite_post(id, auth.claims.user_id)
+        .await
+        .context("Error favoriting post")?;
+
+    if !success {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(())
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn unfavorite_post(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Path(id): Path<i32>,
+) -> Result<(), ApiError> {
+    let success = server
+        .core
+        .unfavorite_post(id, auth.claims.user_id)
+        .await
+        .context("Error unfavoriting post")?;
+
+    if !success {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(())
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn vote_post(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Path(id): Path<i32>,
+    Json(req): Json<vm::PostVote>,
+) -> Result<(), ApiError> {
+    if !(-1..=1).contains(&req.score) {
+        return Err(ApiError::BadRequest);
+    }
+
+    let success = server
+        .core
+        .vote_post(id, auth.claims.user_id, req.score)
+        .await
+        .context("Error voting on post")?;
+
+    if !success {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(())
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn get_popular_posts(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Option<Authorized>,
+    Query(PopularPostsQuery { period, limit }): Query<PopularPostsQuery>,
+) -> Result<Json<Vec<vm::PopularPost>>, ApiError> {
+    if server.config.require_login && auth.is_none() {
+        return Err(ApiError::Unauthorized);
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_POPULAR_POSTS_LIMIT).clamp(1, MAX_POPULAR_POSTS_LIMIT);
+
+    let posts = server
+        .core
+        .get_popular_posts(period, auth.map(|a| a.claims.user_id), limit)
+        .await
+        .context("Error getting popular posts")?;
+
+    Ok(Json(posts))
+}
+
 #[axum::debug_handler(state = Arc<BlazeBooruServer>)]
 async fn get_view_posts(
     State(server): State<Arc<BlazeBooruServer>>,
     auth: Option<Authorized>,
-    Query(PostSearchQuery {
-        include_tags,
-        exclude_tags,
-    }): Query<PostSearchQuery>,
-    Query(PaginatedQuery { start_id, limit }): Query<PaginatedQuery>,
+    Query(search): Query<PostSearchQuery>,
+    Query(PaginatedQuery {
+        start_id,
+        start_key,
+        limit,
+    }): Query<PaginatedQuery>,
 ) -> Result<Json<Vec<vm::Post>>, ApiError> {
     if server.config.require_login && auth.is_none() {
         return Err(ApiError::Unauthorized);
     }
 
+    let query = search.into_filtered_search_query(&server, auth.as_ref()).await?;
+    let start = page_cursor(&query.order, start_id, start_key)?;
+
     let posts = server
         .core
-        .get_view_posts(include_tags, exclude_tags, start_id, limit)
+        .get_view_posts(&query, start, limit)
         .await
         .context("Error getting view posts")?;
 
     Ok(Json(posts))
 }
 
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn get_random_post(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Option<Authorized>,
+    Query(search): Query<PostSearchQuery>,
+) -> Result<Json<vm::Post>, ApiError> {
+    if server.config.require_login && auth.is_none() {
+        return Err(ApiError::Unauthorized);
+    }
+
+    let query = search.into_filtered_search_query(&server, auth.as_ref()).await?;
+
+    let post = server
+        .core
+        .get_random_view_post(&query)
+        .await
+        .context("Error getting random post")?;
+
+    Ok(Json(post.ok_or(ApiError::NotFound)?))
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn get_post_neighbors(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Option<Authorized>,
+    Path(id): Path<i32>,
+    Query(search): Query<PostSearchQuery>,
+) -> Result<Json<vm::PostNeighbors>, ApiError> {
+    if server.config.require_login && auth.is_none() {
+        return Err(ApiError::Unauthorized);
+    }
+
+    let query = search.into_filtered_search_query(&server, auth.as_ref()).await?;
+
+    let neighbors = server
+        .core
+        .get_post_neighbors(&query, id)
+        .await
+        .context("Error getting post neighbors")?;
+
+    Ok(Json(neighbors.ok_or(ApiError::NotFound)?))
+}
+
 #[axum::debug_handler(state = Arc<BlazeBooruServer>)]
 async fn calculate_pages(
     State(server): State<Arc<BlazeBooruServer>>,
-    Query(PostSearchQuery {
-        include_tags,
-        exclude_tags,
-    }): Query<PostSearchQuery>,
+    auth: Option<Authorized>,
+    Query(search): Query<PostSearchQuery>,
     Query(CalculatePageQuery { posts_per_page }): Query<CalculatePageQuery>,
     Query(CalculatePagesQuery {
         page_count,
         origin_page_no,
         origin_page_start_id,
+        origin_page_start_key,
     }): Query<CalculatePagesQuery>,
 ) -> Result<Json<Vec<vm::PageInfo>>, ApiError> {
-    let include_tags = include_tags.iter().map(|t| t.as_str()).collect();
-    let exclude_tags = exclude_tags.iter().map(|t| t.as_str()).collect();
+    let query = search.into_filtered_search_query(&server, auth.as_ref()).await?;
 
-    let origin_page = if let (Some(no), Some(start_id)) = (origin_page_no, origin_page_start_id) {
-        Some(vm::PageInfo { no, start_id })
+    let origin_cursor = page_cursor(&query.order, origin_page_start_id, origin_page_start_key)?;
+    let origin_page = if let (Some(no), Some(cursor)) = (origin_page_no, origin_cursor) {
+        Some(vm::PageInfo {
+            no,
+            start_id: cursor.id,
+            start_key: cursor.key,
+        })
     } else {
         None
     };
 
     let pages = server
         .core
-        .calculate_pages(include_tags, exclude_tags, posts_per_page, page_count, origin_page)
+        .calculate_pages(&query, posts_per_page, page_count, origin_page)
         .await
         .context("Error calculating pages")?;
 
@@ -194,18 +693,15 @@ async fn calculate_pages(
 #[axum::debug_handler(state = Arc<BlazeBooruServer>)]
 async fn calculate_last_page(
     State(server): State<Arc<BlazeBooruServer>>,
-    Query(PostSearchQuery {
-        include_tags,
-        exclude_tags,
-    }): Query<PostSearchQuery>,
+    auth: Option<Authorized>,
+    Query(search): Query<PostSearchQuery>,
     Query(CalculatePageQuery { posts_per_page }): Query<CalculatePageQuery>,
 ) -> Result<Json<vm::PageInfo>, ApiError> {
-    let include_tags = include_tags.iter().map(|t| t.as_str()).collect();
-    let exclude_tags = exclude_tags.iter().map(|t| t.as_str()).collect();
+    let query = search.into_filtered_search_query(&server, auth.as_ref()).await?;
 
     let page = server
         .core
-        .calculate_last_page(include_tags, exclude_tags, posts_per_page)
+        .calculate_last_page(&query, posts_per_page)
         .await
         .context("Error calculating last page")?;
 
@@ -217,7 +713,7 @@ async fn upload_post(
     State(server): State<Arc<BlazeBooruServer>>,
     auth: Authorized,
     mut multipart: Multipart,
-) -> Result<Json<i32>, ApiError> {
+) -> Result<Json<vm::UploadPostResult>, ApiError> {
     let mut info: Option<PostInfo> = None;
     let mut file: Option<(HashedFile, String)> = None;
 
@@ -259,16 +755,257 @@ async fn upload_post(
             filename: filename.into(),
             file,
             tags: info.tags.iter().map(|t| t.as_str()).collect(),
+            rating: info.rating,
         };
 
-        let new_post_id = server.core.create_post(new_post).await.context("Error creating post")?;
+        let result = server.core.create_post(new_post).await.context("Error creating post")?;
 
-        Ok(Json(new_post_id))
+        Ok(Json(result))
     } else {
         Err(ApiError::BadRequest)
     }
 }
 
+/// Upload a post with a file downloaded from a URL.
+/// The URL is used as the source, unless another one is specified.
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn upload_post_from_url(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Json(UrlUploadRequest { url, info }): Json<UrlUploadRequest>,
+) -> Result<Json<vm::UploadPostResult>, ApiError> {
+    let (file, filename) = server.core.hash_url_to_temp_file(&url).await?;
+
+    let new_post = lm::NewPost {
+        user_id: auth.claims.user_id,
+        title: info.title.filter(|v| !v.is_empty()).map(|s| s.into()),
+        description: info.description.filter(|v| !v.is_empty()).map(|s| s.into()),
+        source: Some(info.source.filter(|v| !v.is_empty()).unwrap_or(url).into()),
+        filename: filename.into(),
+        file,
+        tags: info.tags.iter().map(|t| t.as_str()).collect(),
+        rating: info.rating,
+    };
+
+    let result = server.core.create_post(new_post).await.context("Error creating post")?;
+
+    Ok(Json(result))
+}
+
+/// Upload several files at once, each becoming a post.
+/// ZIP and TAR archives are unpacked, with each file in them becoming a post.
+///
+/// The post info of a file is taken from the `file_info` field at the same position as its `file` field,
+/// falling back to the shared `info` field.
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn upload_posts(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    mut multipart: Multipart,
+) -> Result<Json<vm::BatchUploadResult>, ApiError> {
+    let mut shared_info: Option<PostInfo> = None;
+    let mut file_infos: Vec<PostInfo> = Vec::new();
+    let mut files: Vec<(Result<HashedFile, anyhow::Error>, String)> = Vec::new();
+
+    while let Some(mut field) = multipart
+        .next_field()
+        .await
+        .context("Error getting next multipart field")?
+    {
+        let field_name = field.name().ok_or_else(|| anyhow!("Field has no name."))?;
+
+        match field_name {
+            "info" | "file_info" => {
+                let is_shared = field_name == "info";
+                let json = field.text().await.map_err(|err| ApiError::Anyhow(err.into()))?;
+                let info = serde_json::from_str(&json).context("Deserializing post info")?;
+
+                if is_shared {
+                    shared_info = Some(info);
+                } else {
+                    file_infos.push(info);
+                }
+            }
+            "file" => {
+                let filename = field
+                    .file_name()
+                    .ok_or_else(|| anyhow!("Image has no filename."))?
+                    .to_string();
+
+                // Archives are limited by the size of their contents when extracted
+                let hashed_file = if ArchiveKind::from_filename(&filename).is_some() {
+                    Ok(server.core.hash_stream_to_temp_file(&mut field).await?)
+                } else {
+                    match server
+                        .core
+                        .hash_stream_to_temp_file_with_limit(&mut field, server.core.max_image_size)
+                        .await
+                    {
+                        Ok(file) => Ok(file),
+                        Err(err) if err.is::<FileTooLargeError>() => Err(err),
+                        Err(err) => return Err(err.into()),
+                    }
+                };
+
+                files.push((hashed_file, filename));
+            }
+            _ => {}
+        }
+    }
+
+    if files.is_empty() {
+        return Err(ApiError::BadRequest);
+    }
+
+    let shared_info = shared_info.unwrap_or_default();
+
+    let mut results = Vec::new();
+    for (i, (file, filename)) in files.into_iter().enumerate() {
+        let info = file_infos.get(i).unwrap_or(&shared_info);
+
+        let Some(kind) = ArchiveKind::from_filename(&filename) else {
+            let status = upload_batch_file(&server, &auth, info, &filename, file).await;
+            results.push(vm::BatchUploadFileResult {
+                filename,
+                archive: None,
+                status,
+            });
+
+            continue;
+        };
+
+        let extracted_files = match file {
+            Ok(file) => server.core.extract_archive_to_temp_files(kind, file).await,
+            Err(err) => Err(err),
+        };
+
+        match extracted_files {
+            Ok(extracted_files) => {
+                for ExtractedFile {
+                    filename: entry_filename,
+                    file,
+                } in extracted_files
+                {
+                    let status = upload_batch_file(&server, &auth, info, &entry_filename, file).await;
+                    results.push(vm::BatchUploadFileResult {
+                        filename: entry_filename,
+                        archive: Some(filename.clone()),
+                        status,
+                    });
+                }
+            }
+            Err(err) => results.push(vm::BatchUploadFileResult {
+                filename,
+                archive: None,
+                status: vm::BatchUploadFileStatus::Error {
+                    message: format!("{err:#}"),
+                },
+            }),
+        }
+    }
+
+    Ok(Json(vm::BatchUploadResult { files: results }))
+}
+
+async fn upload_batch_file(
+    server: &BlazeBooruServer,
+    auth: &Authorized,
+    info: &PostInfo,
+    filename: &str,
+    file: Result<HashedFile, anyhow::Error>,
+) -> vm::BatchUploadFileStatus {
+    let result = match file {
+        Ok(file) => {
+            let new_post = lm::NewPost {
+                user_id: auth.claims.user_id,
+                title: info.title.as_deref().filter(|v| !v.is_empty()).map(|s| s.into()),
+                description: info.description.as_deref().filter(|v| !v.is_empty()).map(|s| s.into()),
+                source: info.source.as_deref().filter(|v| !v.is_empty()).map(|s| s.into()),
+                filename: filename.into(),
+                file,
+                tags: info.tags.iter().map(|t| t.as_str()).collect(),
+                rating: info.rating,
+            };
+
+            server.core.create_post(new_post).await
+        }
+        Err(err) => Err(err),
+    };
+
+    match result {
+        Ok(result) => vm::BatchUploadFileStatus::Created(result),
+        Err(err) => match err.downcast_ref::<DuplicatePostError>() {
+            Some(DuplicatePostError(id)) => vm::BatchUploadFileStatus::Duplicate { id: *id },
+            None => vm::BatchUploadFileStatus::Error {
+                message: format!("{err:#}"),
+            },
+        },
+    }
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn get_similar_posts(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Option<Authorized>,
+    Path(id): Path<i32>,
+    Query(SimilarPostsQuery { distance }): Query<SimilarPostsQuery>,
+) -> Result<Json<Vec<vm::SimilarPost>>, ApiError> {
+    if server.config.require_login && auth.is_none() {
+        return Err(ApiError::Unauthorized);
+    }
+
+    let posts = server
+        .core
+        .get_similar_posts(id, distance.map(|d| d.min(MAX_SIMILAR_POST_DISTANCE)))
+        .await
+        .context("Error getting similar posts")?;
+
+    Ok(Json(posts))
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn find_similar_posts(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Option<Authorized>,
+    Query(SimilarPostsQuery { distance }): Query<SimilarPostsQuery>,
+    mut multipart: Multipart,
+) -> Result<Json<Vec<vm::SimilarPost>>, ApiError> {
+    if server.config.require_login && auth.is_none() {
+        return Err(ApiError::Unauthorized);
+    }
+
+    while let Some(mut field) = multipart
+        .next_field()
+        .await
+        .context("Error getting next multipart field")?
+    {
+        if field.name() != Some("file") {
+            continue;
+        }
+
+        let filename = field
+            .file_name()
+            .ok_or_else(|| anyhow!("Image has no filename."))?
+            .to_string();
+
+        let hashed_file = server.core.hash_stream_to_temp_file(&mut field).await?;
+
+        let posts = server
+            .core
+            .find_similar_posts_by_file(
+                hashed_file,
+                &filename,
+                distance.map(|d| d.min(MAX_SIMILAR_POST_DISTANCE)),
+            )
+            .await
+            .context("Error finding similar posts")?;
+
+        return Ok(Json(posts));
+    }
+
+    Err(ApiError::BadRequest)
+}
+
 #[axum::debug_handler(state = Arc<BlazeBooruServer>)]
 async fn get_post_comments(
     State(server): State<Arc<BlazeBooruServer>>,
@@ -298,3 +1035,72 @@ async fn post_comment(
 
     Ok(Json(comment))
 }
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn get_post_notes(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Option<Authorized>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<vm::PostNote>>, ApiError> {
+    if server.config.require_login && auth.is_none() {
+        return Err(ApiError::Unauthorized);
+    }
+
+    let notes = server
+        .core
+        .get_post_notes(id)
+        .await
+        .context("Error getting post notes")?;
+
+    Ok(Json(notes))
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn create_post_note(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Path(id): Path<i32>,
+    Json(req): Json<vm::PostNoteContent>,
+) -> Result<Json<vm::PostNote>, ApiError> {
+    if !req.is_valid() {
+        return Err(ApiError::BadRequest);
+    }
+
+    let note_id = server
+        .core
+        .create_post_note(id, req, auth.claims.user_id)
+        .await
+        .context("Error creating post note")?
+        .ok_or(ApiError::NotFound)?;
+
+    let note = server
+        .core
+        .get_post_note(note_id)
+        .await
+        .context("Error getting post note")?;
+
+    Ok(Json(note.ok_or(ApiError::NotFound)?))
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn get_search_tags(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Option<Authorized>,
+    Query(search): Query<PostSearchQuery>,
+    Query(SearchTagsQuery { limit }): Query<SearchTagsQuery>,
+) -> Result<Json<Vec<vm::SearchTag>>, ApiError> {
+    if server.config.require_login && auth.is_none() {
+        return Err(ApiError::Unauthorized);
+    }
+
+    let query = search.into_filtered_search_query(&server, auth.as_ref()).await?;
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_TAGS_LIMIT).clamp(1, MAX_SEARCH_TAGS_LIMIT);
+
+    let tags = server
+        .core
+        .get_search_tags(&query, limit)
+        .await
+        .context("Error getting search tags")?;
+
+    Ok(Json(tags))
+}
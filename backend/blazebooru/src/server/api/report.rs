@@ -0,0 +1,143 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::extract::{Path, Query, State};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+
+use blazebooru_models::view as vm;
+
+use crate::server::api::Authorized;
+use crate::server::{ApiError, BlazeBooruServer};
+
+const DEFAULT_REPORTS_LIMIT: i32 = 50;
+const MAX_REPORTS_LIMIT: i32 = 200;
+
+#[derive(Deserialize)]
+struct ReportsQuery {
+    #[serde(default)]
+    status: vm::ReportStatus,
+    #[serde(rename = "sid")]
+    start_id: Option<i32>,
+    limit: Option<i32>,
+}
+
+pub fn router() -> Router<Arc<BlazeBooruServer>> {
+    Router::new()
+        .route("/", get(get_reports))
+        .route("/new", post(create_report))
+        .route("/{id}", get(get_report))
+        .route("/{id}/resolve", post(resolve_report))
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn create_report(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Json(req): Json<vm::NewReport>,
+) -> Result<Json<vm::Report>, ApiError> {
+    if req.reason.trim().is_empty() {
+        return Err(ApiError::BadRequest);
+    }
+
+    let id = server
+        .core
+        .create_report(req, auth.claims.user_id)
+        .await
+        .context("Error creating report")?
+        .ok_or(ApiError::NotFound)?;
+
+    let report = server.core.get_report(id).await.context("Error getting report")?;
+
+    Ok(Json(report.ok_or(ApiError::NotFound)?))
+}
+
+/// Get the moderation queue, open reports by default
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn get_reports(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Query(query): Query<ReportsQuery>,
+) -> Result<Json<Vec<vm::Report>>, ApiError> {
+    let user = server.core.get_user_profile(auth.claims.user_id).await?;
+
+    if user.ok_or(ApiError::Unauthorized)?.rank <= 0 {
+        // TODO define ranks
+        return Err(ApiError::Unauthorized);
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_REPORTS_LIMIT).clamp(1, MAX_REPORTS_LIMIT);
+
+    let reports = server
+        .core
+        .get_reports(query.status, query.start_id, limit)
+        .await
+        .context("Error getting reports")?;
+
+    Ok(Json(reports))
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn get_report(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Path(id): Path<i32>,
+) -> Result<Json<vm::Report>, ApiError> {
+    let user = server.core.get_user_profile(auth.claims.user_id).await?;
+
+    if user.ok_or(ApiError::Unauthorized)?.rank <= 0 {
+        // TODO define ranks
+        return Err(ApiError::Unauthorized);
+    }
+
+    let report = server.core.get_report(id).await.context("Error getting report")?;
+
+    Ok(Json(report.ok_or(ApiError::NotFound)?))
+}
+
+/// Resolve or dismiss an open report, optionally deleting the reported item in the same action
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn resolve_report(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Path(id): Path<i32>,
+    Json(req): Json<vm::ResolveReport>,
+) -> Result<(), ApiError> {
+    let user = server.core.get_user_profile(auth.claims.user_id).await?;
+
+    if user.ok_or(ApiError::Unauthorized)?.rank <= 0 {
+        // TODO define ranks
+        return Err(ApiError::Unauthorized);
+    }
+
+    if req.status == vm::ReportStatus::Open {
+        return Err(ApiError::BadRequest);
+    }
+
+    let report = server
+        .core
+        .get_report(id)
+        .await
+        .context("Error getting report")?
+        .ok_or(ApiError::NotFound)?;
+
+    // Only the deletion of posts, comments and wiki pages is supported, and only when resolving
+    if req.delete_target
+        && (req.status != vm::ReportStatus::Resolved || report.target_type == vm::ReportTarget::User)
+    {
+        return Err(ApiError::BadRequest);
+    }
+
+    let success = server
+        .core
+        .resolve_report(id, req, auth.claims.user_id)
+        .await
+        .context("Error resolving report")?;
+
+    if !success {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(())
+}
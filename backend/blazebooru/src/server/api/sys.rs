@@ -16,8 +16,10 @@ pub fn router() -> Router<Arc<BlazeBooruServer>> {
 async fn get_config(State(server): State<Arc<BlazeBooruServer>>) -> Result<Json<vm::Config>, ApiError> {
     let config = vm::Config {
         max_image_size: server.config.max_image_size,
+        max_batch_upload_size: server.config.max_batch_upload_size,
         require_login: server.config.require_login,
         allow_registration: server.config.allow_registration,
+        renditions: server.core.renditions.clone(),
     };
 
     Ok(Json(config))
@@ -4,18 +4,47 @@ use anyhow::Context;
 use axum::Json;
 use axum::Router;
 use axum::extract::Path;
+use axum::extract::Query;
 use axum::extract::State;
 use axum::routing::{get, post};
+use serde::Deserialize;
 
+use blazebooru_core::search::build_search_query;
 use blazebooru_models::view as vm;
 
 use crate::server::ApiError;
 use crate::server::BlazeBooruServer;
 use crate::server::api::Authorized;
 
+const DEFAULT_AUTOCOMPLETE_LIMIT: i32 = 10;
+const MAX_AUTOCOMPLETE_LIMIT: i32 = 100;
+const DEFAULT_TAG_CHANGES_LIMIT: i32 = 50;
+const MAX_TAG_CHANGES_LIMIT: i32 = 200;
+
+#[derive(Deserialize)]
+struct AutocompleteQuery {
+    q: String,
+    limit: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct TagChangesQuery {
+    post_id: Option<i32>,
+    user_id: Option<i32>,
+    #[serde(rename = "sid")]
+    start_id: Option<i32>,
+    limit: Option<i32>,
+}
+
 pub fn router() -> Router<Arc<BlazeBooruServer>> {
     Router::new()
         .route("/", get(get_view_tags))
+        .route("/autocomplete", get(autocomplete_tags))
+        .route("/changes", get(get_tag_changes))
+        .route("/changes/revert", post(revert_user_tag_changes))
+        .route("/changes/{id}/revert", post(revert_tag_change))
+        .route("/edit", post(create_tag_edit_job))
+        .route("/edit/{id}", get(get_tag_edit_job))
         .route("/{id}", get(get_view_tag))
         .route("/{id}/update", post(update_tag))
 }
@@ -37,6 +66,24 @@ async fn get_view_tags(State(server): State<Arc<BlazeBooruServer>>) -> Result<Js
     Ok(Json(tags))
 }
 
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn autocomplete_tags(
+    State(server): State<Arc<BlazeBooruServer>>,
+    Query(AutocompleteQuery { q, limit }): Query<AutocompleteQuery>,
+) -> Result<Json<Vec<vm::AutocompleteTag>>, ApiError> {
+    let limit = limit
+        .unwrap_or(DEFAULT_AUTOCOMPLETE_LIMIT)
+        .clamp(1, MAX_AUTOCOMPLETE_LIMIT);
+
+    let tags = server
+        .core
+        .autocomplete_tags(&q, limit)
+        .await
+        .context("Error getting autocomplete tags")?;
+
+    Ok(Json(tags))
+}
+
 #[axum::debug_handler(state = Arc<BlazeBooruServer>)]
 async fn update_tag(
     State(server): State<Arc<BlazeBooruServer>>,
@@ -56,3 +103,143 @@ async fn update_tag(
 
     Ok(())
 }
+
+/// List tag changes, optionally only of a post and/or user, newest first
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn get_tag_changes(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Option<Authorized>,
+    Query(TagChangesQuery {
+        post_id,
+        user_id,
+        start_id,
+        limit,
+    }): Query<TagChangesQuery>,
+) -> Result<Json<Vec<vm::PostTagChange>>, ApiError> {
+    if server.config.require_login && auth.is_none() {
+        return Err(ApiError::Unauthorized);
+    }
+
+    let limit = limit
+        .unwrap_or(DEFAULT_TAG_CHANGES_LIMIT)
+        .clamp(1, MAX_TAG_CHANGES_LIMIT);
+
+    let changes = server
+        .core
+        .get_tag_changes(post_id, user_id, start_id, limit)
+        .await
+        .context("Error getting tag changes")?;
+
+    Ok(Json(changes))
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn revert_tag_change(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Path(id): Path<i32>,
+) -> Result<(), ApiError> {
+    let user = server.core.get_user_profile(auth.claims.user_id).await?;
+
+    if user.ok_or(ApiError::Unauthorized)?.rank <= 0 {
+        // TODO define ranks
+        return Err(ApiError::Unauthorized);
+    }
+
+    let success = server
+        .core
+        .revert_tag_change(id, auth.claims.user_id)
+        .await
+        .context("Error reverting tag change")?;
+
+    if !success {
+        return Err(ApiError::NotFound);
+    }
+
+    Ok(())
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn revert_user_tag_changes(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Json(req): Json<vm::RevertUserTagChanges>,
+) -> Result<Json<vm::RevertTagChangesResult>, ApiError> {
+    let user = server.core.get_user_profile(auth.claims.user_id).await?;
+
+    if user.ok_or(ApiError::Unauthorized)?.rank <= 0 {
+        // TODO define ranks
+        return Err(ApiError::Unauthorized);
+    }
+
+    let result = server
+        .core
+        .revert_user_tag_changes(req, auth.claims.user_id)
+        .await
+        .context("Error reverting user tag changes")?;
+
+    Ok(Json(result))
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn create_tag_edit_job(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Json(req): Json<vm::NewTagEditJob>,
+) -> Result<Json<vm::TagEditJob>, ApiError> {
+    let user = server.core.get_user_profile(auth.claims.user_id).await?;
+
+    if user.ok_or(ApiError::Unauthorized)?.rank <= 0 {
+        // TODO define ranks
+        return Err(ApiError::Unauthorized);
+    }
+
+    if req.add_tags.is_empty() && req.remove_tags.is_empty() {
+        return Err(ApiError::BadRequest);
+    }
+
+    // A job without any search tags would edit every post
+    if req.include_tags.is_empty() && req.exclude_tags.is_empty() {
+        return Err(ApiError::BadRequest);
+    }
+
+    let query = build_search_query(req.include_tags.clone(), req.exclude_tags.clone(), None)?;
+
+    let id = server
+        .core
+        .create_tag_edit_job(&query, req, auth.claims.user_id)
+        .await
+        .context("Error creating tag edit job")?;
+
+    server.spawn_tag_edit_job(id);
+
+    let job = server
+        .core
+        .get_tag_edit_job(id)
+        .await
+        .context("Error getting tag edit job")?;
+
+    Ok(Json(job.ok_or(ApiError::NotFound)?))
+}
+
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn get_tag_edit_job(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Authorized,
+    Path(id): Path<i32>,
+) -> Result<Json<vm::TagEditJob>, ApiError> {
+    let user = server.core.get_user_profile(auth.claims.user_id).await?;
+
+    if user.ok_or(ApiError::Unauthorized)?.rank <= 0 {
+        // TODO define ranks
+        return Err(ApiError::Unauthorized);
+    }
+
+    let job = server
+        .core
+        .get_tag_edit_job(id)
+        .await
+        .context("Error getting tag edit job")?;
+
+    Ok(Json(job.ok_or(ApiError::NotFound)?))
+}
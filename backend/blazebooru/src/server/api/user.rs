@@ -7,7 +7,9 @@ use axum::{Json, Router};
 use axum_client_ip::ClientIp;
 use serde::Deserialize;
 
+use blazebooru_core::search::page_cursor;
 use blazebooru_models::local as lm;
+use blazebooru_models::search as sm;
 use blazebooru_models::view as vm;
 
 use crate::auth::{AuthClaims, JwtClaims, SessionClaims};
@@ -26,9 +28,19 @@ struct PublicProfileUserRequest {
     name: String
 }
 
+#[derive(Deserialize)]
+struct FavoritesQuery {
+    #[serde(rename = "sid")]
+    start_id: Option<i32>,
+    #[serde(rename = "skey")]
+    start_key: Option<i64>,
+    limit: i32,
+}
+
 pub fn router() -> Router<Arc<BlazeBooruServer>> {
     Router::new()
         .route("/{id}", post(update_user).delete(delete_user))
+        .route("/{id}/favorites", get(get_user_favorites))
         .route("/profile", get(get_user_profile))
         .route("/pubprofile", get(get_public_user_profile))
         .route("/register", post(register_user))
@@ -119,6 +131,32 @@ async fn get_public_user_profile(
     Ok(Json(user.ok_or(ApiError::NotFound)?))
 }
 
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn get_user_favorites(
+    State(server): State<Arc<BlazeBooruServer>>,
+    auth: Option<Authorized>,
+    Path(id): Path<i32>,
+    Query(FavoritesQuery {
+        start_id,
+        start_key,
+        limit,
+    }): Query<FavoritesQuery>,
+) -> Result<Json<Vec<vm::Post>>, ApiError> {
+    if server.config.require_login && auth.is_none() {
+        return Err(ApiError::Unauthorized);
+    }
+
+    let start = page_cursor(&sm::Order::default(), start_id, start_key)?;
+
+    let posts = server
+        .core
+        .get_user_favorites(id, auth.map(|a| a.claims.user_id), start, limit)
+        .await
+        .context("Error getting user favorites")?;
+
+    Ok(Json(posts.ok_or(ApiError::NotFound)?))
+}
+
 #[axum::debug_handler(state = Arc<BlazeBooruServer>)]
 async fn get_all_users(
     State(server): State<Arc<BlazeBooruServer>>
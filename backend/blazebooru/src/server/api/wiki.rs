@@ -1,20 +1,31 @@
 use std::sync::Arc;
 
 use anyhow::Context;
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use axum::routing::{get, post};
 use axum::{Json, Router};
 use axum_client_ip::ClientIp;
+use serde::Deserialize;
 
 use blazebooru_models::view as vm;
 
 use crate::server::api::Authorized;
 use crate::server::{ApiError, BlazeBooruServer};
 
+const DEFAULT_SEARCH_LIMIT: i32 = 50;
+const MAX_SEARCH_LIMIT: i32 = 100;
+
+#[derive(Deserialize)]
+struct WikiSearchQuery {
+    q: String,
+    limit: Option<i32>,
+}
+
 pub fn router() -> Router<Arc<BlazeBooruServer>> {
     Router::new()
         .route("/n/{name}", get(get_wiki_page).post(update_wiki_page).delete(delete_wiki_page))
         .route("/list", get(list_wiki_pages))
+        .route("/search", get(search_wiki_pages))
         .route("/new", post(create_wiki_page))
 }
 
@@ -61,6 +72,22 @@ async fn list_wiki_pages(
     Ok(Json(pages))
 }
 
+#[axum::debug_handler(state = Arc<BlazeBooruServer>)]
+async fn search_wiki_pages(
+    State(server): State<Arc<BlazeBooruServer>>,
+    Query(WikiSearchQuery { q, limit }): Query<WikiSearchQuery>,
+) -> Result<Json<Vec<vm::WikiPage>>, ApiError> {
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT);
+
+    let pages = server
+        .core
+        .search_wiki_pages(&q, limit)
+        .await
+        .context("Error searching wiki pages")?;
+
+    Ok(Json(pages))
+}
+
 #[axum::debug_handler(state = Arc<BlazeBooruServer>)]
 async fn update_wiki_page(
     State(server): State<Arc<BlazeBooruServer>>,
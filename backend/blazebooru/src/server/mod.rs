@@ -12,7 +12,9 @@ use tokio::net::TcpListener;
 use tower_http::services::ServeDir;
 use tracing::{error, info};
 
-use blazebooru_core::{BlazeBooruCore, config::BlazeBooruConfig};
+use blazebooru_core::{
+    BlazeBooruCore, PostParentError, config::BlazeBooruConfig, fetch::UrlUploadError, search::SearchQueryError,
+};
 
 use crate::auth::{AuthError, BlazeBooruAuth};
 
@@ -29,6 +31,12 @@ enum ApiError {
     Anyhow(#[from] anyhow::Error),
     #[error(transparent)]
     AuthError(#[from] AuthError),
+    #[error(transparent)]
+    SearchQueryError(#[from] SearchQueryError),
+    #[error(transparent)]
+    UrlUploadError(#[from] UrlUploadError),
+    #[error(transparent)]
+    PostParentError(#[from] PostParentError),
     #[error("Bad request")]
     BadRequest,
     #[error("Not found")]
@@ -37,6 +45,8 @@ enum ApiError {
     Unauthorized,
     #[error("Forbidden")]
     Forbidden,
+    #[error("Conflict")]
+    Conflict,
 }
 
 impl BlazeBooruServer {
@@ -45,6 +55,12 @@ impl BlazeBooruServer {
 
         let server = Arc::new(self);
 
+        // Resume tag edit jobs interrupted by a restart
+        for id in server.core.get_running_tag_edit_job_ids().await? {
+            info!("Resuming tag edit job {id}");
+            server.spawn_tag_edit_job(id);
+        }
+
         let mut app = Router::new().nest("/api", api);
 
         // If file serving is enabled, serve public files under /f.
@@ -68,6 +84,17 @@ impl BlazeBooruServer {
 
         Ok(())
     }
+
+    /// Run a tag edit job in the background. Progress can be followed through the job,
+    /// and a job interrupted by a shutdown is resumed on the next start.
+    fn spawn_tag_edit_job(self: &Arc<Self>, id: i32) {
+        let server = self.clone();
+        tokio::spawn(async move {
+            if let Err(err) = server.core.run_tag_edit_job(id).await {
+                error!("Error running tag edit job {id}: {err:#}");
+            }
+        });
+    }
 }
 
 impl IntoResponse for ApiError {
@@ -79,10 +106,17 @@ impl IntoResponse for ApiError {
             }
             Self::AuthError(AuthError::ExpiredToken) => (StatusCode::UNAUTHORIZED, ()).into_response(),
             Self::AuthError(err) => (StatusCode::BAD_REQUEST, format!("{err:#}")).into_response(),
+            Self::SearchQueryError(err) => (StatusCode::BAD_REQUEST, format!("{err:#}")).into_response(),
+            Self::UrlUploadError(err @ UrlUploadError::Fetch(_)) => {
+                (StatusCode::BAD_GATEWAY, format!("{err:#}")).into_response()
+            }
+            Self::UrlUploadError(err) => (StatusCode::BAD_REQUEST, format!("{err:#}")).into_response(),
+            Self::PostParentError(err) => (StatusCode::BAD_REQUEST, format!("{err:#}")).into_response(),
             Self::BadRequest => (StatusCode::BAD_REQUEST, ()).into_response(),
             Self::NotFound => (StatusCode::NOT_FOUND, ()).into_response(),
             Self::Unauthorized => (StatusCode::UNAUTHORIZED, ()).into_response(),
             Self::Forbidden => (StatusCode::FORBIDDEN, ()).into_response(),
+            Self::Conflict => (StatusCode::CONFLICT, ()).into_response(),
         }
     }
 }
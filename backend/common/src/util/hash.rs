@@ -54,10 +54,11 @@ pub async fn hash_blake3_to_file_from_file(src_path: &Path, dst_path: &Path) ->
 }
 
 /// Read bytes from stream, calculate hash and write to a file
-pub async fn hash_blake3_to_file_from_stream<S: Stream<Item = Result<Bytes, E>> + Unpin, E>(
-    stream: &mut S,
-    path: &Path,
-) -> Result<HashResult, anyhow::Error> {
+pub async fn hash_blake3_to_file_from_stream<S, E>(stream: &mut S, path: &Path) -> Result<HashResult, anyhow::Error>
+where
+    S: Stream<Item = Result<Bytes, E>> + Unpin,
+    E: std::error::Error + Send + Sync + 'static,
+{
     let mut file = fs::File::create(path)
         .await
         .with_context(|| format!("Opening file for writing: {}", path.display()))?;
@@ -67,8 +68,8 @@ pub async fn hash_blake3_to_file_from_stream<S: Stream<Item = Result<Bytes, E>>
     let mut buf = [0u8; BUFFER_SIZE];
 
     let mut total_size = 0;
-    while let Some(Ok(bytes)) = stream.next().await {
-        let mut reader = bytes.reader();
+    while let Some(bytes) = stream.next().await {
+        let mut reader = bytes.context("Error reading stream")?.reader();
 
         loop {
             let bytes = reader.read(&mut buf)?;
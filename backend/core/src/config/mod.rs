@@ -9,6 +9,7 @@ use serde_derive::Deserialize;
 use tracing::error;
 
 use blazebooru_common::util;
+use blazebooru_models::view as vm;
 
 pub const CONFIG_DIR_NAME: &str = "blazebooru";
 pub const CONFIG_FILENAME: &str = "config.toml";
@@ -16,14 +17,27 @@ pub const CONFIG_FILENAME: &str = "config.toml";
 pub const DEFAULT_CONFIG: &str = include_str!("default_config.toml");
 
 const DEFAULT_MAX_IMAGE_SIZE: usize = 10_000_000; // 10MB
+const DEFAULT_MAX_BATCH_UPLOAD_SIZE: usize = 500_000_000; // 500MB
 const DEFAULT_REQUIRE_LOGIN: bool = false;
 const DEFAULT_ALLOW_REGISTRATION: bool = true;
+const DEFAULT_SIMILAR_POST_DISTANCE: u32 = 6;
+const DEFAULT_REJECT_SIMILAR_POSTS: bool = false;
+const DEFAULT_ANONYMOUS_MAX_RATING: vm::Rating = vm::Rating::Explicit;
+const DEFAULT_URL_UPLOAD_TIMEOUT: u64 = 30;
+const DEFAULT_URL_UPLOAD_DENIED_HOSTS: &[&str] = &["localhost", "127.0.0.1", "[::1]"];
+const DEFAULT_REQUIRE_UPLOAD_APPROVAL: bool = false;
+const DEFAULT_UPLOAD_APPROVAL_RANK: i16 = 1;
+const DEFAULT_THUMBNAIL_SIZE: u32 = 200;
 
 // Workaround for serde not supporting specifying default values directly
 fn default_max_image_size() -> usize {
     DEFAULT_MAX_IMAGE_SIZE
 }
 
+fn default_max_batch_upload_size() -> usize {
+    DEFAULT_MAX_BATCH_UPLOAD_SIZE
+}
+
 fn default_require_login() -> bool {
     DEFAULT_REQUIRE_LOGIN
 }
@@ -32,6 +46,42 @@ fn default_allow_registration() -> bool {
     DEFAULT_ALLOW_REGISTRATION
 }
 
+fn default_similar_post_distance() -> u32 {
+    DEFAULT_SIMILAR_POST_DISTANCE
+}
+
+fn default_reject_similar_posts() -> bool {
+    DEFAULT_REJECT_SIMILAR_POSTS
+}
+
+fn default_anonymous_max_rating() -> vm::Rating {
+    DEFAULT_ANONYMOUS_MAX_RATING
+}
+
+fn default_url_upload_timeout() -> u64 {
+    DEFAULT_URL_UPLOAD_TIMEOUT
+}
+
+fn default_url_upload_denied_hosts() -> Vec<String> {
+    DEFAULT_URL_UPLOAD_DENIED_HOSTS.iter().map(|h| h.to_string()).collect()
+}
+
+fn default_require_upload_approval() -> bool {
+    DEFAULT_REQUIRE_UPLOAD_APPROVAL
+}
+
+fn default_upload_approval_rank() -> i16 {
+    DEFAULT_UPLOAD_APPROVAL_RANK
+}
+
+fn default_renditions() -> Vec<vm::Rendition> {
+    vec![vm::Rendition {
+        name: "t".to_string(),
+        size: DEFAULT_THUMBNAIL_SIZE,
+        sample: false,
+    }]
+}
+
 #[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct BlazeBooruConfig {
@@ -43,11 +93,52 @@ pub struct BlazeBooruConfig {
     #[serde(default = "default_max_image_size")]
     pub max_image_size: usize,
 
+    /// Maximum total size of a batch upload request, including archives
+    #[serde(default = "default_max_batch_upload_size")]
+    pub max_batch_upload_size: usize,
+
     #[serde(default = "default_require_login")]
     pub require_login: bool,
 
     #[serde(default = "default_allow_registration")]
     pub allow_registration: bool,
+
+    /// Maximum perceptual hash distance (in bits) for posts to be considered similar
+    #[serde(default = "default_similar_post_distance")]
+    pub similar_post_distance: u32,
+
+    /// Reject uploads that are similar to existing posts, instead of only warning
+    #[serde(default = "default_reject_similar_posts")]
+    pub reject_similar_posts: bool,
+
+    /// Highest content rating shown to anonymous users, unless searched for explicitly
+    #[serde(default = "default_anonymous_max_rating")]
+    pub anonymous_max_rating: vm::Rating,
+
+    /// Timeout (in seconds) for downloading a file uploaded by URL
+    #[serde(default = "default_url_upload_timeout")]
+    pub url_upload_timeout: u64,
+
+    /// Hosts that files can be uploaded from by URL, including their subdomains. All hosts are allowed if empty.
+    #[serde(default)]
+    pub url_upload_allowed_hosts: Vec<String>,
+
+    /// Hosts that files can not be uploaded from by URL, including their subdomains.
+    /// Hosts resolving to loopback, private or other internal addresses are always denied.
+    #[serde(default = "default_url_upload_denied_hosts")]
+    pub url_upload_denied_hosts: Vec<String>,
+
+    /// Hide new posts until they are approved, unless uploaded by a user with at least `upload_approval_rank`
+    #[serde(default = "default_require_upload_approval")]
+    pub require_upload_approval: bool,
+
+    /// Lowest user rank whose uploads are public without approval
+    #[serde(default = "default_upload_approval_rank")]
+    pub upload_approval_rank: i16,
+
+    /// Resized renditions generated for post files. The `t` rendition is used as the thumbnail.
+    #[serde(default = "default_renditions")]
+    pub renditions: Vec<vm::Rendition>,
 }
 
 impl BlazeBooruConfig {
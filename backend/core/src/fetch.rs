@@ -0,0 +1,489 @@
+//! Fetching of remote files for uploads by URL.
+//!
+//! Files are fetched through a [`UrlFetcher`], which can be replaced
+//! with [`BlazeBooruCore::set_url_fetcher`], for example to serve files locally.
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use bytes::Bytes;
+use futures_util::future::BoxFuture;
+use futures_util::stream::BoxStream;
+use futures_util::{FutureExt, StreamExt, TryStreamExt};
+use reqwest::Url;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::redirect;
+use thiserror::Error;
+use url::Host;
+
+use blazebooru_models::local::HashedFile;
+
+use crate::file::FileTooLargeError;
+
+use super::BlazeBooruCore;
+
+/// Maximum number of redirects followed when fetching a file
+const MAX_REDIRECTS: usize = 5;
+
+#[derive(Debug, Error)]
+pub enum UrlUploadError {
+    #[error("Invalid URL: '{0}'")]
+    InvalidUrl(String),
+    #[error("Unsupported URL scheme: '{0}'")]
+    UnsupportedScheme(String),
+    #[error("Uploading from host is not allowed: '{0}'")]
+    HostNotAllowed(String),
+    #[error("File is larger than the maximum size of {0} bytes")]
+    TooLarge(usize),
+    #[error("Could not determine the file type")]
+    UnknownFileType,
+    #[error("Error fetching file: {0:#}")]
+    Fetch(anyhow::Error),
+}
+
+/// Response of a fetched URL, with the body yet to be read
+pub struct FetchedFile {
+    /// Size of the body, if announced by the server
+    pub content_length: Option<u64>,
+    pub content_type: Option<String>,
+    pub body: BoxStream<'static, Result<Bytes, io::Error>>,
+}
+
+pub trait UrlFetcher: Send + Sync {
+    fn fetch<'a>(&'a self, url: &'a Url) -> BoxFuture<'a, Result<FetchedFile, anyhow::Error>>;
+}
+
+/// Hosts that files may be fetched from
+#[derive(Debug, Clone)]
+pub struct HostFilter {
+    allowed_hosts: Arc<[String]>,
+    denied_hosts: Arc<[String]>,
+}
+
+impl HostFilter {
+    pub fn new(allowed_hosts: &[String], denied_hosts: &[String]) -> Self {
+        let normalize = |hosts: &[String]| hosts.iter().map(|h| h.trim().to_lowercase()).collect();
+
+        Self {
+            allowed_hosts: normalize(allowed_hosts),
+            denied_hosts: normalize(denied_hosts),
+        }
+    }
+
+    /// Check whether the host of a URL is allowed.
+    /// IP addresses must also be publicly routable, host names are checked once resolved.
+    pub fn is_url_allowed(&self, url: &Url) -> bool {
+        let is_ip_allowed = match url.host() {
+            Some(Host::Ipv4(ip)) => is_public_ip(ip.into()),
+            Some(Host::Ipv6(ip)) => is_public_ip(ip.into()),
+            Some(Host::Domain(_)) => true,
+            None => false,
+        };
+
+        is_ip_allowed && url.host_str().is_some_and(|host| self.is_allowed(host))
+    }
+
+    /// Check whether a host is allowed. Entries match the host and its subdomains.
+    pub fn is_allowed(&self, host: &str) -> bool {
+        let host = host.to_lowercase();
+        let matches = |entry: &String| host == *entry || host.ends_with(&format!(".{entry}"));
+
+        if self.denied_hosts.iter().any(matches) {
+            return false;
+        }
+
+        self.allowed_hosts.is_empty() || self.allowed_hosts.iter().any(matches)
+    }
+}
+
+/// Check whether an IP address is publicly routable, to prevent fetching from internal services
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => is_public_ipv6(ip),
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        // "This network" (0.0.0.0/8)
+        || a == 0
+        // Shared address space used by carrier-grade NAT (100.64.0.0/10)
+        || (a == 100 && (b & 0xc0) == 64)
+        // Benchmarking (198.18.0.0/15)
+        || (a == 198 && (b & 0xfe) == 18)
+        // Reserved (240.0.0.0/4)
+        || a >= 240)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    let [s0, s1, ..] = ip.segments();
+
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // IPv4-mapped and IPv4-compatible addresses (::ffff:0:0/96 and ::/96)
+        || ip.to_ipv4().is_some()
+        // NAT64 (64:ff9b::/96)
+        || (s0 == 0x64 && s1 == 0xff9b)
+        // Unique local (fc00::/7)
+        || (s0 & 0xfe00) == 0xfc00
+        // Link-local (fe80::/10)
+        || (s0 & 0xffc0) == 0xfe80
+        // Documentation (2001:db8::/32)
+        || (s0 == 0x2001 && s1 == 0xdb8))
+}
+
+/// DNS resolver only returning publicly routable addresses.
+/// The HTTP client connects to the addresses returned, so a host can't resolve
+/// to an internal address after being checked.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            if addrs.iter().any(|addr| !is_public_ip(addr.ip())) {
+                return Err(format!("Host resolves to an address that is not allowed: '{}'", name.as_str()).into());
+            }
+
+            let addrs: Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        }
+        .boxed()
+    }
+}
+
+/// Fetches files over HTTP(S), following redirects to allowed hosts only
+pub struct HttpUrlFetcher {
+    client: reqwest::Client,
+}
+
+impl HttpUrlFetcher {
+    pub fn new(timeout: Duration, host_filter: HostFilter) -> Result<Self, anyhow::Error> {
+        let redirect_policy = redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                return attempt.error("Too many redirects");
+            }
+
+            // Redirects to host names are also checked by the resolver
+            if host_filter.is_url_allowed(attempt.url()) {
+                attempt.follow()
+            } else {
+                attempt.error("Redirected to a host that is not allowed")
+            }
+        });
+
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .connect_timeout(timeout)
+            .redirect(redirect_policy)
+            .dns_resolver(Arc::new(PublicResolver))
+            // A proxy would resolve host names itself, bypassing the resolver
+            .no_proxy()
+            .user_agent(concat!("BlazeBooru/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .context("Error building HTTP client")?;
+
+        Ok(Self { client })
+    }
+}
+
+impl UrlFetcher for HttpUrlFetcher {
+    fn fetch<'a>(&'a self, url: &'a Url) -> BoxFuture<'a, Result<FetchedFile, anyhow::Error>> {
+        async move {
+            let response = self
+                .client
+                .get(url.clone())
+                .send()
+                .await
+                .and_then(|r| r.error_for_status())?;
+
+            let content_type = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_string());
+
+            Ok(FetchedFile {
+                content_length: response.content_length(),
+                content_type,
+                body: response.bytes_stream().map_err(io::Error::other).boxed(),
+            })
+        }
+        .boxed()
+    }
+}
+
+impl BlazeBooruCore {
+    pub fn set_url_fetcher(&mut self, fetcher: impl UrlFetcher + 'static) {
+        self.url_fetcher = Box::new(fetcher);
+    }
+
+    /// Download a file into a temporary file, hashing it like an uploaded file.
+    /// Returns the hashed file and a filename for it.
+    pub async fn hash_url_to_temp_file(&self, url: &str) -> Result<(HashedFile, String), UrlUploadError> {
+        let url = Url::parse(url).map_err(|_| UrlUploadError::InvalidUrl(url.to_string()))?;
+
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(UrlUploadError::UnsupportedScheme(url.scheme().to_string()));
+        }
+
+        let host = url
+            .host_str()
+            .ok_or_else(|| UrlUploadError::InvalidUrl(url.to_string()))?;
+        if !self.url_host_filter.is_url_allowed(&url) {
+            return Err(UrlUploadError::HostNotAllowed(host.to_string()));
+        }
+
+        let fetched = self.url_fetcher.fetch(&url).await.map_err(UrlUploadError::Fetch)?;
+
+        let max_size = self.max_image_size;
+        if fetched.content_length.is_some_and(|l| l > max_size as u64) {
+            return Err(UrlUploadError::TooLarge(max_size));
+        }
+
+        let filename = url_filename(&url, fetched.content_type.as_deref()).ok_or(UrlUploadError::UnknownFileType)?;
+
+        // The announced size can't be trusted, so the size is also checked while reading
+        let file = self
+            .hash_stream_to_temp_file_with_limit(fetched.body, max_size)
+            .await
+            .map_err(|err| match err.downcast::<FileTooLargeError>() {
+                Ok(_) => UrlUploadError::TooLarge(max_size),
+                Err(err) => UrlUploadError::Fetch(err),
+            })?;
+
+        Ok((file, filename))
+    }
+}
+
+/// Get a filename for a fetched file from its URL,
+/// with an extension matching its content type if it has none.
+fn url_filename(url: &Url, content_type: Option<&str>) -> Option<String> {
+    let name = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|name| !name.is_empty())
+        .unwrap_or("file");
+
+    if name.rsplit_once('.').is_some_and(|(_, ext)| !ext.is_empty()) {
+        return Some(name.to_string());
+    }
+
+    let mime = content_type?.split(';').next()?.trim().to_lowercase();
+    let ext = match mime.as_str() {
+        "image/jpeg" => "jpg",
+        "image/png" => "png",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "video/webm" => "webm",
+        "video/mp4" => "mp4",
+        _ => return None,
+    };
+
+    Some(format!("{name}.{ext}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::config::BlazeBooruConfig;
+
+    const MAX_IMAGE_SIZE: usize = 16;
+
+    /// Stand-in fetcher serving a fixed response without network access
+    struct StandInFetcher {
+        content_type: Option<&'static str>,
+        body: &'static [u8],
+        fetch_count: Arc<AtomicUsize>,
+    }
+
+    impl UrlFetcher for StandInFetcher {
+        fn fetch<'a>(&'a self, _url: &'a Url) -> BoxFuture<'a, Result<FetchedFile, anyhow::Error>> {
+            self.fetch_count.fetch_add(1, Ordering::SeqCst);
+
+            async move {
+                Ok(FetchedFile {
+                    content_length: Some(self.body.len() as u64),
+                    content_type: self.content_type.map(|t| t.to_string()),
+                    body: futures_util::stream::iter([Ok(Bytes::from_static(self.body))]).boxed(),
+                })
+            }
+            .boxed()
+        }
+    }
+
+    /// Core fetching files with a stand-in fetcher, removing its files when dropped
+    struct TestCore {
+        core: BlazeBooruCore,
+        files_path: std::path::PathBuf,
+        fetch_count: Arc<AtomicUsize>,
+    }
+
+    impl Drop for TestCore {
+        fn drop(&mut self) {
+            std::fs::remove_dir_all(&self.files_path).ok();
+        }
+    }
+
+    fn test_core(content_type: Option<&'static str>, body: &'static [u8]) -> TestCore {
+        let files_path = std::env::temp_dir().join(format!("blazebooru-test-{}", uuid::Uuid::new_v4()));
+        let config = BlazeBooruConfig::from_str(&format!(
+            "files-path = '{}'\ndatabase-uri = 'postgres://localhost/blazebooru'\nmax-image-size = {MAX_IMAGE_SIZE}\n",
+            files_path.display()
+        ))
+        .unwrap();
+
+        let fetch_count = Arc::new(AtomicUsize::new(0));
+        let mut core = BlazeBooruCore::new(&config).unwrap();
+        core.set_url_fetcher(StandInFetcher {
+            content_type,
+            body,
+            fetch_count: fetch_count.clone(),
+        });
+
+        TestCore {
+            core,
+            files_path,
+            fetch_count,
+        }
+    }
+
+    fn default_host_filter() -> HostFilter {
+        let denied_hosts = BlazeBooruConfig::load_default().unwrap().url_upload_denied_hosts;
+
+        HostFilter::new(&[], &denied_hosts)
+    }
+
+    #[test]
+    fn host_filter_rejects_internal_addresses() {
+        let filter = default_host_filter();
+
+        for url in [
+            "http://localhost/a.png",
+            "http://sub.localhost/a.png",
+            "http://127.0.0.1/a.png",
+            "http://127.1/a.png",
+            "http://0x7f000001/a.png",
+            "http://0.0.0.0/a.png",
+            "http://[::1]/a.png",
+            "http://[::]/a.png",
+            "http://[::ffff:127.0.0.1]/a.png",
+            "http://[::ffff:10.0.0.1]/a.png",
+            "http://169.254.169.254/latest/meta-data",
+            "http://10.1.2.3/a.png",
+            "http://172.16.0.1/a.png",
+            "http://192.168.1.1/a.png",
+            "http://100.64.0.1/a.png",
+            "http://[fd00::1]/a.png",
+            "http://[fe80::1]/a.png",
+        ] {
+            let url = Url::parse(url).unwrap();
+            assert!(!filter.is_url_allowed(&url), "{url} should not be allowed");
+        }
+    }
+
+    #[test]
+    fn host_filter_allows_public_addresses() {
+        let filter = default_host_filter();
+
+        for url in [
+            "https://example.com/a.png",
+            "http://93.184.215.14/a.png",
+            "http://100.128.0.1/a.png",
+            "http://[2606:4700::1111]/a.png",
+        ] {
+            let url = Url::parse(url).unwrap();
+            assert!(filter.is_url_allowed(&url), "{url} should be allowed");
+        }
+    }
+
+    #[test]
+    fn host_filter_matches_subdomains() {
+        let filter = HostFilter::new(&["example.com".to_string()], &["private.example.com".to_string()]);
+
+        assert!(filter.is_allowed("example.com"));
+        assert!(filter.is_allowed("img.EXAMPLE.com"));
+        assert!(!filter.is_allowed("notexample.com"));
+        assert!(!filter.is_allowed("private.example.com"));
+        assert!(!filter.is_allowed("a.private.example.com"));
+    }
+
+    #[tokio::test]
+    async fn resolver_rejects_internal_addresses() {
+        let result = PublicResolver.resolve(Name::from_str("localhost").unwrap()).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn url_upload_uses_fetcher() {
+        let test = test_core(Some("image/png"), b"\x89PNG\r\n\x1a\n");
+
+        let (file, filename) = test
+            .core
+            .hash_url_to_temp_file("https://example.com/images/image")
+            .await
+            .unwrap();
+
+        assert_eq!(filename, "image.png");
+        assert_eq!(file.size, 8);
+        assert!(file.path.exists());
+        assert_eq!(test.fetch_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn url_upload_rejects_internal_hosts_without_fetching() {
+        let test = test_core(Some("image/png"), b"\x89PNG\r\n\x1a\n");
+
+        for url in [
+            "http://127.1/a.png",
+            "http://[::ffff:127.0.0.1]/a.png",
+            "http://localhost/a.png",
+        ] {
+            let result = test.core.hash_url_to_temp_file(url).await;
+            assert!(
+                matches!(result, Err(UrlUploadError::HostNotAllowed(_))),
+                "{url} should not be allowed"
+            );
+        }
+
+        let result = test.core.hash_url_to_temp_file("file:///etc/passwd").await;
+        assert!(matches!(result, Err(UrlUploadError::UnsupportedScheme(_))));
+
+        assert_eq!(test.fetch_count.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn url_upload_rejects_large_files() {
+        let test = test_core(Some("image/png"), &[0; MAX_IMAGE_SIZE + 1]);
+
+        let result = test.core.hash_url_to_temp_file("https://example.com/a.png").await;
+
+        assert!(matches!(result, Err(UrlUploadError::TooLarge(MAX_IMAGE_SIZE))));
+    }
+
+    #[tokio::test]
+    async fn url_upload_rejects_unknown_file_types() {
+        let test = test_core(Some("text/html"), b"<html>");
+
+        let result = test.core.hash_url_to_temp_file("https://example.com/page").await;
+
+        assert!(matches!(result, Err(UrlUploadError::UnknownFileType)));
+    }
+}
@@ -1,6 +1,6 @@
 use std::{
     borrow::Cow,
-    io::Read,
+    io::{self, Read},
     path::{Path, PathBuf},
 };
 
@@ -8,11 +8,17 @@ use anyhow::Context;
 use blazebooru_common::util::hash::hash_blake3_to_file_from_file;
 use bytes::Bytes;
 use futures_core::Stream;
+use futures_util::StreamExt;
 use once_cell::sync::Lazy;
+use thiserror::Error;
+use tracing::warn;
 
 use blazebooru_common::util;
 use blazebooru_models::local::HashedFile;
 
+use crate::util::archive::{ExtractLimits, ExtractedEntry, extract_archive};
+use crate::util::phash::{compute_image_phash, compute_video_phashes};
+
 use super::BlazeBooruCore;
 use blazebooru_common::util::hash::hash_blake3_to_file_from_stream;
 
@@ -20,6 +26,9 @@ pub const IMAGE_EXT: &str = "webp";
 pub const ANIM_IMAGE_EXT: &str = "webp";
 pub const VIDEO_EXT: &str = "webm";
 
+/// Maximum number of files extracted from an uploaded archive
+const MAX_ARCHIVE_ENTRIES: usize = 1000;
+
 pub struct ProcessFileResult<'a> {
     pub hash: String,
     pub original_ext: Cow<'a, str>,
@@ -33,13 +42,50 @@ pub enum FileKind {
     Video,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+}
+
+impl ArchiveKind {
+    /// Identify an archive by the extension of its filename
+    pub fn from_filename(filename: &str) -> Option<Self> {
+        let (_, ext) = filename.rsplit_once('.')?;
+
+        match ext.to_lowercase().as_str() {
+            "zip" => Some(ArchiveKind::Zip),
+            "tar" => Some(ArchiveKind::Tar),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+#[error("File is larger than the maximum size of {0} bytes")]
+pub struct FileTooLargeError(pub usize);
+
+/// File extracted from an archive
+pub struct ExtractedFile {
+    pub filename: String,
+    pub file: Result<HashedFile, anyhow::Error>,
+}
+
 static RE_IS_ANIMATED_WEBP: Lazy<regex::bytes::Regex> =
     Lazy::new(|| regex::bytes::Regex::new(r"^(?s-u:RIFF.{4}WEBPVP8X.{14}ANIM)").unwrap());
 
 impl BlazeBooruCore {
     pub async fn hash_file_to_temp_file(&self, path: &Path) -> Result<HashedFile, anyhow::Error> {
         let temp_path = self.temp_path.join(uuid::Uuid::new_v4().to_string());
-        let result = hash_blake3_to_file_from_file(path, &temp_path).await?;
+        let result = match hash_blake3_to_file_from_file(path, &temp_path).await {
+            Ok(result) => result,
+            Err(err) => {
+                // Don't leave partially written files behind
+                tokio::fs::remove_file(&temp_path).await.ok();
+
+                return Err(err);
+            }
+        };
 
         Ok(HashedFile {
             hash: result.hash,
@@ -48,12 +94,21 @@ impl BlazeBooruCore {
         })
     }
 
-    pub async fn hash_stream_to_temp_file<S: Stream<Item = Result<Bytes, E>> + Unpin, E>(
-        &self,
-        stream: &mut S,
-    ) -> Result<HashedFile, anyhow::Error> {
+    pub async fn hash_stream_to_temp_file<S, E>(&self, stream: &mut S) -> Result<HashedFile, anyhow::Error>
+    where
+        S: Stream<Item = Result<Bytes, E>> + Unpin,
+        E: std::error::Error + Send + Sync + 'static,
+    {
         let temp_path = self.temp_path.join(uuid::Uuid::new_v4().to_string());
-        let result = hash_blake3_to_file_from_stream(stream, &temp_path).await?;
+        let result = match hash_blake3_to_file_from_stream(stream, &temp_path).await {
+            Ok(result) => result,
+            Err(err) => {
+                // Don't leave partially written files behind
+                tokio::fs::remove_file(&temp_path).await.ok();
+
+                return Err(err);
+            }
+        };
 
         Ok(HashedFile {
             hash: result.hash,
@@ -62,6 +117,77 @@ impl BlazeBooruCore {
         })
     }
 
+    /// Hash a stream to a temporary file, failing with [`FileTooLargeError`]
+    /// if the stream is larger than `max_size` bytes.
+    pub async fn hash_stream_to_temp_file_with_limit<S, E>(
+        &self,
+        stream: S,
+        max_size: usize,
+    ) -> Result<HashedFile, anyhow::Error>
+    where
+        S: Stream<Item = Result<Bytes, E>> + Unpin,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        // The announced size can't be trusted, so the size is checked while reading
+        let mut size = 0;
+        let mut stream = stream.map(|chunk| {
+            let chunk = chunk.map_err(io::Error::other)?;
+
+            size += chunk.len();
+            if size > max_size {
+                return Err(io::Error::other(FileTooLargeError(max_size)));
+            }
+
+            Ok(chunk)
+        });
+
+        self.hash_stream_to_temp_file(&mut stream).await.map_err(|err| {
+            match err
+                .downcast_ref::<io::Error>()
+                .and_then(|e| e.get_ref())
+                .and_then(|e| e.downcast_ref::<FileTooLargeError>())
+            {
+                Some(err) => err.clone().into(),
+                None => err,
+            }
+        })
+    }
+
+    /// Extract the files in an archive into hashed temporary files.
+    /// The archive itself is deleted afterwards.
+    pub async fn extract_archive_to_temp_files(
+        &self,
+        kind: ArchiveKind,
+        archive: HashedFile,
+    ) -> Result<Vec<ExtractedFile>, anyhow::Error> {
+        let temp_path = self.temp_path.clone();
+        let limits = ExtractLimits {
+            max_entry_size: self.max_image_size as u64,
+            max_total_size: self.max_batch_upload_size as u64,
+            max_entries: MAX_ARCHIVE_ENTRIES,
+        };
+        let archive_path = archive.path.clone();
+
+        let entries = tokio::task::spawn_blocking(move || extract_archive(kind, &archive_path, &temp_path, limits))
+            .await
+            .context("Error awaiting archive extraction")?;
+
+        drop(archive);
+
+        let mut files = Vec::new();
+        for ExtractedEntry { filename, path } in entries? {
+            // The extracted file is removed when `path` is dropped
+            let file = match path {
+                Ok(path) => self.hash_file_to_temp_file(&path).await,
+                Err(err) => Err(err),
+            };
+
+            files.push(ExtractedFile { filename, file });
+        }
+
+        Ok(files)
+    }
+
     /// Process file and move it into the originals directory,
     /// or delete it if it already exists there.
     pub async fn process_file<'a>(
@@ -74,14 +200,15 @@ impl BlazeBooruCore {
             .rsplit_once('.')
             .context("Could not get extension from filename")?;
 
-        let hash = file.hash;
+        let hash = file.hash.clone();
 
         let original_image_filename = format!("{hash}.{original_ext}");
         let original_image_path = destination_path.join(original_image_filename);
 
         // If image does not already exist in originals path, move it there.
         if !original_image_path.exists() {
-            util::async_fs::move_file(file.path, &original_image_path).await?;
+            util::async_fs::move_file(&file.path, &original_image_path).await?;
+            file.keep();
 
             #[cfg(unix)]
             {
@@ -91,7 +218,7 @@ impl BlazeBooruCore {
             }
         } else {
             // ... otherwise, delete it.
-            tokio::fs::remove_file(file.path).await?;
+            drop(file);
         }
 
         Ok(ProcessFileResult {
@@ -115,6 +242,27 @@ impl BlazeBooruCore {
             _ => FileKind::Image,
         }
     }
+
+    /// Compute perceptual hashes of a file, for finding similar posts.
+    /// Returns no hashes if the file could not be hashed.
+    pub fn compute_perceptual_hashes(&self, ext: &str, path: &Path) -> Vec<i64> {
+        let file_kind = self.identify_file(ext, path);
+
+        let result = match file_kind {
+            FileKind::Image => compute_image_phash(path).map(|h| vec![h]),
+            // Animated WebP can't be decoded by ffmpeg, so only the first frame is hashed
+            FileKind::AnimatedImage if ext == "webp" => compute_image_phash(path).map(|h| vec![h]),
+            FileKind::AnimatedImage | FileKind::Video => compute_video_phashes(path),
+        };
+
+        match result {
+            Ok(hashes) => hashes,
+            Err(err) => {
+                warn!("Error computing perceptual hash of {}: {err:#}", path.display());
+                Vec::new()
+            }
+        }
+    }
 }
 
 fn is_animated_webp(path: &Path) -> bool {
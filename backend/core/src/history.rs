@@ -0,0 +1,123 @@
+use std::cmp::Reverse;
+
+use anyhow::anyhow;
+use chrono::Utc;
+
+use blazebooru_models::view as vm;
+
+use super::BlazeBooruCore;
+
+impl BlazeBooruCore {
+    /// Get the edit history of a post, with metadata changes, tag changes and file replacements merged,
+    /// newest first
+    pub async fn get_post_history(&self, post_id: i32) -> Result<Vec<vm::PostHistoryEntry>, anyhow::Error> {
+        let metadata_changes = self.store.get_post_metadata_changes(post_id).await?;
+        let tag_changes = self.store.get_post_tag_changes(post_id).await?;
+        let file_replacements = self.store.get_post_file_replacements(post_id).await?;
+
+        let mut history = metadata_changes
+            .into_iter()
+            .map(|c| c.try_into().map(vm::PostHistoryEntry::Metadata))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        history.extend(tag_changes.into_iter().map(|c| vm::PostHistoryEntry::Tags(c.into())));
+        history.extend(
+            file_replacements
+                .into_iter()
+                .map(|r| vm::PostHistoryEntry::File(r.into())),
+        );
+
+        history.sort_by_key(|e| Reverse(e.created_at()));
+
+        Ok(history)
+    }
+
+    /// Revert the metadata of a post to the version before a metadata change.
+    /// The revert is recorded as a new change.
+    pub async fn revert_post_metadata(
+        &self,
+        post_id: i32,
+        change_id: i32,
+        user_id: i32,
+    ) -> Result<bool, anyhow::Error> {
+        let Some(change) = self
+            .store
+            .get_post_metadata_change(change_id)
+            .await?
+            .filter(|c| c.post_id == Some(post_id))
+        else {
+            return Ok(false);
+        };
+
+        let Some(post) = self.store.get_post(post_id).await? else {
+            return Ok(false);
+        };
+
+        let rating = change
+            .old_rating
+            .map(|r| {
+                r.parse()
+                    .map_err(|_| anyhow!("Invalid rating in metadata change {change_id}: {r}"))
+            })
+            .transpose()?;
+
+        // The parent is only validated when it changes,
+        // so that reverting other fields does not fail if the parent has been deleted since
+        let parent_changed = change.old_parent_id != post.parent_id;
+
+        let request = vm::UpdatePost {
+            title: change.old_title,
+            description: change.old_description,
+            source: change.old_source,
+            add_tags: Vec::new(),
+            remove_tags: Vec::new(),
+            rating,
+            parent_id: change.old_parent_id.filter(|_| parent_changed),
+            remove_parent: parent_changed && change.old_parent_id.is_none(),
+        };
+
+        self.update_post(post_id, request, user_id).await
+    }
+
+    /// Get tag changes, optionally only of a post and/or user, newest first
+    pub async fn get_tag_changes(
+        &self,
+        post_id: Option<i32>,
+        user_id: Option<i32>,
+        start_id: Option<i32>,
+        limit: i32,
+    ) -> Result<Vec<vm::PostTagChange>, anyhow::Error> {
+        let changes = self
+            .store
+            .get_tag_changes(post_id, user_id, start_id, limit)
+            .await?
+            .into_iter()
+            .map(vm::PostTagChange::from)
+            .collect();
+
+        Ok(changes)
+    }
+
+    /// Revert a single tag change. The revert is recorded as a new change.
+    pub async fn revert_tag_change(&self, change_id: i32, user_id: i32) -> Result<bool, anyhow::Error> {
+        let success = self.store.revert_post_tag_change(change_id, user_id).await?;
+
+        Ok(success)
+    }
+
+    /// Revert all tag changes of a user made within a time window
+    pub async fn revert_user_tag_changes(
+        &self,
+        request: vm::RevertUserTagChanges,
+        user_id: i32,
+    ) -> Result<vm::RevertTagChangesResult, anyhow::Error> {
+        let until = request.until.unwrap_or_else(Utc::now);
+
+        let reverted_count = self
+            .store
+            .revert_user_tag_changes(request.user_id, request.since, until, user_id)
+            .await?;
+
+        Ok(vm::RevertTagChangesResult { reverted_count })
+    }
+}
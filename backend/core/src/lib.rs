@@ -1,28 +1,48 @@
 use std::path::PathBuf;
+use std::time::Duration;
 use std::{env, fs};
 
-use anyhow::Context;
+use anyhow::{Context, anyhow};
 
+use blazebooru_models::view as vm;
 use blazebooru_store::PgStore;
 use config::BlazeBooruConfig;
+use fetch::{HostFilter, HttpUrlFetcher, UrlFetcher};
 
 mod auth;
 mod comment;
 pub mod config;
+pub mod fetch;
 mod file;
+mod history;
+mod note;
+mod pool;
 mod post;
+mod report;
+pub mod search;
 mod tag;
 mod user;
 mod wiki;
 mod util;
 
 pub use self::file::*;
+pub use self::pool::DuplicatePoolNameError;
+pub use self::post::{DuplicatePostError, PostParentError};
 
 pub struct BlazeBooruCore {
     pub temp_path: PathBuf,
     pub public_path: PathBuf,
     pub public_original_path: PathBuf,
-    pub public_thumbnail_path: PathBuf,
+    pub renditions: Vec<vm::Rendition>,
+    pub max_image_size: usize,
+    pub max_batch_upload_size: usize,
+    pub similar_post_distance: u32,
+    pub reject_similar_posts: bool,
+    pub anonymous_max_rating: vm::Rating,
+    pub require_upload_approval: bool,
+    pub upload_approval_rank: i16,
+    url_fetcher: Box<dyn UrlFetcher>,
+    url_host_filter: HostFilter,
     store: PgStore,
 }
 
@@ -38,12 +58,15 @@ impl BlazeBooruCore {
 
         let public_path = files_path.join("public");
         let public_original_path = public_path.join("o");
-        let public_thumbnail_path = public_path.join("t");
+
+        validate_renditions(&config.renditions)?;
 
         // Ensure that all necessary directories exist
         fs::create_dir_all(&temp_path)?;
         fs::create_dir_all(&public_original_path)?;
-        fs::create_dir_all(&public_thumbnail_path)?;
+        for rendition in config.renditions.iter() {
+            fs::create_dir_all(public_path.join(&rendition.name))?;
+        }
 
         let database_uri = env::var("DATABASE_URL")
             .ok()
@@ -52,11 +75,26 @@ impl BlazeBooruCore {
 
         let store = PgStore::new(&database_uri)?;
 
+        let url_host_filter = HostFilter::new(&config.url_upload_allowed_hosts, &config.url_upload_denied_hosts);
+        let url_fetcher = HttpUrlFetcher::new(
+            Duration::from_secs(config.url_upload_timeout),
+            url_host_filter.clone(),
+        )?;
+
         Ok(Self {
             temp_path,
             public_path,
             public_original_path,
-            public_thumbnail_path,
+            renditions: config.renditions.clone(),
+            max_image_size: config.max_image_size,
+            max_batch_upload_size: config.max_batch_upload_size,
+            similar_post_distance: config.similar_post_distance,
+            reject_similar_posts: config.reject_similar_posts,
+            anonymous_max_rating: config.anonymous_max_rating,
+            require_upload_approval: config.require_upload_approval,
+            upload_approval_rank: config.upload_approval_rank,
+            url_fetcher: Box::new(url_fetcher),
+            url_host_filter,
             store,
         })
     }
@@ -64,4 +102,37 @@ impl BlazeBooruCore {
     pub async fn migrate(&self) -> Result<(), anyhow::Error> {
         self.store.migrate().await
     }
+
+    /// Get the path of the directory containing a rendition
+    pub fn rendition_path(&self, name: &str) -> PathBuf {
+        self.public_path.join(name)
+    }
+}
+
+/// Ensure that rendition names are unique and usable as directory names,
+/// and that the `t` rendition used as the thumbnail is always generated
+fn validate_renditions(renditions: &[vm::Rendition]) -> Result<(), anyhow::Error> {
+    if !renditions.iter().any(|r| r.name == "t" && !r.sample) {
+        return Err(anyhow!("Missing thumbnail rendition 't', which must not be a sample"));
+    }
+
+    for (i, rendition) in renditions.iter().enumerate() {
+        let name = &rendition.name;
+
+        let is_valid_name =
+            !name.is_empty() && name != "o" && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !is_valid_name {
+            return Err(anyhow!("Invalid rendition name: {name}"));
+        }
+
+        if rendition.size == 0 {
+            return Err(anyhow!("Invalid size of rendition: {name}"));
+        }
+
+        if renditions[..i].iter().any(|r| &r.name == name) {
+            return Err(anyhow!("Duplicate rendition name: {name}"));
+        }
+    }
+
+    Ok(())
 }
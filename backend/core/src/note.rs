@@ -0,0 +1,66 @@
+use anyhow::anyhow;
+
+use blazebooru_models::view as vm;
+use blazebooru_store::transform::{dbm_new_post_note_from_vm, dbm_update_post_note_from_vm};
+
+use super::BlazeBooruCore;
+
+impl BlazeBooruCore {
+    /// Create a note on a post. Returns `None` if the post does not exist or is deleted.
+    pub async fn create_post_note(
+        &self,
+        post_id: i32,
+        note: vm::PostNoteContent,
+        user_id: i32,
+    ) -> Result<Option<i32>, anyhow::Error> {
+        if !note.is_valid() {
+            return Err(anyhow!("Invalid note"));
+        }
+
+        let new_note = dbm_new_post_note_from_vm(post_id, note);
+        let new_note_id = self.store.create_post_note(&new_note, user_id).await?;
+
+        Ok(new_note_id)
+    }
+
+    pub async fn get_post_note(&self, id: i32) -> Result<Option<vm::PostNote>, anyhow::Error> {
+        let note = self.store.get_post_note(id).await?;
+
+        Ok(note.map(vm::PostNote::from))
+    }
+
+    pub async fn get_post_notes(&self, post_id: i32) -> Result<Vec<vm::PostNote>, anyhow::Error> {
+        let notes = self.store.get_post_notes(post_id).await?;
+
+        Ok(notes.into_iter().map(vm::PostNote::from).collect())
+    }
+
+    /// Get all versions of a note, newest first
+    pub async fn get_post_note_versions(&self, id: i32) -> Result<Vec<vm::PostNoteVersion>, anyhow::Error> {
+        let versions = self.store.get_post_note_versions(id).await?;
+
+        Ok(versions.into_iter().map(vm::PostNoteVersion::from).collect())
+    }
+
+    pub async fn update_post_note(
+        &self,
+        id: i32,
+        note: vm::PostNoteContent,
+        user_id: i32,
+    ) -> Result<bool, anyhow::Error> {
+        if !note.is_valid() {
+            return Err(anyhow!("Invalid note"));
+        }
+
+        let update_note = dbm_update_post_note_from_vm(id, note);
+        let success = self.store.update_post_note(&update_note, user_id).await?;
+
+        Ok(success)
+    }
+
+    pub async fn delete_post_note(&self, id: i32, user_id: i32) -> Result<bool, anyhow::Error> {
+        let success = self.store.delete_post_note(id, user_id).await?;
+
+        Ok(success)
+    }
+}
@@ -0,0 +1,93 @@
+use anyhow::anyhow;
+use thiserror::Error;
+
+use blazebooru_models::view as vm;
+use blazebooru_store::StoreError;
+use blazebooru_store::transform::dbm_update_pool_from_vm;
+
+use super::BlazeBooruCore;
+
+/// Error returned when creating or renaming a pool to the name of another pool
+#[derive(Debug, Error)]
+#[error("Another pool with the same name already exists")]
+pub struct DuplicatePoolNameError;
+
+impl BlazeBooruCore {
+    pub async fn create_pool(&self, pool: vm::NewPool, user_id: i32) -> Result<i32, anyhow::Error> {
+        if pool.name.trim().is_empty() {
+            return Err(anyhow!("Pool name can not be blank"));
+        }
+
+        let new_pool_id = self
+            .store
+            .create_pool(&pool.into(), user_id)
+            .await
+            .map_err(map_duplicate_pool_name)?;
+
+        Ok(new_pool_id)
+    }
+
+    pub async fn get_pool(&self, id: i32) -> Result<Option<vm::Pool>, anyhow::Error> {
+        let pool = self.store.get_pool(id).await?;
+
+        Ok(pool.map(vm::Pool::from))
+    }
+
+    pub async fn get_all_pools(&self) -> Result<Vec<vm::Pool>, anyhow::Error> {
+        let pools = self.store.get_all_pools().await?;
+
+        Ok(pools.into_iter().map(vm::Pool::from).collect())
+    }
+
+    /// Get the posts of a pool in order, as seen by the viewing user.
+    /// Returns `None` if the pool does not exist.
+    pub async fn get_pool_posts(
+        &self,
+        id: i32,
+        viewer_user_id: Option<i32>,
+    ) -> Result<Option<Vec<vm::Post>>, anyhow::Error> {
+        if self.store.get_pool(id).await?.is_none() {
+            return Ok(None);
+        }
+
+        let ratings = self.get_allowed_ratings(viewer_user_id).await?;
+        let posts = self.store.get_pool_posts(id, &ratings).await?;
+
+        let posts = posts.into_iter().map(vm::Post::try_from).collect::<Result<_, _>>()?;
+
+        Ok(Some(posts))
+    }
+
+    /// Update a pool, which only its creator or a moderator can do
+    pub async fn update_pool(&self, id: i32, request: vm::UpdatePool, user_id: i32) -> Result<bool, anyhow::Error> {
+        let update_pool = dbm_update_pool_from_vm(id, request);
+        let success = self
+            .store
+            .update_pool(&update_pool, user_id)
+            .await
+            .map_err(map_duplicate_pool_name)?;
+
+        Ok(success)
+    }
+
+    /// Replace the posts of a pool with the specified posts, in order,
+    /// which only its creator or a moderator can do
+    pub async fn set_pool_posts(&self, id: i32, post_ids: &[i32], user_id: i32) -> Result<bool, anyhow::Error> {
+        let success = self.store.set_pool_posts(id, post_ids, user_id).await?;
+
+        Ok(success)
+    }
+
+    pub async fn delete_pool(&self, id: i32, user_id: i32) -> Result<bool, anyhow::Error> {
+        let success = self.store.delete_pool(id, user_id).await?;
+
+        Ok(success)
+    }
+}
+
+fn map_duplicate_pool_name(err: StoreError) -> anyhow::Error {
+    match err {
+        StoreError::UniqueViolation => DuplicatePoolNameError.into(),
+        err => err.into(),
+    }
+}
@@ -1,16 +1,22 @@
 use std::borrow::Cow;
+use std::cmp;
+use std::collections::HashMap;
 use std::path::Path;
 
 use anyhow::Context as _;
 use anyhow::anyhow;
+use chrono::{DateTime, TimeDelta, Utc};
+use thiserror::Error;
 
 use blazebooru_models::export as em;
 use blazebooru_models::local as lm;
+use blazebooru_models::search as sm;
 use blazebooru_models::view as vm;
 use blazebooru_store::models as dbm;
-use blazebooru_store::transform::dbm_update_post_from_vm;
+use blazebooru_store::transform::{dbm_post_note_content_from_vm, dbm_update_post_from_vm};
 
 use crate::file::ProcessFileResult;
+use crate::search::apply_max_rating;
 use crate::util::image::{ImageMetadata, get_image_metadata};
 use crate::util::thumbnail::{
     AnimatedThumbnailGenerator, StaticThumbnailGenerator, ThumbnailGenerator, ThumbnailQuality,
@@ -19,18 +25,62 @@ use crate::{ANIM_IMAGE_EXT, FileKind, IMAGE_EXT};
 
 use super::BlazeBooruCore;
 
-const THUMBNAIL_WIDTH: u32 = 200;
-const THUMBNAIL_HEIGHT: u32 = 200;
+/// Maximum number of similar posts to return
+const MAX_SIMILAR_POSTS: i32 = 20;
+
+/// Error returned when setting the parent of a post to an invalid post
+#[derive(Debug, Error)]
+pub enum PostParentError {
+    #[error("Parent post {0} does not exist")]
+    NotFound(i32),
+    #[error("A post can not be its own ancestor")]
+    Cycle,
+}
+
+/// Error returned when uploading a file that an existing post already has
+#[derive(Debug, Error)]
+#[error("Another post with the same file already exists with ID: {0}")]
+pub struct DuplicatePostError(pub i32);
+
+pub struct PurgeDeletedPostsResult {
+    pub purged_post_count: usize,
+    pub removed_file_count: usize,
+}
 
 pub struct GeneratePostThumbnailResult<'a> {
     pub ext: Cow<'a, str>,
     pub tn_ext: Cow<'a, str>,
+    pub renditions: Vec<String>,
+    pub width: i32,
+    pub height: i32,
 }
 
 impl BlazeBooruCore {
-    pub async fn create_post(&self, post: lm::NewPost<'_>) -> Result<i32, anyhow::Error> {
+    pub async fn create_post(&self, post: lm::NewPost<'_>) -> Result<vm::UploadPostResult, anyhow::Error> {
         let size = post.file.size as i32;
 
+        // Check whether there are existing posts with the same hash
+        let identical_posts = self.store.get_posts_by_hash(&post.file.hash).await?;
+        if let Some(identical_post) = identical_posts.first() {
+            return Err(DuplicatePostError(identical_post.id).into());
+        }
+
+        // Check whether there are existing posts with a similar file,
+        // before the file is moved, so that a rejected file is removed with the temporary file.
+        let ext = post.filename.rsplit_once('.').map(|(_, ext)| ext).unwrap_or_default();
+        let phashes = self.compute_perceptual_hashes(ext, &post.file.path);
+        let similar_posts = self
+            .find_similar_posts(&phashes, self.similar_post_distance, None)
+            .await?;
+        if self.reject_similar_posts
+            && let Some(similar_post) = similar_posts.first()
+        {
+            return Err(anyhow!(
+                "Another post with a similar file already exists with ID: {}",
+                similar_post.post.id,
+            ));
+        }
+
         // Process file
         let ProcessFileResult {
             hash,
@@ -40,21 +90,18 @@ impl BlazeBooruCore {
             .process_file(post.file, &post.filename, &self.public_original_path)
             .await?;
 
-        // Check whether there are existing posts with the same hash
-        let identical_posts = self.store.get_posts_by_hash(&hash).await?;
-        if let Some(identical_post) = identical_posts.first() {
-            return Err(anyhow!(
-                "Another post with the same file already exists with ID: {}",
-                identical_post.id,
-            ));
-        }
-
         // Generate thumbnail
-        let GeneratePostThumbnailResult { ext, tn_ext } = self
+        let GeneratePostThumbnailResult {
+            ext,
+            tn_ext,
+            renditions,
+            width,
+            height,
+        } = self
             .generate_post_thumbnail(&original_file_path, &hash, &original_ext, false)
             .await?;
 
-        let ImageMetadata { width, height } = get_image_metadata(&original_file_path)?;
+        let is_pending = self.requires_upload_approval(post.user_id).await?;
 
         let db_post = dbm::NewPost {
             user_id: Some(post.user_id),
@@ -68,14 +115,44 @@ impl BlazeBooruCore {
             hash: Some(hash.to_string()),
             ext: Some(ext.as_ref().into()),
             tn_ext: Some(tn_ext.into()),
+            renditions: Some(renditions),
+            rating: Some(post.rating.as_str().to_string()),
+            is_pending: Some(is_pending),
         };
 
-        let new_post_id = self.store.create_post(&db_post, &post.tags).await?;
+        let new_post_id = self.store.create_post(&db_post, &post.tags, &phashes).await?;
 
-        Ok(new_post_id)
+        Ok(vm::UploadPostResult {
+            id: new_post_id,
+            pending: is_pending,
+            similar_posts,
+        })
     }
 
     pub async fn import_post(&self, post: em::Post, user_id: i32, file: Option<&Path>) -> Result<i32, anyhow::Error> {
+        let notes = post
+            .notes
+            .into_iter()
+            .map(|note| {
+                let note = vm::PostNoteContent {
+                    x: note.x,
+                    y: note.y,
+                    width: note.width,
+                    height: note.height,
+                    body: note.body,
+                };
+
+                if !note.is_valid() {
+                    return Err(anyhow!("Invalid note"));
+                }
+
+                Ok(dbm_post_note_content_from_vm(note))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut phashes = Vec::new();
+        let mut renditions = post.renditions;
+
         if let Some(path) = file {
             let hashed_file = self.hash_file_to_temp_file(path).await?;
 
@@ -89,8 +166,12 @@ impl BlazeBooruCore {
                 .await?;
 
             // Generate thumbnail
-            self.generate_post_thumbnail(&original_file_path, &hash, &original_ext, false)
-                .await?;
+            renditions = self
+                .generate_post_thumbnail(&original_file_path, &hash, &original_ext, false)
+                .await?
+                .renditions;
+
+            phashes = self.compute_perceptual_hashes(&original_ext, &original_file_path);
         }
 
         let db_post = dbm::NewPost {
@@ -105,87 +186,591 @@ impl BlazeBooruCore {
             hash: Some(post.hash),
             ext: Some(post.ext),
             tn_ext: Some(post.tn_ext),
+            renditions: Some(renditions),
+            rating: Some(post.rating.as_str().to_string()),
+            is_pending: Some(post.is_pending),
         };
 
         let tags: Vec<_> = post.tags.iter().map(|t| t.as_str()).collect();
 
-        let new_post_id = self.store.create_post(&db_post, &tags).await?;
+        let new_post_id = self
+            .store
+            .create_post_with_notes(&db_post, &tags, &phashes, &notes)
+            .await?;
 
         Ok(new_post_id)
     }
 
     pub async fn get_view_post(&self, id: i32) -> Result<Option<vm::Post>, anyhow::Error> {
-        let post = self.store.get_view_post(id).await?.map(vm::Post::from);
+        let post = self
+            .store
+            .get_view_post(id)
+            .await?
+            .map(vm::Post::try_from)
+            .transpose()?;
 
         Ok(post)
     }
 
+    /// Get posts similar to an existing post
+    pub async fn get_similar_posts(
+        &self,
+        id: i32,
+        max_distance: Option<u32>,
+    ) -> Result<Vec<vm::SimilarPost>, anyhow::Error> {
+        let phashes = self.store.get_post_phashes(id).await?;
+        let max_distance = max_distance.unwrap_or(self.similar_post_distance);
+
+        self.find_similar_posts(&phashes, max_distance, Some(id)).await
+    }
+
+    /// Get posts similar to a file, without creating a post.
+    /// The file is deleted afterwards.
+    pub async fn find_similar_posts_by_file(
+        &self,
+        file: lm::HashedFile,
+        filename: &str,
+        max_distance: Option<u32>,
+    ) -> Result<Vec<vm::SimilarPost>, anyhow::Error> {
+        let ext = filename.rsplit_once('.').map(|(_, ext)| ext).unwrap_or_default();
+
+        let phashes = self.compute_perceptual_hashes(ext, &file.path);
+        drop(file);
+
+        if phashes.is_empty() {
+            return Err(anyhow!("Could not compute perceptual hash of file"));
+        }
+
+        let max_distance = max_distance.unwrap_or(self.similar_post_distance);
+
+        self.find_similar_posts(&phashes, max_distance, None).await
+    }
+
+    async fn find_similar_posts(
+        &self,
+        phashes: &[i64],
+        max_distance: u32,
+        exclude_post_id: Option<i32>,
+    ) -> Result<Vec<vm::SimilarPost>, anyhow::Error> {
+        if phashes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let posts = self
+            .store
+            .get_similar_posts(phashes, max_distance as i32, exclude_post_id, MAX_SIMILAR_POSTS)
+            .await?
+            .into_iter()
+            .map(vm::SimilarPost::try_from)
+            .collect::<Result<_, _>>()?;
+
+        Ok(posts)
+    }
+
     pub async fn update_post(&self, id: i32, request: vm::UpdatePost, user_id: i32) -> Result<bool, anyhow::Error> {
+        if let Some(parent_id) = request.parent_id {
+            self.validate_post_parent(id, parent_id).await?;
+        }
+
         let update_post = dbm_update_post_from_vm(id, request);
         let success = self.store.update_post(&update_post, user_id).await?;
 
         Ok(success)
     }
 
+    /// Check that a post can be made the parent of another post,
+    /// without creating a cycle
+    async fn validate_post_parent(&self, id: i32, parent_id: i32) -> Result<(), anyhow::Error> {
+        let parent = self
+            .store
+            .get_post(parent_id)
+            .await?
+            .filter(|p| !p.is_deleted)
+            .ok_or(PostParentError::NotFound(parent_id))?;
+
+        // Deleted posts keep their parent, so they are followed as well
+        let mut ancestor = Some(parent);
+        while let Some(post) = ancestor {
+            if post.id == id {
+                return Err(PostParentError::Cycle.into());
+            }
+
+            ancestor = match post.parent_id {
+                Some(parent_id) => self.store.get_post(parent_id).await?,
+                None => None,
+            };
+        }
+
+        Ok(())
+    }
+
+    pub async fn can_user_edit_post(&self, id: i32, user_id: i32) -> Result<bool, anyhow::Error> {
+        let can_edit = self.store.can_user_edit_post(id, user_id).await?;
+
+        Ok(can_edit)
+    }
+
+    /// Replace the file of a post, keeping its metadata, tags and comments.
+    /// The previous file is kept in the replacement history, so the replacement can be reverted.
+    pub async fn replace_post_file(
+        &self,
+        id: i32,
+        file: lm::HashedFile,
+        filename: &str,
+        user_id: i32,
+    ) -> Result<bool, anyhow::Error> {
+        let size = file.size as i32;
+
+        // Check whether there are other posts with the same hash
+        let identical_posts = self.store.get_posts_by_hash(&file.hash).await?;
+        if let Some(identical_post) = identical_posts.iter().find(|p| p.id != id) {
+            return Err(DuplicatePostError(identical_post.id).into());
+        }
+
+        let ProcessFileResult {
+            hash,
+            original_ext,
+            original_file_path,
+        } = self.process_file(file, filename, &self.public_original_path).await?;
+
+        let GeneratePostThumbnailResult {
+            ext,
+            tn_ext,
+            renditions,
+            width,
+            height,
+        } = match self
+            .generate_post_thumbnail(&original_file_path, &hash, &original_ext, false)
+            .await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                self.remove_unused_post_files(&hash, &original_ext, None, &[]).await?;
+                return Err(err);
+            }
+        };
+
+        let phashes = self.compute_perceptual_hashes(&original_ext, &original_file_path);
+
+        let db_file = dbm::PostFile {
+            filename: filename.to_string(),
+            size,
+            width,
+            height,
+            hash: hash.clone(),
+            ext: ext.into(),
+            tn_ext: tn_ext.to_string(),
+            renditions: renditions.clone(),
+        };
+
+        let result = self
+            .store
+            .replace_post_file(id, &db_file, &phashes, user_id)
+            .await
+            .map_err(anyhow::Error::from);
+
+        // The post keeps its old file if the new one could not be stored
+        if !matches!(result, Ok(true)) {
+            self.remove_unused_post_files(&hash, &original_ext, Some(&tn_ext), &renditions)
+                .await?;
+        }
+
+        result
+    }
+
+    /// Restore the file a post had before a file replacement.
+    /// The revert is recorded as a new replacement.
+    pub async fn revert_post_file_replacement(
+        &self,
+        id: i32,
+        replacement_id: i32,
+        user_id: i32,
+    ) -> Result<bool, anyhow::Error> {
+        let success = self
+            .store
+            .revert_post_file_replacement(id, replacement_id, user_id)
+            .await?;
+
+        Ok(success)
+    }
+
     pub async fn delete_post(&self, id: i32, user_id: i32) -> Result<bool, anyhow::Error> {
         let success = self.store.delete_post(id, user_id).await?;
 
         Ok(success)
     }
 
-    pub async fn get_export_posts(
+    pub async fn get_deleted_posts(
+        &self,
+        start_id: Option<i32>,
+        limit: i32,
+    ) -> Result<Vec<vm::DeletedPost>, anyhow::Error> {
+        let posts = self.store.get_deleted_posts(start_id, limit).await?;
+
+        posts.into_iter().map(vm::DeletedPost::try_from).collect()
+    }
+
+    /// Check whether posts uploaded by the user are hidden until they are approved
+    async fn requires_upload_approval(&self, user_id: i32) -> Result<bool, anyhow::Error> {
+        if !self.require_upload_approval {
+            return Ok(false);
+        }
+
+        let rank = self.store.get_user(user_id).await?.map_or(0, |u| u.rank);
+
+        Ok(rank < self.upload_approval_rank)
+    }
+
+    pub async fn get_pending_posts(
         &self,
-        include_tags: Vec<String>,
-        exclude_tags: Vec<String>,
-        start_id: i32,
+        start_id: Option<i32>,
         limit: i32,
-    ) -> Result<Vec<em::Post>, anyhow::Error> {
+    ) -> Result<Vec<vm::PendingPost>, anyhow::Error> {
+        let posts = self.store.get_pending_posts(start_id, limit).await?;
+
+        posts.into_iter().map(vm::PendingPost::try_from).collect()
+    }
+
+    pub async fn approve_post(&self, id: i32) -> Result<bool, anyhow::Error> {
+        let success = self.store.approve_post(id).await?;
+
+        Ok(success)
+    }
+
+    /// Reject a post awaiting approval, which deletes it
+    pub async fn reject_post(&self, id: i32) -> Result<bool, anyhow::Error> {
+        let success = self.store.reject_post(id).await?;
+
+        Ok(success)
+    }
+
+    pub async fn undelete_post(&self, id: i32) -> Result<bool, anyhow::Error> {
+        let success = self.store.undelete_post(id).await?;
+
+        Ok(success)
+    }
+
+    /// Permanently delete posts that were deleted before the specified time.
+    /// Files are removed once no other post uses them.
+    pub async fn purge_deleted_posts(
+        &self,
+        deleted_before: DateTime<Utc>,
+    ) -> Result<PurgeDeletedPostsResult, anyhow::Error> {
+        let posts = self.store.get_posts_deleted_before(deleted_before).await?;
+
+        let mut result = PurgeDeletedPostsResult {
+            purged_post_count: 0,
+            removed_file_count: 0,
+        };
+
+        for post in posts {
+            // Files replaced by later files are kept for reverting, until the post is purged
+            let replaced_files = self.store.get_replaced_post_files(post.id).await?;
+
+            if !self.store.purge_post(post.id).await? {
+                continue;
+            }
+
+            result.purged_post_count += 1;
+
+            result.removed_file_count += self
+                .remove_unused_post_files(&post.hash, &post.ext, Some(&post.tn_ext), &post.renditions)
+                .await?;
+
+            for file in replaced_files {
+                result.removed_file_count += self
+                    .remove_unused_post_files(&file.hash, &file.ext, Some(&file.tn_ext), &file.renditions)
+                    .await?;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Remove the original and thumbnail files of a post file, unless a post still uses it.
+    /// Returns the number of removed files.
+    async fn remove_unused_post_files(
+        &self,
+        hash: &str,
+        ext: &str,
+        tn_ext: Option<&str>,
+        renditions: &[String],
+    ) -> Result<usize, anyhow::Error> {
+        if self.store.is_post_file_in_use(hash).await? {
+            return Ok(0);
+        }
+
+        let mut paths = vec![self.public_original_path.join(format!("{hash}.{ext}"))];
+        if let Some(tn_ext) = tn_ext {
+            let thumbnail_filename = format!("{hash}.{tn_ext}");
+            paths.extend(
+                renditions
+                    .iter()
+                    .map(|name| self.rendition_path(name).join(&thumbnail_filename)),
+            );
+        }
+
+        let mut removed_file_count = 0;
+        for path in paths {
+            match tokio::fs::remove_file(&path).await {
+                Ok(()) => removed_file_count += 1,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err).with_context(|| format!("Error removing file {}", path.display())),
+            }
+        }
+
+        Ok(removed_file_count)
+    }
+
+    /// Get the IDs of the posts matching a filter, in ascending order
+    pub async fn get_post_ids_by_filter(&self, filter: &lm::PostFilter) -> Result<Vec<i32>, anyhow::Error> {
+        let tags: Vec<_> = filter.tags.iter().map(|t| t.to_lowercase()).collect();
+        let exts: Vec<_> = filter
+            .exts
+            .iter()
+            .map(|e| e.trim_start_matches('.').to_lowercase())
+            .collect();
+
+        let post_ids = self
+            .store
+            .get_post_ids_by_filter(
+                &tags,
+                &exts,
+                filter.min_id.unwrap_or(i32::MIN),
+                filter.max_id.unwrap_or(i32::MAX),
+            )
+            .await?;
+
+        Ok(post_ids)
+    }
+
+    /// Regenerate the thumbnail and renditions of a post, overwriting existing ones,
+    /// and update the dimensions and perceptual hashes of its file.
+    /// Files of renditions that are no longer generated are removed.
+    /// Returns `false` if the post does not exist.
+    pub async fn regenerate_post_thumbnail(&self, id: i32) -> Result<bool, anyhow::Error> {
+        let Some(post) = self.store.get_post(id).await? else {
+            return Ok(false);
+        };
+
+        let original_file_path = self.public_original_path.join(format!("{}.{}", post.hash, post.ext));
+        if !original_file_path.exists() {
+            return Err(anyhow!(
+                "Original file of post {id} not found: {}",
+                original_file_path.display()
+            ));
+        }
+
+        let GeneratePostThumbnailResult {
+            tn_ext,
+            renditions,
+            width,
+            height,
+            ..
+        } = self
+            .generate_post_thumbnail(&original_file_path, &post.hash, &post.ext, true)
+            .await?;
+
+        // Posts created before perceptual hashes were computed get them here
+        let phashes = self.compute_perceptual_hashes(&post.ext, &original_file_path);
+
+        // Remove stale thumbnails
+        let thumbnail_path = |name: &str, ext: &str| self.rendition_path(name).join(format!("{}.{ext}", post.hash));
+        let new_thumbnail_paths: Vec<_> = renditions.iter().map(|name| thumbnail_path(name, &tn_ext)).collect();
+        for path in post.renditions.iter().map(|name| thumbnail_path(name, &post.tn_ext)) {
+            if new_thumbnail_paths.contains(&path) {
+                continue;
+            }
+
+            match tokio::fs::remove_file(&path).await {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err).with_context(|| format!("Error removing file {}", path.display())),
+            }
+        }
+
+        let success = self
+            .store
+            .update_post_file_metadata(id, width, height, &tn_ext, &renditions, &phashes)
+            .await?;
+
+        Ok(success)
+    }
+
+    pub async fn favorite_post(&self, id: i32, user_id: i32) -> Result<bool, anyhow::Error> {
+        let success = self.store.favorite_post(id, user_id).await?;
+
+        Ok(success)
+    }
+
+    pub async fn unfavorite_post(&self, id: i32, user_id: i32) -> Result<bool, anyhow::Error> {
+        let success = self.store.unfavorite_post(id, user_id).await?;
+
+        Ok(success)
+    }
+
+    /// Get the posts favorited by a user, as seen by the viewing user
+    pub async fn get_user_favorites(
+        &self,
+        user_id: i32,
+        viewer_user_id: Option<i32>,
+        start: Option<sm::Cursor>,
+        limit: i32,
+    ) -> Result<Option<Vec<vm::Post>>, anyhow::Error> {
+        let Some(user) = self.store.get_user(user_id).await? else {
+            return Ok(None);
+        };
+
+        let query = sm::SearchQuery::default().and(sm::Expr::Term(sm::Term::Fav(user.name)));
+        let query = self.apply_rating_filter(query, viewer_user_id).await?;
+
+        let posts = self.get_view_posts(&query, start, limit).await?;
+
+        Ok(Some(posts))
+    }
+
+    /// Set the vote of a user on a post. A score of 0 removes the vote.
+    pub async fn vote_post(&self, id: i32, user_id: i32, score: i16) -> Result<bool, anyhow::Error> {
+        let success = self.store.vote_post(id, user_id, score).await?;
+
+        Ok(success)
+    }
+
+    /// Get the posts with the most votes received in a period, as seen by the viewing user
+    pub async fn get_popular_posts(
+        &self,
+        period: vm::PopularPeriod,
+        viewer_user_id: Option<i32>,
+        limit: i32,
+    ) -> Result<Vec<vm::PopularPost>, anyhow::Error> {
+        let period = match period {
+            vm::PopularPeriod::Day => TimeDelta::days(1),
+            vm::PopularPeriod::Week => TimeDelta::weeks(1),
+            vm::PopularPeriod::Month => TimeDelta::days(30),
+        };
+
+        let ratings = self.get_allowed_ratings(viewer_user_id).await?;
+
         let posts = self
             .store
-            .get_view_posts(&include_tags, &exclude_tags, start_id, limit)
+            .get_popular_posts(Utc::now() - period, &ratings, limit)
             .await?
             .into_iter()
-            .map(em::Post::from)
+            .map(vm::PopularPost::try_from)
+            .collect::<Result<_, _>>()?;
+
+        Ok(posts)
+    }
+
+    /// Apply the default rating filter of a user, or of anonymous users if not logged in
+    pub async fn apply_rating_filter(
+        &self,
+        query: sm::SearchQuery,
+        user_id: Option<i32>,
+    ) -> Result<sm::SearchQuery, anyhow::Error> {
+        let max_rating = self.get_max_rating(user_id).await?;
+
+        Ok(apply_max_rating(query, max_rating))
+    }
+
+    /// Get the names of the ratings allowed by the default rating filter of a user,
+    /// or of anonymous users if not logged in
+    pub(crate) async fn get_allowed_ratings(&self, user_id: Option<i32>) -> Result<Vec<&'static str>, anyhow::Error> {
+        let max_rating = self.get_max_rating(user_id).await?;
+
+        let ratings = vm::Rating::ALL
+            .into_iter()
+            .filter(|r| *r <= max_rating)
+            .map(|r| r.as_str())
             .collect();
 
+        Ok(ratings)
+    }
+
+    /// Get the default maximum rating of a user, or of anonymous users if not logged in
+    async fn get_max_rating(&self, user_id: Option<i32>) -> Result<vm::Rating, anyhow::Error> {
+        let max_rating = match user_id {
+            Some(user_id) => match self.store.get_user(user_id).await? {
+                Some(user) => vm::User::try_from(user)?.max_rating,
+                None => self.anonymous_max_rating,
+            },
+            None => self.anonymous_max_rating,
+        };
+
+        Ok(max_rating)
+    }
+
+    /// Get all posts for exporting, including posts awaiting approval
+    pub async fn get_export_posts(&self) -> Result<Vec<em::Post>, anyhow::Error> {
+        let posts = self.store.get_export_posts().await?;
+
+        let post_ids: Vec<_> = posts.iter().map(|p| p.post.id).collect();
+        let mut notes: HashMap<i32, Vec<em::Note>> = HashMap::new();
+        for note in self.store.get_notes_by_post_ids(&post_ids).await? {
+            notes.entry(note.post_id.unwrap()).or_default().push(note.into());
+        }
+
+        let posts = posts
+            .into_iter()
+            .map(|p| {
+                let post_notes = notes.remove(&p.post.id).unwrap_or_default();
+
+                Ok(em::Post {
+                    notes: post_notes,
+                    ..em::Post::try_from(p)?
+                })
+            })
+            .collect::<Result<_, anyhow::Error>>()?;
+
         Ok(posts)
     }
 
     pub async fn get_view_posts(
         &self,
-        include_tags: Vec<String>,
-        exclude_tags: Vec<String>,
-        start_id: i32,
+        query: &sm::SearchQuery,
+        start: Option<sm::Cursor>,
         limit: i32,
     ) -> Result<Vec<vm::Post>, anyhow::Error> {
         let posts = self
             .store
-            .get_view_posts(&include_tags, &exclude_tags, start_id, limit)
+            .get_view_posts(query, start, limit)
             .await?
             .into_iter()
-            .map(vm::Post::from)
-            .collect();
+            .map(vm::Post::try_from)
+            .collect::<Result<_, _>>()?;
 
         Ok(posts)
     }
 
+    /// Get a random post matching a search query
+    pub async fn get_random_view_post(&self, query: &sm::SearchQuery) -> Result<Option<vm::Post>, anyhow::Error> {
+        let post = self.store.get_random_view_post(query).await?;
+
+        post.map(vm::Post::try_from).transpose()
+    }
+
+    /// Get the IDs of the previous and next posts of a post within the results of a search query.
+    /// Returns `None` if the post does not exist.
+    pub async fn get_post_neighbors(
+        &self,
+        query: &sm::SearchQuery,
+        id: i32,
+    ) -> Result<Option<vm::PostNeighbors>, anyhow::Error> {
+        let neighbors = self.store.get_post_neighbors(query, id).await?;
+
+        Ok(neighbors.map(|(prev_id, next_id)| vm::PostNeighbors { prev_id, next_id }))
+    }
+
     pub async fn calculate_pages(
         &self,
-        include_tags: Vec<&str>,
-        exclude_tags: Vec<&str>,
+        query: &sm::SearchQuery,
         posts_per_page: i32,
         page_count: i32,
         origin_page: Option<vm::PageInfo>,
     ) -> Result<Vec<vm::PageInfo>, anyhow::Error> {
         let pages = self
             .store
-            .calculate_pages(
-                &include_tags,
-                &exclude_tags,
-                posts_per_page,
-                page_count,
-                origin_page.map(dbm::PageInfo::from),
-            )
+            .calculate_pages(query, posts_per_page, page_count, origin_page.map(dbm::KeysetPageInfo::from))
             .await?;
 
         Ok(pages.into_iter().map(vm::PageInfo::from).collect())
@@ -193,19 +778,16 @@ impl BlazeBooruCore {
 
     pub async fn calculate_last_page(
         &self,
-        include_tags: Vec<&str>,
-        exclude_tags: Vec<&str>,
+        query: &sm::SearchQuery,
         posts_per_page: i32,
     ) -> Result<vm::PageInfo, anyhow::Error> {
-        let page = self
-            .store
-            .calculate_last_page(&include_tags, &exclude_tags, posts_per_page)
-            .await?;
+        let page = self.store.calculate_last_page(query, posts_per_page).await?;
 
         Ok(vm::PageInfo::from(page))
     }
 
-    /// Generate thumbnail
+    /// Generate the thumbnail and the other configured renditions of a post file,
+    /// returning the dimensions of the original file along with the generated renditions.
     pub async fn generate_post_thumbnail<'a>(
         &self,
         original_image_path: &Path,
@@ -223,8 +805,23 @@ impl BlazeBooruCore {
             FileKind::AnimatedImage | FileKind::Video => ANIM_IMAGE_EXT,
         };
 
+        let ImageMetadata { width, height } = get_image_metadata(original_image_path)?;
+
+        // Samples are only generated for images larger than the sample size
+        let can_sample = !preserve_original && file_kind != FileKind::Video;
+        let original_size = if can_sample { cmp::max(width, height) as u32 } else { 0 };
+
+        let renditions: Vec<_> = self
+            .renditions
+            .iter()
+            .filter(|r| !r.sample || r.size < original_size)
+            .collect();
+
         let thumbnail_filename = format!("{hash}.{tn_ext}");
-        let thumbnail_path = self.public_thumbnail_path.join(thumbnail_filename);
+        let thumbnail_paths: Vec<_> = renditions
+            .iter()
+            .map(|r| self.rendition_path(&r.name).join(&thumbnail_filename))
+            .collect();
 
         let mut tn_gen: Box<dyn ThumbnailGenerator> = match file_kind {
             FileKind::Image => Box::new(StaticThumbnailGenerator::new(original_image_path)),
@@ -234,18 +831,20 @@ impl BlazeBooruCore {
             )),
         };
 
-        // If thumbnail does not already exist, create it.
-        let thumbnail_exists = thumbnail_path.exists();
-        if overwrite || !thumbnail_exists {
-            if preserve_original {
-                if thumbnail_exists {
-                    tokio::fs::remove_file(&thumbnail_path).await?;
+        for (rendition, thumbnail_path) in renditions.iter().zip(thumbnail_paths.iter()) {
+            // If thumbnail does not already exist, create it.
+            let thumbnail_exists = thumbnail_path.exists();
+            if overwrite || !thumbnail_exists {
+                if preserve_original {
+                    if thumbnail_exists {
+                        tokio::fs::remove_file(thumbnail_path).await?;
+                    }
+
+                    // If preserving original, simply create a hard link to the original file
+                    tokio::fs::hard_link(&original_image_path, thumbnail_path).await?;
+                } else {
+                    tn_gen.add(thumbnail_path, rendition.size, rendition.size);
                 }
-
-                // If preserving original, simply create a hard link to the original file
-                tokio::fs::hard_link(&original_image_path, &thumbnail_path).await?;
-            } else {
-                tn_gen.add(&thumbnail_path, THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT);
             }
         }
 
@@ -254,6 +853,9 @@ impl BlazeBooruCore {
         Ok(GeneratePostThumbnailResult {
             ext: ext.into(),
             tn_ext: tn_ext.into(),
+            renditions: renditions.into_iter().map(|r| r.name.clone()).collect(),
+            width,
+            height,
         })
     }
 }
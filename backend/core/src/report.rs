@@ -0,0 +1,51 @@
+use anyhow::anyhow;
+
+use blazebooru_models::view as vm;
+use blazebooru_store::transform::dbm_resolve_report_from_vm;
+
+use super::BlazeBooruCore;
+
+impl BlazeBooruCore {
+    /// Report a post, comment, wiki page or user.
+    /// Returns `None` if the reported item does not exist.
+    pub async fn create_report(&self, report: vm::NewReport, user_id: i32) -> Result<Option<i32>, anyhow::Error> {
+        if report.reason.trim().is_empty() {
+            return Err(anyhow!("Report reason can not be blank"));
+        }
+
+        let new_report_id = self.store.create_report(&report.into(), user_id).await?;
+
+        Ok(new_report_id)
+    }
+
+    pub async fn get_report(&self, id: i32) -> Result<Option<vm::Report>, anyhow::Error> {
+        let report = self.store.get_report(id).await?;
+
+        Ok(report.map(vm::Report::from))
+    }
+
+    /// Get reports with the specified status, newest first
+    pub async fn get_reports(
+        &self,
+        status: vm::ReportStatus,
+        start_id: Option<i32>,
+        limit: i32,
+    ) -> Result<Vec<vm::Report>, anyhow::Error> {
+        let reports = self.store.get_reports(status.as_str(), start_id, limit).await?;
+
+        Ok(reports.into_iter().map(vm::Report::from).collect())
+    }
+
+    /// Resolve or dismiss an open report.
+    /// If requested, the reported post, comment or wiki page is deleted in the same transaction.
+    pub async fn resolve_report(&self, id: i32, request: vm::ResolveReport, user_id: i32) -> Result<bool, anyhow::Error> {
+        if request.status == vm::ReportStatus::Open {
+            return Err(anyhow!("A report can not be resolved as open"));
+        }
+
+        let resolution = dbm_resolve_report_from_vm(id, request);
+        let success = self.store.resolve_report(&resolution, user_id).await?;
+
+        Ok(success)
+    }
+}
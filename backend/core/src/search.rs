@@ -0,0 +1,728 @@
+//! Post search query parser
+//!
+//! Terms are separated by whitespace and implicitly combined with AND.
+//! Terms can be combined with OR using `or` or `|`, grouped using parentheses
+//! and negated by prefixing them with `-`. Tags containing whitespace,
+//! parentheses or other special characters can be enclosed in double quotes.
+//!
+//! Supported metatags:
+//! * `user:name` - Uploaded by user
+//! * `fav:name` - Favorited by user
+//! * `pool:` - In pool, by ID or name
+//! * `parent:ID` - The post and its children
+//! * `parent:any`, `parent:none` - Has a parent or not
+//! * `child:any`, `child:none` - Has children or not
+//! * `note:any`, `note:none` - Has notes or not
+//! * `ext:gif` - Original file extension
+//! * `id:`, `width:`, `height:`, `size:`, `score:` - Numeric comparison
+//!   (`N`, `>N`, `>=N`, `<N`, `<=N`, `A..B`, `A..`, `..B`)
+//! * `date:` - Upload date (`YYYY`, `YYYY-MM` or `YYYY-MM-DD`),
+//!   using the same comparison syntax as numeric metatags
+//! * `text:` - Full-text search in title and description
+//!   (web search syntax, e.g. `text:"old harbor" -text:ship`)
+//! * `rating:` - Content rating (`general`, `sensitive`, `questionable` or `explicit`,
+//!   or their first letter), or a comma separated list of ratings (`rating:g,s`)
+//!
+//! Metatag values can be enclosed in double quotes, e.g. `user:"some name"`.
+//!
+//! The sort order can be specified using `order:`, with one of `id`, `date`, `size`,
+//! `resolution`, `random:SEED`, `rank` or `score`, optionally followed by `_asc` or `_desc`.
+//! The default is `order:rank` (best match first) if the query contains `text:` terms,
+//! and `order:id` (newest first) otherwise.
+//!
+//! Example: `(cat or dog) -sleeping width:>=1920 date:2025-01..2025-03 order:size`
+
+use chrono::{DateTime, Months, NaiveDate, Utc};
+use thiserror::Error;
+
+use blazebooru_models::search as sm;
+use blazebooru_models::view as vm;
+
+#[derive(Debug, Error)]
+pub enum SearchQueryError {
+    #[error("Unterminated quote")]
+    UnterminatedQuote,
+    #[error("Unbalanced parentheses")]
+    UnbalancedParentheses,
+    #[error("Expected search term")]
+    ExpectedTerm,
+    #[error("Invalid value for {0}: '{1}'")]
+    InvalidValue(String, String),
+    #[error("Invalid sort order: '{0}'")]
+    InvalidOrder(String),
+    #[error("Missing sort key of the start post")]
+    MissingStartKey,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Not,
+    And,
+    Or,
+    Open,
+    Close,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+/// Parse a post search query string
+pub fn parse_search_query(query: &str) -> Result<sm::SearchQuery, SearchQueryError> {
+    let mut tokens = tokenize(query)?;
+
+    // Sort order is not part of the filter expression, so extract it before parsing.
+    // If specified multiple times, the last one wins.
+    let mut order = None;
+    let mut result = Ok(());
+    tokens.retain(|token| match token {
+        Token::Word(word) => match word.split_once(':') {
+            Some((key, value)) if key.eq_ignore_ascii_case("order") => {
+                match parse_order(value) {
+                    Some(o) => order = Some(o),
+                    None => result = Err(SearchQueryError::InvalidOrder(value.to_string())),
+                }
+
+                false
+            }
+            _ => true,
+        },
+        _ => true,
+    });
+    result?;
+
+    if tokens.is_empty() {
+        return Ok(sm::SearchQuery {
+            expr: None,
+            order: order.unwrap_or_default(),
+        });
+    }
+
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.parse_or()?;
+
+    // If there are tokens left, it can only be an unmatched closing parenthesis
+    if parser.peek().is_some() {
+        return Err(SearchQueryError::UnbalancedParentheses);
+    }
+
+    // Full-text searches are ordered by relevance, unless specified otherwise
+    let order = order.unwrap_or_else(|| {
+        if has_text_terms(&expr) {
+            sm::Order {
+                by: sm::SortBy::Rank,
+                ascending: false,
+            }
+        } else {
+            sm::Order::default()
+        }
+    });
+
+    Ok(sm::SearchQuery {
+        expr: Some(expr),
+        order,
+    })
+}
+
+/// Build a search query from lists of included and excluded tags,
+/// optionally combined with a query string
+pub fn build_search_query(
+    include_tags: Vec<String>,
+    exclude_tags: Vec<String>,
+    query: Option<&str>,
+) -> Result<sm::SearchQuery, SearchQueryError> {
+    let mut search = match query {
+        Some(query) => parse_search_query(query)?,
+        None => sm::SearchQuery::default(),
+    };
+
+    for tag in include_tags.into_iter().filter(|t| !t.is_empty()) {
+        search = search.and(sm::Expr::Term(sm::Term::Tag(tag)));
+    }
+
+    for tag in exclude_tags.into_iter().filter(|t| !t.is_empty()) {
+        search = search.and(sm::Expr::Not(Box::new(sm::Expr::Term(sm::Term::Tag(tag)))));
+    }
+
+    Ok(search)
+}
+
+/// Limit a search query to posts rated at most `max_rating`,
+/// unless the query already filters by rating
+pub fn apply_max_rating(query: sm::SearchQuery, max_rating: vm::Rating) -> sm::SearchQuery {
+    fn has_rating_terms(expr: &sm::Expr) -> bool {
+        match expr {
+            sm::Expr::And(exprs) | sm::Expr::Or(exprs) => exprs.iter().any(has_rating_terms),
+            sm::Expr::Not(expr) => has_rating_terms(expr),
+            sm::Expr::Term(term) => matches!(term, sm::Term::Rating(_)),
+        }
+    }
+
+    if max_rating == vm::Rating::Explicit || query.expr.as_ref().is_some_and(has_rating_terms) {
+        return query;
+    }
+
+    let ratings = vm::Rating::ALL.into_iter().filter(|r| *r <= max_rating).collect();
+
+    query.and(sm::Expr::Term(sm::Term::Rating(ratings)))
+}
+
+/// Get the keyset cursor to start a page from
+///
+/// For the default ID order, the post ID alone is sufficient.
+/// For other orders, the sort key of the post must also be specified.
+pub fn page_cursor(
+    order: &sm::Order,
+    start_id: Option<i32>,
+    start_key: Option<i64>,
+) -> Result<Option<sm::Cursor>, SearchQueryError> {
+    match (start_id, start_key) {
+        (Some(id), Some(key)) => Ok(Some(sm::Cursor { key, id })),
+        (Some(id), None) if order.by == sm::SortBy::Id => Ok(Some(sm::Cursor { key: id.into(), id })),
+        (Some(_), None) => Err(SearchQueryError::MissingStartKey),
+        (None, _) => Ok(None),
+    }
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>, SearchQueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '|' => tokens.push(Token::Or),
+            '-' => tokens.push(Token::Not),
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => text.push(chars.next().ok_or(SearchQueryError::UnterminatedQuote)?),
+                        Some(c) => text.push(c),
+                        None => return Err(SearchQueryError::UnterminatedQuote),
+                    }
+                }
+
+                tokens.push(Token::Quoted(text));
+            }
+            c => {
+                let mut text = String::from(c);
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | '|' | '"') {
+                        break;
+                    }
+
+                    text.push(c);
+                    chars.next();
+                }
+
+                let token = match text.to_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    _ => Token::Word(text),
+                };
+
+                tokens.push(token);
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<sm::Expr, SearchQueryError> {
+        let mut exprs = vec![self.parse_and()?];
+
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            exprs.push(self.parse_and()?);
+        }
+
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            sm::Expr::Or(exprs)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<sm::Expr, SearchQueryError> {
+        let mut exprs = Vec::new();
+
+        loop {
+            match self.peek() {
+                None | Some(Token::Or) | Some(Token::Close) => break,
+                // AND is implied, so an explicit one can simply be skipped
+                Some(Token::And) => self.pos += 1,
+                _ => exprs.push(self.parse_unary()?),
+            }
+        }
+
+        match exprs.len() {
+            0 => Err(SearchQueryError::ExpectedTerm),
+            1 => Ok(exprs.remove(0)),
+            _ => Ok(sm::Expr::And(exprs)),
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<sm::Expr, SearchQueryError> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+
+            return Ok(sm::Expr::Not(Box::new(self.parse_unary()?)));
+        }
+
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<sm::Expr, SearchQueryError> {
+        match self.next() {
+            Some(Token::Open) => {
+                let expr = self.parse_or()?;
+                if self.next() != Some(Token::Close) {
+                    return Err(SearchQueryError::UnbalancedParentheses);
+                }
+
+                Ok(expr)
+            }
+            // Metatag with a quoted value
+            Some(Token::Word(word)) if word.ends_with(':') && matches!(self.peek(), Some(Token::Quoted(_))) => {
+                let Some(Token::Quoted(value)) = self.next() else {
+                    unreachable!()
+                };
+
+                Ok(sm::Expr::Term(parse_term(word + &value)?))
+            }
+            Some(Token::Word(word)) => Ok(sm::Expr::Term(parse_term(word)?)),
+            Some(Token::Quoted(text)) if !text.is_empty() => Ok(sm::Expr::Term(sm::Term::Tag(text))),
+            _ => Err(SearchQueryError::ExpectedTerm),
+        }
+    }
+}
+
+fn parse_term(word: String) -> Result<sm::Term, SearchQueryError> {
+    let Some((key, value)) = word.split_once(':') else {
+        return Ok(sm::Term::Tag(word));
+    };
+
+    let invalid = || SearchQueryError::InvalidValue(key.to_string(), value.to_string());
+
+    let term = match key.to_lowercase().as_str() {
+        "user" if !value.is_empty() => sm::Term::User(value.to_string()),
+        "fav" if !value.is_empty() => sm::Term::Fav(value.to_string()),
+        "pool" if !value.is_empty() => sm::Term::Pool(value.to_string()),
+        "parent" => match value.to_lowercase().as_str() {
+            "any" => sm::Term::HasParent(true),
+            "none" => sm::Term::HasParent(false),
+            _ => sm::Term::Parent(value.parse().map_err(|_| invalid())?),
+        },
+        "child" => match value.to_lowercase().as_str() {
+            "any" => sm::Term::HasChildren(true),
+            "none" => sm::Term::HasChildren(false),
+            _ => return Err(invalid()),
+        },
+        "note" => match value.to_lowercase().as_str() {
+            "any" => sm::Term::HasNotes(true),
+            "none" => sm::Term::HasNotes(false),
+            _ => return Err(invalid()),
+        },
+        "ext" if !value.is_empty() => sm::Term::Ext(value.trim_start_matches('.').to_lowercase()),
+        "id" => sm::Term::Id(parse_num_range(value).ok_or_else(invalid)?),
+        "width" => sm::Term::Width(parse_num_range(value).ok_or_else(invalid)?),
+        "height" => sm::Term::Height(parse_num_range(value).ok_or_else(invalid)?),
+        "size" => sm::Term::Size(parse_num_range(value).ok_or_else(invalid)?),
+        "score" => sm::Term::Score(parse_num_range(value).ok_or_else(invalid)?),
+        "date" => sm::Term::Date(parse_date_range(value).ok_or_else(invalid)?),
+        "text" if !value.trim().is_empty() => sm::Term::Text(value.to_string()),
+        "rating" => sm::Term::Rating(
+            value
+                .split(',')
+                .map(|r| r.parse().ok())
+                .collect::<Option<_>>()
+                .ok_or_else(invalid)?,
+        ),
+        "user" | "fav" | "pool" | "ext" | "text" => return Err(invalid()),
+        // Not a known metatag, so treat it as a regular tag
+        _ => sm::Term::Tag(word),
+    };
+
+    Ok(term)
+}
+
+fn has_text_terms(expr: &sm::Expr) -> bool {
+    match expr {
+        sm::Expr::And(exprs) | sm::Expr::Or(exprs) => exprs.iter().any(has_text_terms),
+        sm::Expr::Not(_) => false,
+        sm::Expr::Term(term) => matches!(term, sm::Term::Text(_)),
+    }
+}
+
+fn parse_order(value: &str) -> Option<sm::Order> {
+    let value = value.to_lowercase();
+
+    let (by, ascending) = if let Some(by) = value.strip_suffix("_asc") {
+        (by, true)
+    } else if let Some(by) = value.strip_suffix("_desc") {
+        (by, false)
+    } else {
+        (value.as_str(), false)
+    };
+
+    let by = match by {
+        "id" => sm::SortBy::Id,
+        "date" => sm::SortBy::Date,
+        "size" => sm::SortBy::Size,
+        "resolution" => sm::SortBy::Resolution,
+        "random" => sm::SortBy::Random(0),
+        "rank" => sm::SortBy::Rank,
+        "score" => sm::SortBy::Score,
+        _ => match by.strip_prefix("random:") {
+            Some(seed) => sm::SortBy::Random(seed.parse().ok()?),
+            None => return None,
+        },
+    };
+
+    Some(sm::Order { by, ascending })
+}
+
+fn parse_num_range(value: &str) -> Option<sm::NumRange> {
+    let parse = |s: &str| s.parse::<i32>().ok();
+
+    let range = if let Some(v) = value.strip_prefix(">=") {
+        sm::NumRange {
+            min: Some(parse(v)?),
+            max: None,
+        }
+    } else if let Some(v) = value.strip_prefix('>') {
+        sm::NumRange {
+            min: Some(parse(v)?.checked_add(1)?),
+            max: None,
+        }
+    } else if let Some(v) = value.strip_prefix("<=") {
+        sm::NumRange {
+            min: None,
+            max: Some(parse(v)?),
+        }
+    } else if let Some(v) = value.strip_prefix('<') {
+        sm::NumRange {
+            min: None,
+            max: Some(parse(v)?.checked_sub(1)?),
+        }
+    } else if let Some((a, b)) = value.split_once("..") {
+        if a.is_empty() && b.is_empty() {
+            return None;
+        }
+
+        sm::NumRange {
+            min: if a.is_empty() { None } else { Some(parse(a)?) },
+            max: if b.is_empty() { None } else { Some(parse(b)?) },
+        }
+    } else {
+        let v = parse(value)?;
+
+        sm::NumRange {
+            min: Some(v),
+            max: Some(v),
+        }
+    };
+
+    Some(range)
+}
+
+fn parse_date_range(value: &str) -> Option<sm::DateRange> {
+    let range = if let Some(v) = value.strip_prefix(">=") {
+        sm::DateRange {
+            start: Some(parse_date_period(v)?.0),
+            end: None,
+        }
+    } else if let Some(v) = value.strip_prefix('>') {
+        sm::DateRange {
+            start: Some(parse_date_period(v)?.1),
+            end: None,
+        }
+    } else if let Some(v) = value.strip_prefix("<=") {
+        sm::DateRange {
+            start: None,
+            end: Some(parse_date_period(v)?.1),
+        }
+    } else if let Some(v) = value.strip_prefix('<') {
+        sm::DateRange {
+            start: None,
+            end: Some(parse_date_period(v)?.0),
+        }
+    } else if let Some((a, b)) = value.split_once("..") {
+        if a.is_empty() && b.is_empty() {
+            return None;
+        }
+
+        sm::DateRange {
+            start: if a.is_empty() {
+                None
+            } else {
+                Some(parse_date_period(a)?.0)
+            },
+            end: if b.is_empty() {
+                None
+            } else {
+                Some(parse_date_period(b)?.1)
+            },
+        }
+    } else {
+        let (start, end) = parse_date_period(value)?;
+
+        sm::DateRange {
+            start: Some(start),
+            end: Some(end),
+        }
+    };
+
+    Some(range)
+}
+
+/// Parse a year (`YYYY`), month (`YYYY-MM`) or day (`YYYY-MM-DD`)
+/// into the start and (exclusive) end of that period
+fn parse_date_period(value: &str) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let parts = value
+        .split('-')
+        .map(|p| p.parse::<u32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    let (start, end) = match parts[..] {
+        [y] => {
+            let start = NaiveDate::from_ymd_opt(y.try_into().ok()?, 1, 1)?;
+            (start, start.checked_add_months(Months::new(12))?)
+        }
+        [y, m] => {
+            let start = NaiveDate::from_ymd_opt(y.try_into().ok()?, m, 1)?;
+            (start, start.checked_add_months(Months::new(1))?)
+        }
+        [y, m, d] => {
+            let start = NaiveDate::from_ymd_opt(y.try_into().ok()?, m, d)?;
+            (start, start.succ_opt()?)
+        }
+        _ => return None,
+    };
+
+    Some((
+        start.and_hms_opt(0, 0, 0)?.and_utc(),
+        end.and_hms_opt(0, 0, 0)?.and_utc(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn tag(name: &str) -> sm::Expr {
+        sm::Expr::Term(sm::Term::Tag(name.to_string()))
+    }
+
+    fn not(expr: sm::Expr) -> sm::Expr {
+        sm::Expr::Not(Box::new(expr))
+    }
+
+    fn parse_expr(query: &str) -> sm::Expr {
+        parse_search_query(query).unwrap().expr.unwrap()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        let query = parse_search_query("   ").unwrap();
+
+        assert_eq!(query.expr, None);
+        assert_eq!(query.order, sm::Order::default());
+    }
+
+    #[test]
+    fn terms_are_combined_with_and() {
+        assert_eq!(parse_expr("cat dog"), sm::Expr::And(vec![tag("cat"), tag("dog")]));
+        assert_eq!(parse_expr("cat and dog"), sm::Expr::And(vec![tag("cat"), tag("dog")]));
+    }
+
+    #[test]
+    fn or_binds_weaker_than_and() {
+        assert_eq!(
+            parse_expr("cat dog | bird"),
+            sm::Expr::Or(vec![sm::Expr::And(vec![tag("cat"), tag("dog")]), tag("bird")]),
+        );
+        assert_eq!(
+            parse_expr("cat (dog or bird)"),
+            sm::Expr::And(vec![tag("cat"), sm::Expr::Or(vec![tag("dog"), tag("bird")])]),
+        );
+    }
+
+    #[test]
+    fn negation() {
+        assert_eq!(parse_expr("cat -dog"), sm::Expr::And(vec![tag("cat"), not(tag("dog"))]));
+        assert_eq!(
+            parse_expr("-(cat | dog)"),
+            not(sm::Expr::Or(vec![tag("cat"), tag("dog")])),
+        );
+    }
+
+    #[test]
+    fn quoted_tags_and_values() {
+        assert_eq!(parse_expr(r#""a (b) \"c\"""#), tag(r#"a (b) "c""#));
+        assert_eq!(
+            parse_expr(r#"user:"some name""#),
+            sm::Expr::Term(sm::Term::User("some name".to_string())),
+        );
+    }
+
+    #[test]
+    fn unknown_metatags_are_tags() {
+        assert_eq!(parse_expr("artist:someone"), tag("artist:someone"));
+    }
+
+    #[test]
+    fn metatags() {
+        assert_eq!(parse_expr("parent:12"), sm::Expr::Term(sm::Term::Parent(12)));
+        assert_eq!(parse_expr("parent:none"), sm::Expr::Term(sm::Term::HasParent(false)));
+        assert_eq!(parse_expr("child:any"), sm::Expr::Term(sm::Term::HasChildren(true)));
+        assert_eq!(parse_expr("ext:.GIF"), sm::Expr::Term(sm::Term::Ext("gif".to_string())));
+        assert_eq!(
+            parse_expr("rating:g,Explicit"),
+            sm::Expr::Term(sm::Term::Rating(vec![vm::Rating::General, vm::Rating::Explicit])),
+        );
+    }
+
+    #[test]
+    fn numeric_ranges() {
+        let range = |min, max| sm::NumRange { min, max };
+
+        assert_eq!(parse_num_range("5"), Some(range(Some(5), Some(5))));
+        assert_eq!(parse_num_range(">5"), Some(range(Some(6), None)));
+        assert_eq!(parse_num_range(">=5"), Some(range(Some(5), None)));
+        assert_eq!(parse_num_range("<5"), Some(range(None, Some(4))));
+        assert_eq!(parse_num_range("<=5"), Some(range(None, Some(5))));
+        assert_eq!(parse_num_range("2..8"), Some(range(Some(2), Some(8))));
+        assert_eq!(parse_num_range("2.."), Some(range(Some(2), None)));
+        assert_eq!(parse_num_range("..8"), Some(range(None, Some(8))));
+        assert_eq!(parse_num_range(".."), None);
+        assert_eq!(parse_num_range("abc"), None);
+        assert_eq!(parse_num_range(&format!(">{}", i32::MAX)), None);
+    }
+
+    #[test]
+    fn date_ranges() {
+        assert_eq!(
+            parse_date_range("2025"),
+            Some(sm::DateRange {
+                start: Some(date(2025, 1, 1)),
+                end: Some(date(2026, 1, 1)),
+            }),
+        );
+        assert_eq!(
+            parse_date_range("2025-01..2025-03"),
+            Some(sm::DateRange {
+                start: Some(date(2025, 1, 1)),
+                end: Some(date(2025, 4, 1)),
+            }),
+        );
+        assert_eq!(
+            parse_date_range(">2025-02-28"),
+            Some(sm::DateRange {
+                start: Some(date(2025, 3, 1)),
+                end: None,
+            }),
+        );
+        assert_eq!(parse_date_range("2025-13"), None);
+    }
+
+    #[test]
+    fn sort_order() {
+        let query = parse_search_query("cat order:size_asc").unwrap();
+        assert_eq!(query.expr, Some(tag("cat")));
+        assert_eq!(
+            query.order,
+            sm::Order {
+                by: sm::SortBy::Size,
+                ascending: true,
+            },
+        );
+
+        let query = parse_search_query("order:score order:random:42").unwrap();
+        assert_eq!(query.order.by, sm::SortBy::Random(42));
+    }
+
+    #[test]
+    fn text_searches_are_ordered_by_rank() {
+        let query = parse_search_query("text:harbor").unwrap();
+        assert_eq!(query.order.by, sm::SortBy::Rank);
+
+        // Negated text terms don't rank the results
+        let query = parse_search_query("cat -text:harbor").unwrap();
+        assert_eq!(query.order.by, sm::SortBy::Id);
+    }
+
+    #[test]
+    fn invalid_queries() {
+        assert!(matches!(
+            parse_search_query(r#"cat "dog"#),
+            Err(SearchQueryError::UnterminatedQuote)
+        ));
+        assert!(matches!(
+            parse_search_query("(cat dog"),
+            Err(SearchQueryError::UnbalancedParentheses)
+        ));
+        assert!(matches!(
+            parse_search_query("cat dog)"),
+            Err(SearchQueryError::UnbalancedParentheses)
+        ));
+        assert!(matches!(
+            parse_search_query("cat |"),
+            Err(SearchQueryError::ExpectedTerm)
+        ));
+        assert!(matches!(parse_search_query("-"), Err(SearchQueryError::ExpectedTerm)));
+        assert!(matches!(
+            parse_search_query("width:wide"),
+            Err(SearchQueryError::InvalidValue(..))
+        ));
+        assert!(matches!(
+            parse_search_query("order:color"),
+            Err(SearchQueryError::InvalidOrder(_))
+        ));
+    }
+
+    #[test]
+    fn included_and_excluded_tags_are_added_to_the_query() {
+        let query = build_search_query(
+            vec!["cat".to_string(), String::new()],
+            vec!["dog".to_string()],
+            Some("bird | fish"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            query.expr,
+            Some(sm::Expr::And(vec![
+                sm::Expr::Or(vec![tag("bird"), tag("fish")]),
+                tag("cat"),
+                not(tag("dog")),
+            ])),
+        );
+    }
+}
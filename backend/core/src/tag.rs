@@ -1,8 +1,12 @@
+use blazebooru_models::search as sm;
 use blazebooru_models::view as vm;
 use blazebooru_store::models as dbm;
 
 use super::BlazeBooruCore;
 
+/// Number of posts edited per transaction by a tag edit job
+const TAG_EDIT_JOB_BATCH_SIZE: i32 = 100;
+
 impl BlazeBooruCore {
     pub async fn get_view_tag(&self, id: i32) -> Result<Option<vm::Tag>, anyhow::Error> {
         let tag = self.store.get_view_tag(id).await?.map(vm::Tag::from);
@@ -22,10 +26,85 @@ impl BlazeBooruCore {
         Ok(tags)
     }
 
+    pub async fn autocomplete_tags(&self, query: &str, limit: i32) -> Result<Vec<vm::AutocompleteTag>, anyhow::Error> {
+        let tags = self
+            .store
+            .autocomplete_tags(&query.trim().to_lowercase(), limit)
+            .await?
+            .into_iter()
+            .map(vm::AutocompleteTag::from)
+            .collect();
+
+        Ok(tags)
+    }
+
+    pub async fn get_search_tags(
+        &self,
+        query: &sm::SearchQuery,
+        limit: i32,
+    ) -> Result<Vec<vm::SearchTag>, anyhow::Error> {
+        let tags = self
+            .store
+            .get_search_tags(query, limit)
+            .await?
+            .into_iter()
+            .map(vm::SearchTag::from)
+            .collect();
+
+        Ok(tags)
+    }
+
     pub async fn update_tag(&self, id: i32, request: vm::UpdateTag, user_id: i32) -> Result<bool, anyhow::Error> {
         let update_tag = dbm::UpdateTag::from(request);
         let success = self.store.update_tag(id, &update_tag, user_id).await?;
 
         Ok(success)
     }
+
+    /// Create a job editing the tags of all posts currently matching the search query.
+    /// The job must then be run with `run_tag_edit_job`.
+    pub async fn create_tag_edit_job(
+        &self,
+        query: &sm::SearchQuery,
+        request: vm::NewTagEditJob,
+        user_id: i32,
+    ) -> Result<i32, anyhow::Error> {
+        let post_ids = self.store.get_search_post_ids(query).await?;
+
+        let job = dbm::NewTagEditJob::from(request);
+        let job_id = self.store.create_tag_edit_job(&job, &post_ids, user_id).await?;
+
+        Ok(job_id)
+    }
+
+    pub async fn get_tag_edit_job(&self, id: i32) -> Result<Option<vm::TagEditJob>, anyhow::Error> {
+        let job = self.store.get_tag_edit_job(id).await?.map(vm::TagEditJob::from);
+
+        Ok(job)
+    }
+
+    /// Get the IDs of the tag edit jobs that have not completed or failed,
+    /// such as jobs interrupted by a restart
+    pub async fn get_running_tag_edit_job_ids(&self) -> Result<Vec<i32>, anyhow::Error> {
+        let job_ids = self.store.get_running_tag_edit_job_ids().await?;
+
+        Ok(job_ids)
+    }
+
+    /// Run a tag edit job to completion, one batch of posts at a time,
+    /// continuing after the posts already processed.
+    /// The job is marked as failed if a batch fails.
+    pub async fn run_tag_edit_job(&self, id: i32) -> Result<(), anyhow::Error> {
+        loop {
+            match self.store.run_tag_edit_job_batch(id, TAG_EDIT_JOB_BATCH_SIZE).await {
+                Ok(0) => return Ok(()),
+                Ok(_) => {}
+                Err(err) => {
+                    self.store.fail_tag_edit_job(id).await?;
+
+                    return Err(err.into());
+                }
+            }
+        }
+    }
 }
@@ -45,13 +45,13 @@ impl BlazeBooruCore {
     pub async fn get_user_by_name(&self, name: &str) -> Result<Option<vm::User>, anyhow::Error> {
         let user = self.store.get_user_by_name(name).await?;
 
-        Ok(user.map(vm::User::from))
+        user.map(vm::User::try_from).transpose()
     }
 
     pub async fn get_user_profile(&self, user_id: i32) -> Result<Option<vm::User>, anyhow::Error> {
         let user = self.store.get_user(user_id).await?;
 
-        Ok(user.map(vm::User::from))
+        user.map(vm::User::try_from).transpose()
     }
 
     pub async fn get_public_user_profile(&self, username: &str) -> Result<Option<vm::PublicUser>, anyhow::Error> {
@@ -0,0 +1,269 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use anyhow::{Context as _, anyhow};
+
+use crate::ArchiveKind;
+use crate::file::FileTooLargeError;
+use crate::util::temp_path::TempPath;
+
+pub struct ExtractedEntry {
+    /// File name of the entry, without its directory
+    pub filename: String,
+    /// Path of the extracted file, or the reason the entry could not be extracted
+    pub path: Result<TempPath, anyhow::Error>,
+}
+
+/// Limits on the files extracted from an archive
+#[derive(Debug, Clone, Copy)]
+pub struct ExtractLimits {
+    /// Maximum size of a single file. Larger files are not extracted.
+    pub max_entry_size: u64,
+    /// Maximum total size of the extracted files
+    pub max_total_size: u64,
+    /// Maximum number of files
+    pub max_entries: usize,
+}
+
+/// Extract the files in an archive into a directory, each under a random name.
+/// Directories and hidden files are skipped.
+/// Fails without leaving any files behind if the archive has more files or
+/// larger contents than allowed by the limits.
+pub fn extract_archive(
+    kind: ArchiveKind,
+    archive_path: &Path,
+    destination_path: &Path,
+    limits: ExtractLimits,
+) -> anyhow::Result<Vec<ExtractedEntry>> {
+    let file = File::open(archive_path).context("Error opening archive")?;
+
+    let mut extractor = Extractor {
+        destination_path,
+        limits,
+        total_size: 0,
+        entries: Vec::new(),
+    };
+
+    match kind {
+        ArchiveKind::Zip => {
+            let mut archive = zip::ZipArchive::new(file).context("Error reading ZIP archive")?;
+
+            // The number of files is known beforehand, so nothing is extracted if there are too many
+            let file_count = archive.file_names().filter(|name| !name.ends_with('/')).count();
+            if file_count > limits.max_entries {
+                return Err(too_many_entries(limits.max_entries));
+            }
+
+            for i in 0..archive.len() {
+                let entry = archive.by_index(i).context("Error reading ZIP archive entry")?;
+                if !entry.is_file() {
+                    continue;
+                }
+
+                let Some(filename) = entry_filename(Path::new(entry.name())) else {
+                    continue;
+                };
+
+                extractor.extract(filename, entry)?;
+            }
+        }
+        ArchiveKind::Tar => {
+            let mut archive = tar::Archive::new(file);
+
+            for entry in archive.entries().context("Error reading TAR archive")? {
+                let entry = entry.context("Error reading TAR archive entry")?;
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+
+                let Some(filename) = entry_filename(&entry.path().context("Error reading TAR archive entry path")?)
+                else {
+                    continue;
+                };
+
+                extractor.extract(filename, entry)?;
+            }
+        }
+    }
+
+    Ok(extractor.entries)
+}
+
+fn too_many_entries(max_entries: usize) -> anyhow::Error {
+    anyhow!("Archive contains more than {max_entries} files")
+}
+
+/// Extracts archive entries, keeping track of the limits.
+/// Extracted files are removed if extraction fails, as they are dropped.
+struct Extractor<'a> {
+    destination_path: &'a Path,
+    limits: ExtractLimits,
+    total_size: u64,
+    entries: Vec<ExtractedEntry>,
+}
+
+impl Extractor<'_> {
+    fn extract(&mut self, filename: String, entry: impl Read) -> anyhow::Result<()> {
+        let ExtractLimits {
+            max_entry_size,
+            max_total_size,
+            max_entries,
+        } = self.limits;
+
+        if self.entries.len() >= max_entries {
+            return Err(too_many_entries(max_entries));
+        }
+
+        let remaining_size = max_total_size.saturating_sub(self.total_size);
+        let path = TempPath::new(self.destination_path.join(uuid::Uuid::new_v4().to_string()));
+
+        // Read one byte more than allowed to detect oversized entries,
+        // without trusting the size stored in the archive.
+        let size = extract_entry(entry, &path, max_entry_size.min(remaining_size) + 1)?;
+
+        let path = if size > max_entry_size {
+            Err(FileTooLargeError(max_entry_size as usize).into())
+        } else if size > remaining_size {
+            return Err(anyhow!(
+                "Archive contents are larger than the maximum total size of {max_total_size} bytes"
+            ));
+        } else {
+            self.total_size += size;
+            Ok(path)
+        };
+
+        self.entries.push(ExtractedEntry { filename, path });
+
+        Ok(())
+    }
+}
+
+/// Get the file name of an archive entry, unless it is hidden or metadata (such as `__MACOSX/`)
+fn entry_filename(path: &Path) -> Option<String> {
+    if path.components().any(|c| c.as_os_str() == "__MACOSX") {
+        return None;
+    }
+
+    let filename = path.file_name()?.to_str()?;
+    if filename.starts_with('.') {
+        return None;
+    }
+
+    Some(filename.to_string())
+}
+
+/// Extract at most `max_size` bytes of an entry to a file, returning the number of bytes extracted
+fn extract_entry(entry: impl Read, path: &Path, max_size: u64) -> anyhow::Result<u64> {
+    let mut file = File::create(path).context("Error creating file for archive entry")?;
+
+    let size = io::copy(&mut entry.take(max_size), &mut file).context("Error extracting archive entry")?;
+
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// Temporary directory, which is removed when dropped
+    struct TestDir(PathBuf);
+
+    impl TestDir {
+        fn new() -> Self {
+            let path = std::env::temp_dir().join(format!("blazebooru-test-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&path).unwrap();
+
+            Self(path)
+        }
+
+        /// Write a TAR archive with files of the given sizes
+        fn write_tar(&self, sizes: &[usize]) -> PathBuf {
+            let path = self.0.join("archive.tar");
+            let mut builder = tar::Builder::new(File::create(&path).unwrap());
+
+            for (i, size) in sizes.iter().enumerate() {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(*size as u64);
+                header.set_mode(0o644);
+                header.set_cksum();
+
+                builder
+                    .append_data(&mut header, format!("dir/{i}.png"), io::repeat(0).take(*size as u64))
+                    .unwrap();
+            }
+
+            builder.finish().unwrap();
+
+            path
+        }
+
+        fn extracted_file_count(&self) -> usize {
+            std::fs::read_dir(&self.0).unwrap().count() - 1
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            std::fs::remove_dir_all(&self.0).ok();
+        }
+    }
+
+    const LIMITS: ExtractLimits = ExtractLimits {
+        max_entry_size: 100,
+        max_total_size: 250,
+        max_entries: 3,
+    };
+
+    #[test]
+    fn extracts_files() {
+        let dir = TestDir::new();
+        let archive_path = dir.write_tar(&[100, 50]);
+
+        let entries = extract_archive(ArchiveKind::Tar, &archive_path, &dir.0, LIMITS).unwrap();
+
+        let filenames: Vec<_> = entries.iter().map(|e| e.filename.as_str()).collect();
+        assert_eq!(filenames, ["0.png", "1.png"]);
+        assert!(entries.iter().all(|e| e.path.is_ok()));
+        assert_eq!(dir.extracted_file_count(), 2);
+
+        drop(entries);
+        assert_eq!(dir.extracted_file_count(), 0);
+    }
+
+    #[test]
+    fn rejects_large_entries() {
+        let dir = TestDir::new();
+        let archive_path = dir.write_tar(&[101, 50]);
+
+        let entries = extract_archive(ArchiveKind::Tar, &archive_path, &dir.0, LIMITS).unwrap();
+
+        assert!(entries[0].path.is_err());
+        assert!(entries[1].path.is_ok());
+        assert_eq!(dir.extracted_file_count(), 1);
+    }
+
+    #[test]
+    fn rejects_archives_larger_than_total_size() {
+        let dir = TestDir::new();
+        let archive_path = dir.write_tar(&[100, 100, 100]);
+
+        let result = extract_archive(ArchiveKind::Tar, &archive_path, &dir.0, LIMITS);
+
+        assert!(result.is_err());
+        assert_eq!(dir.extracted_file_count(), 0);
+    }
+
+    #[test]
+    fn rejects_archives_with_too_many_entries() {
+        let dir = TestDir::new();
+        let archive_path = dir.write_tar(&[1, 1, 1, 1]);
+
+        let result = extract_archive(ArchiveKind::Tar, &archive_path, &dir.0, LIMITS);
+
+        assert!(result.is_err());
+        assert_eq!(dir.extracted_file_count(), 0);
+    }
+}
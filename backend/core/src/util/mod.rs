@@ -1,2 +1,5 @@
+pub mod archive;
 pub mod image;
+pub mod phash;
+pub mod temp_path;
 pub mod thumbnail;
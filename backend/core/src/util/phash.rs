@@ -0,0 +1,84 @@
+use std::{path::Path, process::Command};
+
+use anyhow::{Context, anyhow};
+use image::{DynamicImage, ImageReader, imageops::FilterType};
+
+const HASH_WIDTH: u32 = 9;
+const HASH_HEIGHT: u32 = 8;
+
+/// Number of frames between sampled frames of animations and videos
+const FRAME_SAMPLE_INTERVAL: u32 = 30;
+/// Maximum number of frames to sample from animations and videos
+const MAX_SAMPLED_FRAMES: u32 = 8;
+
+/// Compute the difference hash (dHash) of an image
+pub fn dhash_image(img: &DynamicImage) -> i64 {
+    let img = img
+        .resize_exact(HASH_WIDTH, HASH_HEIGHT, FilterType::Triangle)
+        .into_luma8();
+
+    dhash_luma(img.as_raw())
+}
+
+/// Compute the perceptual hash of a still image file
+pub fn compute_image_phash(path: &Path) -> anyhow::Result<i64> {
+    // Detect the format from the content, as the file may not have an extension
+    let img = ImageReader::open(path)
+        .context("Error opening image")?
+        .with_guessed_format()
+        .context("Error reading image")?
+        .decode()
+        .context("Error decoding image")?;
+
+    Ok(dhash_image(&img))
+}
+
+/// Compute the perceptual hashes of frames sampled from an animation or video file
+pub fn compute_video_phashes(path: &Path) -> anyhow::Result<Vec<i64>> {
+    let filter_arg = format!(
+        r"select=not(mod(n\,{FRAME_SAMPLE_INTERVAL})),scale={HASH_WIDTH}:{HASH_HEIGHT}:flags=bilinear,format=gray"
+    );
+
+    let output = Command::new("ffmpeg")
+        .args(["-hide_banner", "-v", "error"])
+        .arg("-i")
+        .arg(path)
+        .args(["-filter:v", &filter_arg, "-fps_mode", "vfr"])
+        .args(["-frames:v", &MAX_SAMPLED_FRAMES.to_string()])
+        .args(["-f", "rawvideo", "-pix_fmt", "gray", "-"])
+        .output()
+        .context("Executing ffmpeg")?;
+
+    if !output.status.success() {
+        return Err(anyhow!("Error sampling frames"));
+    }
+
+    let mut hashes: Vec<i64> = output
+        .stdout
+        .chunks_exact((HASH_WIDTH * HASH_HEIGHT) as usize)
+        .map(dhash_luma)
+        .collect();
+
+    // Static scenes produce identical hashes
+    hashes.sort_unstable();
+    hashes.dedup();
+
+    if hashes.is_empty() {
+        return Err(anyhow!("No frames found"));
+    }
+
+    Ok(hashes)
+}
+
+/// Compute a dHash from 9x8 grayscale pixels, one bit per horizontally adjacent pixel pair
+fn dhash_luma(pixels: &[u8]) -> i64 {
+    let mut hash: u64 = 0;
+
+    for row in pixels.chunks_exact(HASH_WIDTH as usize) {
+        for pair in row.windows(2) {
+            hash = (hash << 1) | u64::from(pair[0] < pair[1]);
+        }
+    }
+
+    hash as i64
+}
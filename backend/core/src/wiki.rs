@@ -29,6 +29,12 @@ impl BlazeBooruCore {
         Ok(wiki_pages.into_iter().map(vm::WikiPage::from).collect())
     }
 
+    pub async fn search_wiki_pages(&self, query: &str, limit: i32) -> Result<Vec<vm::WikiPage>, anyhow::Error> {
+        let wiki_pages = self.store.search_wiki_pages(query, limit).await?;
+
+        Ok(wiki_pages.into_iter().map(vm::WikiPage::from).collect())
+    }
+
     pub async fn update_wiki_page(&self, user_id: i32, user_ip: IpAddr, new_wiki_page: vm::UpdateWikiPage) -> Result<bool, anyhow::Error> {
         let res = self.store.update_wiki_page(user_id, user_ip, &new_wiki_page.into()).await?;
 
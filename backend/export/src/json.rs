@@ -6,7 +6,7 @@ use blazebooru_core::BlazeBooruCore;
 
 pub async fn export_json(path: &Path, core: &BlazeBooruCore) -> Result<(), anyhow::Error> {
     let posts = core
-        .get_export_posts(vec![], vec![], i32::MAX, i32::MAX)
+        .get_export_posts()
         .await
         .context("Error retrieving posts")?;
 
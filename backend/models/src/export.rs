@@ -1,6 +1,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::view as vm;
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Post {
     pub created_at: DateTime<Utc>,
@@ -15,5 +17,23 @@ pub struct Post {
     pub hash: String,
     pub ext: String,
     pub tn_ext: String,
+    #[serde(default)]
+    pub renditions: Vec<String>,
     pub tags: Vec<String>,
+    #[serde(default)]
+    pub rating: vm::Rating,
+    /// Whether the post is awaiting approval
+    #[serde(default)]
+    pub is_pending: bool,
+    #[serde(default)]
+    pub notes: Vec<Note>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Note {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    pub body: String,
 }
@@ -1,3 +1,4 @@
 pub mod export;
 pub mod local;
+pub mod search;
 pub mod view;
@@ -1,12 +1,14 @@
 use std::{borrow::Cow, path::PathBuf};
 
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::view as vm;
 
 pub type Post = vm::Post;
 
+/// Hashed temporary file, which is removed when dropped unless it is kept
 #[derive(Debug)]
 pub struct HashedFile {
     pub hash: String,
@@ -14,6 +16,21 @@ pub struct HashedFile {
     pub size: usize,
 }
 
+impl HashedFile {
+    /// Keep the file, after it has been moved elsewhere
+    pub fn keep(mut self) {
+        self.path = PathBuf::new();
+    }
+}
+
+impl Drop for HashedFile {
+    fn drop(&mut self) {
+        if !self.path.as_os_str().is_empty() {
+            std::fs::remove_file(&self.path).ok();
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct User {
     pub id: i32,
@@ -31,6 +48,7 @@ pub struct NewPost<'a> {
     pub filename: Cow<'a, str>,
     pub file: HashedFile,
     pub tags: Vec<&'a str>,
+    pub rating: vm::Rating,
 }
 
 #[derive(Debug)]
@@ -51,3 +69,14 @@ pub struct RefreshRefreshTokenResult {
     pub session: i64,
     pub user_id: i32,
 }
+
+/// Filter for selecting posts, including deleted and pending ones, in maintenance tasks
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostFilter {
+    /// Posts must have all of these tags
+    pub tags: Vec<String>,
+    /// Posts must have one of these file extensions, or any extension if empty
+    pub exts: Vec<String>,
+    pub min_id: Option<i32>,
+    pub max_id: Option<i32>,
+}
@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+
+use crate::view as vm;
+
+/// Parsed post search query
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    /// Filter expression. If `None`, all posts match.
+    pub expr: Option<Expr>,
+    /// Sort order (`order:`)
+    pub order: Order,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Order {
+    pub by: SortBy,
+    pub ascending: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortBy {
+    /// Post ID
+    #[default]
+    Id,
+    /// Upload date
+    Date,
+    /// File size
+    Size,
+    /// Resolution (width * height)
+    Resolution,
+    /// Pseudo-random order, stable for a given seed
+    Random(i64),
+    /// Full-text search relevance of the query's `text:` terms
+    Rank,
+    /// Vote score
+    Score,
+}
+
+/// Keyset pagination cursor, identifying the position of a post in a sort order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    /// Sort key of the post
+    pub key: i64,
+    /// ID of the post
+    pub id: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+    Term(Term),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Term {
+    /// Post has tag (or an alias of it)
+    Tag(String),
+    /// Post was uploaded by user (`user:name`)
+    User(String),
+    /// Post was favorited by user (`fav:name`)
+    Fav(String),
+    /// Post is in pool, by ID or name (`pool:12`, `pool:"some comic"`)
+    Pool(String),
+    /// Post is the specified post or one of its children (`parent:123`)
+    Parent(i32),
+    /// Post has a parent (`parent:any`) or not (`parent:none`)
+    HasParent(bool),
+    /// Post has children (`child:any`) or not (`child:none`)
+    HasChildren(bool),
+    /// Post has notes (`note:any`) or not (`note:none`)
+    HasNotes(bool),
+    /// Original file extension (`ext:gif`)
+    Ext(String),
+    /// Post ID (`id:<500`)
+    Id(NumRange),
+    /// Image width (`width:>1920`)
+    Width(NumRange),
+    /// Image height (`height:>1080`)
+    Height(NumRange),
+    /// File size in bytes (`size:<1000000`)
+    Size(NumRange),
+    /// Vote score (`score:>10`)
+    Score(NumRange),
+    /// Upload date (`date:2025-01..2025-03`)
+    Date(DateRange),
+    /// Full-text search in title and description (`text:"old harbor"`)
+    Text(String),
+    /// Post has one of the ratings (`rating:g,s`)
+    Rating(Vec<vm::Rating>),
+}
+
+/// Inclusive numeric range. A missing bound is unbounded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NumRange {
+    pub min: Option<i32>,
+    pub max: Option<i32>,
+}
+
+/// Half-open date range (`start <= date < end`). A missing bound is unbounded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DateRange {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl SearchQuery {
+    /// Combine the query's expression with another expression using AND
+    pub fn and(mut self, expr: Expr) -> Self {
+        self.expr = Some(match self.expr {
+            Some(Expr::And(mut exprs)) => {
+                exprs.push(expr);
+                Expr::And(exprs)
+            }
+            Some(existing) => Expr::And(vec![existing, expr]),
+            None => expr,
+        });
+
+        self
+    }
+}
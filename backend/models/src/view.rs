@@ -1,3 +1,5 @@
+use std::str::FromStr;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -17,7 +19,154 @@ pub struct Post {
     pub hash: String,
     pub ext: String,
     pub tn_ext: String,
+    /// Names of the resized renditions of the file, each available under its own directory
+    pub renditions: Vec<String>,
+    pub tags: Vec<String>,
+    pub rating: Rating,
+    pub fav_count: i32,
+    pub score: i32,
+    /// IDs of the pools containing the post
+    pub pools: Vec<i32>,
+    pub parent_id: Option<i32>,
+    pub child_ids: Vec<i32>,
+}
+
+/// Content rating of a post, from safest to least safe
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rating {
+    #[default]
+    General,
+    Sensitive,
+    Questionable,
+    Explicit,
+}
+
+impl Rating {
+    pub const ALL: [Rating; 4] = [Rating::General, Rating::Sensitive, Rating::Questionable, Rating::Explicit];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Rating::General => "general",
+            Rating::Sensitive => "sensitive",
+            Rating::Questionable => "questionable",
+            Rating::Explicit => "explicit",
+        }
+    }
+}
+
+impl FromStr for Rating {
+    type Err = ();
+
+    /// Parse a rating from its name or first letter
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "general" | "g" => Ok(Rating::General),
+            "sensitive" | "s" => Ok(Rating::Sensitive),
+            "questionable" | "q" => Ok(Rating::Questionable),
+            "explicit" | "e" => Ok(Rating::Explicit),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Deleted post awaiting restoration or purging
+#[derive(Debug, Serialize)]
+pub struct DeletedPost {
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub user_id: i32,
+    pub user_name: String,
+    pub title: Option<String>,
+    pub filename: String,
+    pub hash: String,
+    pub ext: String,
+    pub tn_ext: String,
     pub tags: Vec<String>,
+    pub rating: Rating,
+}
+
+/// Post awaiting approval before it is shown to other users
+#[derive(Debug, Serialize)]
+pub struct PendingPost {
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub user_id: i32,
+    pub user_name: String,
+    pub title: Option<String>,
+    pub filename: String,
+    pub hash: String,
+    pub ext: String,
+    pub tn_ext: String,
+    pub tags: Vec<String>,
+    pub rating: Rating,
+}
+
+/// Post with a perceptual hash similar to a searched image
+#[derive(Debug, Serialize)]
+pub struct SimilarPost {
+    /// Hamming distance between the perceptual hashes
+    pub distance: i32,
+    pub post: Post,
+}
+
+/// Post ranked by the votes it received in a period
+#[derive(Debug, Serialize)]
+pub struct PopularPost {
+    /// Sum of the votes cast in the period
+    pub period_score: i32,
+    pub post: Post,
+}
+
+/// Time window of the votes to rank popular posts by
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PopularPeriod {
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PostVote {
+    /// 1 for an upvote, -1 for a downvote, or 0 to remove the vote
+    pub score: i16,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UploadPostResult {
+    pub id: i32,
+    /// Whether the post is hidden until it is approved
+    pub pending: bool,
+    /// Existing posts similar to the uploaded file
+    pub similar_posts: Vec<SimilarPost>,
+}
+
+/// Results of a batch upload, one per uploaded file or archive entry
+#[derive(Debug, Serialize)]
+pub struct BatchUploadResult {
+    pub files: Vec<BatchUploadFileResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchUploadFileResult {
+    pub filename: String,
+    /// Filename of the archive the file was extracted from
+    pub archive: Option<String>,
+    #[serde(flatten)]
+    pub status: BatchUploadFileStatus,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum BatchUploadFileStatus {
+    /// A new post was created
+    Created(UploadPostResult),
+    /// An existing post already has the file
+    Duplicate { id: i32 },
+    Error { message: String },
 }
 
 #[derive(Debug, Deserialize)]
@@ -28,6 +177,156 @@ pub struct UpdatePost {
 
     pub add_tags: Vec<String>,
     pub remove_tags: Vec<String>,
+
+    pub rating: Option<Rating>,
+    /// New parent post, or `None` to keep the current parent
+    pub parent_id: Option<i32>,
+    /// Remove the current parent
+    #[serde(default)]
+    pub remove_parent: bool,
+}
+
+/// Metadata of a post, as tracked by its edit history
+#[derive(Debug, Serialize)]
+pub struct PostMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub source: Option<String>,
+    pub rating: Rating,
+    pub parent_id: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PostMetadataChange {
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub post_id: i32,
+    pub user_id: i32,
+    pub user_name: String,
+    pub old: PostMetadata,
+    pub new: PostMetadata,
+}
+
+/// File of a post, as it was before or after a replacement
+#[derive(Debug, Serialize)]
+pub struct PostFile {
+    pub filename: String,
+    pub size: i32,
+    pub width: i32,
+    pub height: i32,
+    pub hash: String,
+    pub ext: String,
+    pub tn_ext: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PostFileReplacement {
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub post_id: i32,
+    pub user_id: i32,
+    pub user_name: String,
+    pub old: PostFile,
+    pub new: PostFile,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PostTagChange {
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub post_id: i32,
+    pub user_id: i32,
+    pub user_name: String,
+    pub tags_added: Vec<String>,
+    pub tags_removed: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RevertUserTagChanges {
+    /// User whose changes to revert
+    pub user_id: i32,
+    /// Start of the time window (inclusive)
+    pub since: DateTime<Utc>,
+    /// End of the time window (exclusive). Defaults to now.
+    pub until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevertTagChangesResult {
+    pub reverted_count: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewTagEditJob {
+    /// Tags the posts to edit must have
+    #[serde(default)]
+    pub include_tags: Vec<String>,
+    /// Tags the posts to edit must not have
+    #[serde(default)]
+    pub exclude_tags: Vec<String>,
+    #[serde(default)]
+    pub add_tags: Vec<String>,
+    #[serde(default)]
+    pub remove_tags: Vec<String>,
+}
+
+/// Mass tag edit across the posts matching a search
+#[derive(Debug, Serialize)]
+pub struct TagEditJob {
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub user_id: i32,
+    pub user_name: String,
+    pub include_tags: Vec<String>,
+    pub exclude_tags: Vec<String>,
+    pub add_tags: Vec<String>,
+    pub remove_tags: Vec<String>,
+    /// Number of posts matched by the search when the job was created
+    pub post_count: i32,
+    /// Number of posts edited so far
+    pub processed_count: i32,
+    pub status: TagEditJobStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagEditJobStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+impl FromStr for TagEditJobStatus {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "running" => Ok(TagEditJobStatus::Running),
+            "completed" => Ok(TagEditJobStatus::Completed),
+            "failed" => Ok(TagEditJobStatus::Failed),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Entry in the edit history of a post
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum PostHistoryEntry {
+    Metadata(PostMetadataChange),
+    Tags(PostTagChange),
+    File(PostFileReplacement),
+}
+
+impl PostHistoryEntry {
+    pub fn created_at(&self) -> DateTime<Utc> {
+        match self {
+            PostHistoryEntry::Metadata(change) => change.created_at,
+            PostHistoryEntry::Tags(change) => change.created_at,
+            PostHistoryEntry::File(replacement) => replacement.created_at,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -35,10 +334,30 @@ pub struct Tag {
     pub id: i32,
     pub tag: String,
     pub alias_of_tag: Option<String>,
+    pub post_count: i32,
     pub aliases: Vec<String>,
     pub implied_tags: Vec<String>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct AutocompleteTag {
+    pub id: i32,
+    pub tag: String,
+    pub alias_of_tag: Option<String>,
+    pub post_count: i32,
+}
+
+/// Tag present in the posts matching a search
+#[derive(Debug, Serialize)]
+pub struct SearchTag {
+    pub id: i32,
+    pub tag: String,
+    /// Number of posts with the tag
+    pub post_count: i32,
+    /// Number of posts matching the search with the tag
+    pub search_post_count: i32,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct UpdateTag {
     #[serde(default)]
@@ -66,6 +385,59 @@ pub struct NewPostComment {
     pub comment: String,
 }
 
+/// Rectangle on the image of a post with a text annotation, such as a translation.
+/// The position and size are in pixels of the original image.
+#[derive(Debug, Serialize)]
+pub struct PostNote {
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub post_id: i32,
+    pub user_id: i32,
+    pub user_name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    pub body: String,
+    pub version: i32,
+}
+
+/// Position, size and text of a new note, or of an existing note after an update
+#[derive(Debug, Deserialize)]
+pub struct PostNoteContent {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    pub body: String,
+}
+
+impl PostNoteContent {
+    /// Check that the note has a non-negative position, a non-empty area and some text
+    pub fn is_valid(&self) -> bool {
+        self.x >= 0 && self.y >= 0 && self.width > 0 && self.height > 0 && !self.body.trim().is_empty()
+    }
+}
+
+/// Note as it was after an edit, including the user who made the edit
+#[derive(Debug, Serialize)]
+pub struct PostNoteVersion {
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub note_id: i32,
+    pub post_id: i32,
+    pub user_id: i32,
+    pub user_name: String,
+    pub version: i32,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    pub body: String,
+    pub is_deleted: bool,
+}
+
 #[derive(Debug, Serialize)]
 pub struct User {
     pub id: i32,
@@ -74,6 +446,7 @@ pub struct User {
     pub rank: i16,
     pub biography: Option<String>,
     pub css: Option<String>,
+    pub max_rating: Rating,
 }
 
 #[derive(Debug, Serialize)]
@@ -91,19 +464,40 @@ pub struct UpdateUser {
     pub rank: Option<i16>,
     pub biography: Option<String>,
     pub css: Option<String>,
+    pub max_rating: Option<Rating>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct PageInfo {
     pub no: i32,
     pub start_id: i32,
+    pub start_key: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PostNeighbors {
+    pub prev_id: Option<i32>,
+    pub next_id: Option<i32>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct Config {
     pub max_image_size: usize,
+    pub max_batch_upload_size: usize,
     pub require_login: bool,
     pub allow_registration: bool,
+    pub renditions: Vec<Rendition>,
+}
+
+/// Resized rendition of post files, stored in its own directory under the public path
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Rendition {
+    pub name: String,
+    /// Maximum width and height
+    pub size: u32,
+    /// Only generated for images larger than `size`, to be shown instead of the original
+    #[serde(default)]
+    pub sample: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -139,3 +533,142 @@ pub struct UpdateWikiPage {
     pub deleted: bool,
     pub reason: String
 }
+
+#[derive(Debug, Serialize)]
+pub struct Pool {
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub user_id: i32,
+    pub user_name: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// IDs of the posts in the pool, in order
+    pub post_ids: Vec<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewPool {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub post_ids: Vec<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdatePool {
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdatePoolPosts {
+    /// IDs of the posts in the pool, in the new order
+    pub post_ids: Vec<i32>,
+}
+
+/// Kind of item a report is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportTarget {
+    Post,
+    Comment,
+    Wiki,
+    User,
+}
+
+impl ReportTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportTarget::Post => "post",
+            ReportTarget::Comment => "comment",
+            ReportTarget::Wiki => "wiki",
+            ReportTarget::User => "user",
+        }
+    }
+}
+
+impl FromStr for ReportTarget {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "post" => Ok(ReportTarget::Post),
+            "comment" => Ok(ReportTarget::Comment),
+            "wiki" => Ok(ReportTarget::Wiki),
+            "user" => Ok(ReportTarget::User),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportStatus {
+    #[default]
+    Open,
+    Resolved,
+    Dismissed,
+}
+
+impl ReportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportStatus::Open => "open",
+            ReportStatus::Resolved => "resolved",
+            ReportStatus::Dismissed => "dismissed",
+        }
+    }
+}
+
+impl FromStr for ReportStatus {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(ReportStatus::Open),
+            "resolved" => Ok(ReportStatus::Resolved),
+            "dismissed" => Ok(ReportStatus::Dismissed),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewReport {
+    pub target_type: ReportTarget,
+    pub target_id: i32,
+    pub reason: String,
+}
+
+/// Report of a post, comment, wiki page or user, to be handled by moderators
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub user_id: i32,
+    pub user_name: String,
+    pub target_type: ReportTarget,
+    pub target_id: i32,
+    /// Frontend path of the reported item, or `None` if it no longer exists
+    pub target_link: Option<String>,
+    pub reason: String,
+    pub status: ReportStatus,
+    pub resolver_id: Option<i32>,
+    pub resolver_name: Option<String>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub resolution_note: Option<String>,
+    /// Whether the reported item was deleted when resolving the report
+    pub target_deleted: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveReport {
+    /// Either `resolved` or `dismissed`
+    pub status: ReportStatus,
+    pub resolution_note: Option<String>,
+    /// Delete the reported post, comment or wiki page along with resolving the report
+    #[serde(default)]
+    pub delete_target: bool,
+}
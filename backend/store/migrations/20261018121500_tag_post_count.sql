@@ -0,0 +1,316 @@
+---- DROP OLD ----
+
+DROP VIEW view_tag;
+DROP FUNCTION update_post_tags;
+DROP FUNCTION delete_post;
+DROP FUNCTION update_tag;
+
+---- TABLES ----
+
+-- Add denormalized post count to tag
+ALTER TABLE tag
+  ADD COLUMN post_count integer NOT NULL DEFAULT 0;
+
+-- Initialize post counts
+UPDATE tag AS t
+SET post_count = (SELECT COUNT(*)
+                  FROM post_tag_id_cache AS ptic
+                  WHERE ptic.tag_ids @> ARRAY[t.id]);
+
+---- VIEWS ----
+
+CREATE VIEW view_tag
+AS
+SELECT
+  t.id,
+  t.tag,
+  aot.tag AS alias_of_tag,
+  COALESCE(aot.post_count, t.post_count) AS post_count,
+  array(SELECT tag FROM tag WHERE tag.alias_of_tag_id = t.id) AS aliases,
+  array(SELECT tag FROM tag AS t1 JOIN unnest(t.implied_tag_ids) AS itid ON t1.id = itid) AS implied_tags
+FROM tag AS t
+LEFT JOIN tag AS aot ON aot.id = t.alias_of_tag_id;
+
+---- FUNCTIONS ----
+
+CREATE FUNCTION update_post_tags(
+  IN p_post_id integer,
+  IN p_add_tags text[],
+  IN p_remove_tags text[],
+  IN p_user_id integer,
+  IN p_new_post boolean
+)
+RETURNS VOID
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_add_tag_ids integer[];
+  v_remove_tag_ids integer[];
+  v_old_tag_ids integer[];
+  v_new_tag_ids integer[];
+BEGIN
+  -- Create missing tags
+  PERFORM create_missing_tags(p_add_tags);
+
+  v_add_tag_ids := get_tag_ids(p_add_tags);
+  v_remove_tag_ids := get_tag_ids(p_remove_tags);
+
+  -- Retrieve old tags
+  v_old_tag_ids := array(SELECT tag_id FROM post_tag AS pt WHERE pt.post_id = p_post_id ORDER BY tag_id ASC);
+
+  -- Compute new tags
+  v_new_tag_ids := (v_old_tag_ids | v_add_tag_ids) - v_remove_tag_ids;
+
+  -- Add links for added tags to post
+  INSERT INTO post_tag (post_id, tag_id)
+    SELECT p_post_id, tag_id
+    FROM unnest(v_add_tag_ids) AS tag_id
+    ON CONFLICT(post_id, tag_id)
+    DO NOTHING;
+
+  -- Remove removed tag links for post
+  DELETE FROM post_tag AS pt
+  USING unnest(v_remove_tag_ids) AS rtid
+  WHERE pt.post_id = p_post_id AND pt.tag_id = rtid;
+
+  -- Update post tags
+  UPDATE post
+  SET tags = array(SELECT tag
+                   FROM tag
+                   WHERE id = ANY(v_new_tag_ids)
+                   ORDER BY tag ASC)
+  WHERE id = p_post_id;
+
+  v_old_tag_ids := compute_post_tag_ids(v_old_tag_ids);
+  v_new_tag_ids := compute_post_tag_ids(v_new_tag_ids);
+
+  -- Update post_tag_id_cache
+  UPDATE post_tag_id_cache
+  SET tag_ids = v_new_tag_ids
+  WHERE post_id = p_post_id;
+
+  -- Update tag post counts, unless the post is deleted
+  IF FOUND THEN
+    UPDATE tag
+    SET post_count = post_count + 1
+    WHERE id = ANY(v_new_tag_ids - v_old_tag_ids);
+
+    UPDATE tag
+    SET post_count = post_count - 1
+    WHERE id = ANY(v_old_tag_ids - v_new_tag_ids);
+  END IF;
+
+  -- Update search cache to reflect added post
+  UPDATE search_cache
+  SET post_count = post_count + 1,
+      first_post_id = (CASE WHEN p_post_id > first_post_id THEN p_post_id ELSE first_post_id END),
+      last_page_post_ids = (CASE WHEN p_post_id < (SELECT MAX(id) FROM unnest(last_page_post_ids) AS id)
+                            THEN last_page_post_ids | p_post_id
+                            ELSE last_page_post_ids
+                            END)
+  WHERE v_new_tag_ids @> tag_ids
+    AND NOT v_new_tag_ids && exclude_tag_ids
+    AND (p_new_post OR (NOT v_old_tag_ids @> tag_ids) OR v_old_tag_ids && exclude_tag_ids);
+
+  -- Update search cache to reflect removed post
+  UPDATE search_cache AS sc
+  SET post_count = post_count - 1,
+      first_post_id = (CASE WHEN p_post_id = first_post_id
+                       THEN (SELECT COALESCE(MAX(post_id), 0)
+                             FROM post_tag_id_cache AS ptic
+                             WHERE ptic.tag_ids @> sc.tag_ids
+                               AND NOT ptic.tag_ids && sc.exclude_tag_ids)
+                       ELSE first_post_id
+                       END),
+      last_page_post_ids = (CASE WHEN (SELECT p_post_id BETWEEN MIN(id) AND MAX(id) FROM unnest(last_page_post_ids) AS id)
+                            THEN last_page_post_ids - p_post_id
+                            ELSE last_page_post_ids
+                            END)
+  WHERE NOT p_new_post
+    AND ((NOT v_new_tag_ids @> tag_ids) OR v_new_tag_ids && exclude_tag_ids)
+    AND v_old_tag_ids @> tag_ids
+    AND NOT v_old_tag_ids && exclude_tag_ids;
+
+  -- Track tag changes
+  INSERT INTO post_tag_change (
+    post_id,
+    user_id,
+    tag_ids_added,
+    tag_ids_removed
+  ) VALUES (
+    p_post_id,
+    p_user_id,
+    v_add_tag_ids,
+    v_remove_tag_ids
+  );
+END;
+$BODY$;
+
+CREATE FUNCTION delete_post(
+  IN p_post_id integer,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_success boolean;
+  v_tag_ids integer[];
+BEGIN
+  -- Update post
+  UPDATE post
+  SET is_deleted = true
+  WHERE id = p_post_id
+    AND user_id = p_user_id
+  RETURNING true INTO v_success;
+
+  IF NOT v_success THEN
+    RETURN v_success;
+  END IF;
+
+  -- Get post tag IDs for later use
+  SELECT tag_ids INTO v_tag_ids FROM post_tag_id_cache WHERE post_id = p_post_id;
+
+  -- Delete post_tag_id_cache so that the post
+  -- will no longer be scanned for tag matches
+  DELETE FROM post_tag_id_cache
+  WHERE post_id = p_post_id;
+
+  -- Update tag post counts to reflect deleted post
+  UPDATE tag
+  SET post_count = post_count - 1
+  WHERE id = ANY(v_tag_ids);
+
+  -- Update search cache to reflect deleted post
+  UPDATE search_cache AS sc
+  SET post_count = post_count - 1,
+      first_post_id = (CASE WHEN p_post_id = first_post_id
+                       THEN (SELECT COALESCE(MAX(post_id), 0)
+                             FROM post_tag_id_cache AS ptic
+                             WHERE ptic.tag_ids @> sc.tag_ids
+                               AND NOT ptic.tag_ids && sc.exclude_tag_ids)
+                       ELSE first_post_id
+                       END),
+      last_page_post_ids = (CASE WHEN (SELECT p_post_id BETWEEN MIN(id) AND MAX(id) FROM unnest(last_page_post_ids) AS id)
+                            THEN last_page_post_ids - p_post_id
+                            ELSE last_page_post_ids
+                            END)
+  WHERE v_tag_ids @> tag_ids
+    AND NOT v_tag_ids && exclude_tag_ids;
+
+  RETURN v_success;
+END;
+$BODY$;
+
+CREATE FUNCTION update_tag(
+  IN p_tag_id integer,
+  IN p_update_tag update_tag,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_add_alias_ids integer[];
+  v_remove_alias_ids integer[];
+  v_old_alias_ids integer[];
+  v_add_implied_tag_ids integer[];
+  v_remove_implied_tag_ids integer[];
+  v_old_implied_tag_ids integer[];
+  v_new_implied_tag_ids integer[];
+  v_affected_tag_ids integer[];
+BEGIN
+  IF NOT can_user_edit_tag(p_tag_id, p_user_id) THEN
+    RETURN false;
+  END IF;
+
+  v_add_implied_tag_ids := get_tag_ids(p_update_tag.add_implied_tags);
+  v_remove_implied_tag_ids := get_tag_ids(p_update_tag.remove_implied_tags);
+
+  -- Retrieve implied tag ids
+  SELECT implied_tag_ids
+  INTO v_old_implied_tag_ids
+  FROM tag
+  WHERE id = p_tag_id;
+
+  -- Compute new implied tag ids
+  v_new_implied_tag_ids := (v_old_implied_tag_ids | v_add_implied_tag_ids) - v_remove_implied_tag_ids;
+
+  -- Update tag
+  UPDATE tag
+  SET implied_tag_ids = v_new_implied_tag_ids
+  WHERE id = p_tag_id;
+
+  -- Get ids of removed aliases
+  v_remove_alias_ids := get_tag_ids(p_update_tag.remove_aliases);
+
+  IF cardinality(p_update_tag.add_aliases) > 0 THEN
+    -- Create missing tags for added aliases
+    PERFORM create_missing_tags(p_update_tag.add_aliases);
+
+    -- Retrieve old alias ids
+    SELECT COALESCE(array_agg(id), '{}')
+    INTO v_old_alias_ids
+    FROM tag
+    WHERE alias_of_tag_id = p_tag_id;
+
+    -- Get ids of added aliases
+    v_add_alias_ids := get_tag_ids(p_update_tag.add_aliases) - v_old_alias_ids - v_remove_alias_ids;
+
+    -- Set alias_of_tag_id for added aliases
+    UPDATE tag
+    SET alias_of_tag_id = p_tag_id
+    WHERE id = ANY(v_add_alias_ids);
+
+    -- Set any aliases of added aliases to be aliases of this tag
+    UPDATE tag
+    SET alias_of_tag_id = p_tag_id
+    WHERE alias_of_tag_id = ANY(v_add_alias_ids);
+  END IF;
+
+  IF icount(v_remove_alias_ids) > 0 THEN
+    -- Get actual alias ids that will be removed
+    SELECT array_agg(id)
+    INTO v_remove_alias_ids
+    FROM tag
+    WHERE alias_of_tag_id = p_tag_id AND id = ANY(v_remove_alias_ids);
+
+    -- Clear alias_of_tag_id of removed aliases
+    UPDATE tag
+    SET alias_of_tag_id = NULL
+    WHERE id = ANY(v_remove_alias_ids);
+  END IF;
+
+  v_affected_tag_ids := v_add_alias_ids || v_remove_alias_ids;
+
+  IF v_new_implied_tag_ids <> v_old_implied_tag_ids THEN
+    v_affected_tag_ids := v_affected_tag_ids + p_tag_id | v_old_implied_tag_ids | v_new_implied_tag_ids;
+  END IF;
+
+  IF icount(v_affected_tag_ids) > 0 THEN
+    v_affected_tag_ids := v_affected_tag_ids | compute_post_tag_ids(v_affected_tag_ids);
+
+    -- Update pre-calculated post tag ID cache
+    UPDATE post_tag_id_cache AS ptic
+    SET tag_ids = compute_post_tag_ids(array(SELECT tag_id FROM post_tag AS pt WHERE pt.post_id = ptic.post_id))
+    WHERE tag_ids && v_affected_tag_ids;
+
+    -- Recalculate post counts of affected tags
+    UPDATE tag AS t
+    SET post_count = (SELECT COUNT(*)
+                      FROM post_tag_id_cache AS ptic
+                      WHERE ptic.tag_ids @> ARRAY[t.id])
+    WHERE id = ANY(v_affected_tag_ids);
+
+    -- Delete cached searches affected by alias change
+    DELETE FROM search_cache
+    WHERE tag_ids && v_affected_tag_ids
+       OR exclude_tag_ids && v_affected_tag_ids;
+  END IF;
+
+  RETURN true;
+END;
+$BODY$;
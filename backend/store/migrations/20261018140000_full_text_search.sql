@@ -0,0 +1,36 @@
+---- FUNCTIONS ----
+
+CREATE FUNCTION post_search_vector(
+  IN p_title text,
+  IN p_description text
+)
+RETURNS tsvector
+LANGUAGE sql
+
+AS $BODY$
+  SELECT setweight(to_tsvector('english', COALESCE(p_title, '')), 'A')
+      || setweight(to_tsvector('english', COALESCE(p_description, '')), 'B');
+$BODY$ IMMUTABLE;
+
+CREATE FUNCTION wiki_search_vector(
+  IN p_title text,
+  IN p_body text
+)
+RETURNS tsvector
+LANGUAGE sql
+
+AS $BODY$
+  SELECT setweight(to_tsvector('english', p_title), 'A')
+      || setweight(to_tsvector('english', p_body), 'B');
+$BODY$ IMMUTABLE;
+
+---- INDEXES ----
+
+-- Full-text search indexes
+CREATE INDEX post_search_idx ON post
+  USING gin
+  (post_search_vector(title, description));
+
+CREATE INDEX wiki_pages_search_idx ON wiki_pages
+  USING gin
+  (wiki_search_vector(title, body));
@@ -0,0 +1,78 @@
+---- DROP OLD ----
+
+DROP FUNCTION create_post;
+
+---- TABLES ----
+
+-- Perceptual hashes of post files, one for still images,
+-- or one per distinct sampled frame for animations and videos
+CREATE TABLE post_phash
+(
+  post_id integer NOT NULL,
+  phash bigint NOT NULL,
+
+  PRIMARY KEY (post_id, phash),
+
+  FOREIGN KEY (post_id)
+    REFERENCES post (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID
+);
+
+---- FUNCTIONS ----
+
+CREATE FUNCTION create_post(
+  IN p_post new_post,
+  IN p_tags text[],
+  IN p_phashes bigint[]
+)
+RETURNS integer
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_post_id integer;
+BEGIN
+  -- Insert post
+  INSERT INTO post (
+    user_id,
+    title,
+    description,
+    source,
+    filename,
+    size,
+    width,
+    height,
+    hash,
+    ext,
+    tn_ext
+  )
+  SELECT
+    p_post.user_id, -- user_id
+    p_post.title, -- title
+    p_post.description, -- description
+    p_post.source, -- source
+    p_post.filename, -- filename
+    p_post.size, -- size
+    p_post.width, -- width
+    p_post.height, -- height
+    p_post.hash, -- hash
+    p_post.ext, -- ext
+    p_post.tn_ext -- tn_ext
+  RETURNING id INTO v_post_id;
+
+  -- Create post_tag_id_cache
+  INSERT INTO post_tag_id_cache (post_id) VALUES (v_post_id);
+
+  -- Store perceptual hashes
+  INSERT INTO post_phash (post_id, phash)
+    SELECT DISTINCT v_post_id, phash
+    FROM unnest(p_phashes) AS phash;
+
+  -- Add post tags
+  PERFORM update_post_tags(v_post_id, p_tags, '{}', p_post.user_id, true);
+
+  RETURN v_post_id;
+END;
+$BODY$;
@@ -0,0 +1,236 @@
+---- DROP OLD ----
+
+DROP FUNCTION get_view_posts;
+DROP VIEW view_post;
+DROP FUNCTION create_post;
+DROP FUNCTION update_post;
+DROP FUNCTION update_user;
+DROP TYPE new_post;
+DROP TYPE update_post;
+DROP TYPE update_user;
+
+---- TABLES ----
+
+-- Add rating column to post
+ALTER TABLE post
+  ADD COLUMN rating text NOT NULL DEFAULT 'general'
+  CHECK (rating IN ('general', 'sensitive', 'questionable', 'explicit'));
+
+-- Add default rating filter to users
+ALTER TABLE users
+  ADD COLUMN max_rating text NOT NULL DEFAULT 'explicit'
+  CHECK (max_rating IN ('general', 'sensitive', 'questionable', 'explicit'));
+
+---- TYPES ----
+
+CREATE TYPE new_post AS (
+  user_id integer,
+  title text,
+  description text,
+  source text,
+  filename text,
+  size integer,
+  width integer,
+  height integer,
+  hash text,
+  ext text,
+  tn_ext text,
+  rating text
+);
+
+CREATE TYPE update_post AS (
+  id integer,
+
+  title text,
+  description text,
+  source text,
+  add_tags text[],
+  remove_tags text[],
+  rating text
+);
+
+CREATE TYPE update_user AS (
+  id integer,
+
+  name text,
+  rank smallint,
+  biography text,
+  css text,
+  max_rating text
+);
+
+---- VIEWS ----
+
+CREATE VIEW view_post
+AS
+SELECT
+  p.id,
+  p.created_at,
+  p.user_id,
+  u.name AS user_name,
+  p.title,
+  p.description,
+  p.source,
+  p.filename,
+  p.size,
+  p.width,
+  p.height,
+  p.hash,
+  p.ext,
+  p.tn_ext,
+  p.tags,
+  p.rating
+FROM post AS p
+JOIN users AS u ON u.id = p.user_id
+WHERE NOT is_deleted;
+
+---- FUNCTIONS ----
+
+CREATE FUNCTION get_view_posts(
+  IN p_include_tags text[],
+  IN p_exclude_tags text[],
+  IN p_start_id integer,
+  IN p_limit integer
+)
+RETURNS SETOF view_post
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_tag_ids integer[];
+  v_exclude_tag_ids integer[];
+  v_valid boolean;
+BEGIN
+  SELECT * INTO v_tag_ids, v_exclude_tag_ids, v_valid FROM resolve_search_tags(p_include_tags, p_exclude_tags);
+  IF NOT v_valid THEN
+    RETURN;
+  END IF;
+
+  RETURN QUERY
+  SELECT p.*
+  FROM post_tag_id_cache AS ptic
+  JOIN view_post AS p ON p.id = ptic.post_id
+  WHERE
+    -- Only scan forward from the origin
+    ptic.post_id <= p_start_id
+    -- Posts with fewer tags than the required tags cannot qualify
+    AND icount(ptic.tag_ids) >= icount(v_tag_ids)
+    -- Post must have all the included tags
+    AND ptic.tag_ids @> v_tag_ids
+    -- Post must not have any of the excluded tags
+    AND NOT ptic.tag_ids && v_exclude_tag_ids
+  ORDER BY ptic.post_id DESC
+  LIMIT p_limit;
+END;
+$BODY$ STABLE;
+
+CREATE FUNCTION create_post(
+  IN p_post new_post,
+  IN p_tags text[],
+  IN p_phashes bigint[]
+)
+RETURNS integer
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_post_id integer;
+BEGIN
+  -- Insert post
+  INSERT INTO post (
+    user_id,
+    title,
+    description,
+    source,
+    filename,
+    size,
+    width,
+    height,
+    hash,
+    ext,
+    tn_ext,
+    rating
+  )
+  SELECT
+    p_post.user_id, -- user_id
+    p_post.title, -- title
+    p_post.description, -- description
+    p_post.source, -- source
+    p_post.filename, -- filename
+    p_post.size, -- size
+    p_post.width, -- width
+    p_post.height, -- height
+    p_post.hash, -- hash
+    p_post.ext, -- ext
+    p_post.tn_ext, -- tn_ext
+    COALESCE(p_post.rating, 'general') -- rating
+  RETURNING id INTO v_post_id;
+
+  -- Create post_tag_id_cache
+  INSERT INTO post_tag_id_cache (post_id) VALUES (v_post_id);
+
+  -- Store perceptual hashes
+  INSERT INTO post_phash (post_id, phash)
+    SELECT DISTINCT v_post_id, phash
+    FROM unnest(p_phashes) AS phash;
+
+  -- Add post tags
+  PERFORM update_post_tags(v_post_id, p_tags, '{}', p_post.user_id, true);
+
+  RETURN v_post_id;
+END;
+$BODY$;
+
+CREATE FUNCTION update_post(
+  IN p_update_post update_post,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_can_edit boolean;
+BEGIN
+  -- Check if user is allowed to edit the post
+  v_can_edit := can_user_edit_post(p_update_post.id, p_user_id);
+
+  -- If user is allowed to edit, update post
+  IF v_can_edit THEN
+    UPDATE post
+    SET
+      title = p_update_post.title,
+      description = p_update_post.description,
+      source = p_update_post.source,
+      rating = COALESCE(p_update_post.rating, rating)
+    WHERE id = p_update_post.id;
+  END IF;
+
+  -- Update post tags
+  PERFORM update_post_tags(p_update_post.id, p_update_post.add_tags, p_update_post.remove_tags, p_user_id, false);
+
+  RETURN true;
+END;
+$BODY$;
+
+CREATE FUNCTION update_user(
+  IN p_update_user update_user
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  -- Update user
+  UPDATE users
+  SET
+    name = COALESCE(p_update_user.name, name),
+    rank = COALESCE(p_update_user.rank, rank),
+    biography = COALESCE(p_update_user.biography, biography),
+    css = COALESCE(p_update_user.css, css),
+    max_rating = COALESCE(p_update_user.max_rating, max_rating)
+  WHERE id = p_update_user.id;
+
+  RETURN true;
+END;
+$BODY$;
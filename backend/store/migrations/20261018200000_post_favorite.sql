@@ -0,0 +1,160 @@
+---- DROP OLD ----
+
+DROP FUNCTION get_view_posts;
+DROP VIEW view_post;
+
+---- TABLES ----
+
+-- Add cached favorite count to post
+ALTER TABLE post
+  ADD COLUMN fav_count integer NOT NULL DEFAULT 0;
+
+-- Posts favorited by users
+CREATE TABLE post_favorite
+(
+  post_id integer NOT NULL,
+  user_id integer NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+  PRIMARY KEY (post_id, user_id),
+
+  FOREIGN KEY (post_id)
+    REFERENCES post (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID,
+
+  FOREIGN KEY (user_id)
+    REFERENCES users (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID
+);
+
+---- VIEWS ----
+
+CREATE VIEW view_post
+AS
+SELECT
+  p.id,
+  p.created_at,
+  p.user_id,
+  u.name AS user_name,
+  p.title,
+  p.description,
+  p.source,
+  p.filename,
+  p.size,
+  p.width,
+  p.height,
+  p.hash,
+  p.ext,
+  p.tn_ext,
+  p.tags,
+  p.rating,
+  p.fav_count
+FROM post AS p
+JOIN users AS u ON u.id = p.user_id
+WHERE NOT is_deleted;
+
+---- FUNCTIONS ----
+
+CREATE FUNCTION get_view_posts(
+  IN p_include_tags text[],
+  IN p_exclude_tags text[],
+  IN p_start_id integer,
+  IN p_limit integer
+)
+RETURNS SETOF view_post
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_tag_ids integer[];
+  v_exclude_tag_ids integer[];
+  v_valid boolean;
+BEGIN
+  SELECT * INTO v_tag_ids, v_exclude_tag_ids, v_valid FROM resolve_search_tags(p_include_tags, p_exclude_tags);
+  IF NOT v_valid THEN
+    RETURN;
+  END IF;
+
+  RETURN QUERY
+  SELECT p.*
+  FROM post_tag_id_cache AS ptic
+  JOIN view_post AS p ON p.id = ptic.post_id
+  WHERE
+    -- Only scan forward from the origin
+    ptic.post_id <= p_start_id
+    -- Posts with fewer tags than the required tags cannot qualify
+    AND icount(ptic.tag_ids) >= icount(v_tag_ids)
+    -- Post must have all the included tags
+    AND ptic.tag_ids @> v_tag_ids
+    -- Post must not have any of the excluded tags
+    AND NOT ptic.tag_ids && v_exclude_tag_ids
+  ORDER BY ptic.post_id DESC
+  LIMIT p_limit;
+END;
+$BODY$ STABLE;
+
+CREATE FUNCTION favorite_post(
+  IN p_post_id integer,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  -- Deleted posts cannot be favorited
+  IF NOT EXISTS (SELECT 1 FROM post WHERE id = p_post_id AND NOT is_deleted) THEN
+    RETURN false;
+  END IF;
+
+  INSERT INTO post_favorite (post_id, user_id)
+  VALUES (p_post_id, p_user_id)
+  ON CONFLICT DO NOTHING;
+
+  -- Only count the favorite if it did not already exist
+  IF FOUND THEN
+    UPDATE post
+    SET fav_count = fav_count + 1
+    WHERE id = p_post_id;
+  END IF;
+
+  RETURN true;
+END;
+$BODY$;
+
+CREATE FUNCTION unfavorite_post(
+  IN p_post_id integer,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  IF NOT EXISTS (SELECT 1 FROM post WHERE id = p_post_id AND NOT is_deleted) THEN
+    RETURN false;
+  END IF;
+
+  DELETE FROM post_favorite
+  WHERE post_id = p_post_id
+    AND user_id = p_user_id;
+
+  IF FOUND THEN
+    UPDATE post
+    SET fav_count = fav_count - 1
+    WHERE id = p_post_id;
+  END IF;
+
+  RETURN true;
+END;
+$BODY$;
+
+---- INDEXES ----
+
+CREATE INDEX post_favorite_user_id_idx ON post_favorite
+  USING btree
+  (user_id ASC NULLS LAST, post_id DESC NULLS LAST);
@@ -0,0 +1,159 @@
+---- DROP OLD ----
+
+DROP FUNCTION get_view_posts;
+DROP VIEW view_post;
+
+---- TABLES ----
+
+-- Add cached vote score to post
+ALTER TABLE post
+  ADD COLUMN score integer NOT NULL DEFAULT 0;
+
+-- Up (1) and down (-1) votes on posts, one per user
+CREATE TABLE post_vote
+(
+  post_id integer NOT NULL,
+  user_id integer NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  score smallint NOT NULL CHECK (score IN (-1, 1)),
+
+  PRIMARY KEY (post_id, user_id),
+
+  FOREIGN KEY (post_id)
+    REFERENCES post (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID,
+
+  FOREIGN KEY (user_id)
+    REFERENCES users (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID
+);
+
+SELECT manage_updated_at('post_vote'); -- Automatically manage updated_at
+
+---- VIEWS ----
+
+CREATE VIEW view_post
+AS
+SELECT
+  p.id,
+  p.created_at,
+  p.user_id,
+  u.name AS user_name,
+  p.title,
+  p.description,
+  p.source,
+  p.filename,
+  p.size,
+  p.width,
+  p.height,
+  p.hash,
+  p.ext,
+  p.tn_ext,
+  p.tags,
+  p.rating,
+  p.fav_count,
+  p.score
+FROM post AS p
+JOIN users AS u ON u.id = p.user_id
+WHERE NOT is_deleted;
+
+---- FUNCTIONS ----
+
+CREATE FUNCTION get_view_posts(
+  IN p_include_tags text[],
+  IN p_exclude_tags text[],
+  IN p_start_id integer,
+  IN p_limit integer
+)
+RETURNS SETOF view_post
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_tag_ids integer[];
+  v_exclude_tag_ids integer[];
+  v_valid boolean;
+BEGIN
+  SELECT * INTO v_tag_ids, v_exclude_tag_ids, v_valid FROM resolve_search_tags(p_include_tags, p_exclude_tags);
+  IF NOT v_valid THEN
+    RETURN;
+  END IF;
+
+  RETURN QUERY
+  SELECT p.*
+  FROM post_tag_id_cache AS ptic
+  JOIN view_post AS p ON p.id = ptic.post_id
+  WHERE
+    -- Only scan forward from the origin
+    ptic.post_id <= p_start_id
+    -- Posts with fewer tags than the required tags cannot qualify
+    AND icount(ptic.tag_ids) >= icount(v_tag_ids)
+    -- Post must have all the included tags
+    AND ptic.tag_ids @> v_tag_ids
+    -- Post must not have any of the excluded tags
+    AND NOT ptic.tag_ids && v_exclude_tag_ids
+  ORDER BY ptic.post_id DESC
+  LIMIT p_limit;
+END;
+$BODY$ STABLE;
+
+CREATE FUNCTION vote_post(
+  IN p_post_id integer,
+  IN p_user_id integer,
+  IN p_score smallint
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_old_score smallint;
+BEGIN
+  -- Deleted posts cannot be voted on
+  PERFORM 1 FROM post WHERE id = p_post_id AND NOT is_deleted FOR UPDATE;
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  SELECT score INTO v_old_score
+  FROM post_vote
+  WHERE post_id = p_post_id
+    AND user_id = p_user_id;
+
+  IF p_score = 0 THEN
+    -- A score of 0 removes the vote
+    DELETE FROM post_vote
+    WHERE post_id = p_post_id
+      AND user_id = p_user_id;
+  ELSIF v_old_score IS DISTINCT FROM p_score THEN
+    -- Each user has at most one vote per post
+    INSERT INTO post_vote (post_id, user_id, score)
+    VALUES (p_post_id, p_user_id, p_score)
+    ON CONFLICT (post_id, user_id) DO UPDATE
+    SET score = EXCLUDED.score;
+  END IF;
+
+  -- Update cached score with the difference
+  UPDATE post
+  SET score = score + p_score - COALESCE(v_old_score, 0)
+  WHERE id = p_post_id
+    AND p_score IS DISTINCT FROM COALESCE(v_old_score, 0);
+
+  RETURN true;
+END;
+$BODY$;
+
+---- INDEXES ----
+
+CREATE INDEX post_vote_updated_at_idx ON post_vote
+  USING btree
+  (updated_at DESC NULLS LAST);
+
+CREATE INDEX post_score_idx ON post
+  USING btree
+  (score DESC NULLS LAST, id DESC NULLS LAST);
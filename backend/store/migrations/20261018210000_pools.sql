@@ -0,0 +1,308 @@
+---- DROP OLD ----
+
+DROP FUNCTION get_view_posts;
+DROP VIEW view_post;
+
+---- TABLES ----
+
+-- Named, ordered collections of posts
+CREATE TABLE pool
+(
+  id serial NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  user_id integer NOT NULL,
+  name text NOT NULL,
+  description text,
+  is_deleted boolean NOT NULL DEFAULT false,
+
+  PRIMARY KEY (id),
+
+  FOREIGN KEY (user_id)
+    REFERENCES users (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID
+);
+
+SELECT manage_updated_at('pool'); -- Automatically manage updated_at
+
+CREATE TABLE pool_post
+(
+  pool_id integer NOT NULL,
+  post_id integer NOT NULL,
+  position integer NOT NULL,
+
+  PRIMARY KEY (pool_id, post_id),
+
+  FOREIGN KEY (pool_id)
+    REFERENCES pool (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID,
+
+  FOREIGN KEY (post_id)
+    REFERENCES post (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID
+);
+
+-- Pool membership changes, similar to post_tag_change
+CREATE TABLE pool_post_change
+(
+  id serial NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+  pool_id integer NOT NULL,
+  user_id integer NOT NULL,
+  post_ids_added integer[],
+  post_ids_removed integer[],
+
+  PRIMARY KEY (id)
+);
+
+---- TYPES ----
+
+CREATE TYPE new_pool AS (
+  name text,
+  description text,
+  post_ids integer[]
+);
+
+CREATE TYPE update_pool AS (
+  id integer,
+
+  name text,
+  description text
+);
+
+---- VIEWS ----
+
+CREATE VIEW view_post
+AS
+SELECT
+  p.id,
+  p.created_at,
+  p.user_id,
+  u.name AS user_name,
+  p.title,
+  p.description,
+  p.source,
+  p.filename,
+  p.size,
+  p.width,
+  p.height,
+  p.hash,
+  p.ext,
+  p.tn_ext,
+  p.tags,
+  p.rating,
+  p.fav_count,
+  p.score,
+  ARRAY(
+    SELECT pp.pool_id
+    FROM pool_post AS pp
+    JOIN pool AS pl ON pl.id = pp.pool_id
+    WHERE pp.post_id = p.id
+      AND NOT pl.is_deleted
+    ORDER BY pp.pool_id ASC
+  ) AS pools
+FROM post AS p
+JOIN users AS u ON u.id = p.user_id
+WHERE NOT is_deleted;
+
+CREATE VIEW view_pool
+AS
+SELECT
+  pl.id,
+  pl.created_at,
+  pl.updated_at,
+  pl.user_id,
+  u.name AS user_name,
+  pl.name,
+  pl.description,
+  ARRAY(
+    SELECT pp.post_id
+    FROM pool_post AS pp
+    JOIN post AS p ON p.id = pp.post_id
+    WHERE pp.pool_id = pl.id
+      AND NOT p.is_deleted
+    ORDER BY pp.position ASC
+  ) AS post_ids
+FROM pool AS pl
+JOIN users AS u ON u.id = pl.user_id
+WHERE NOT pl.is_deleted;
+
+---- FUNCTIONS ----
+
+CREATE FUNCTION get_view_posts(
+  IN p_include_tags text[],
+  IN p_exclude_tags text[],
+  IN p_start_id integer,
+  IN p_limit integer
+)
+RETURNS SETOF view_post
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_tag_ids integer[];
+  v_exclude_tag_ids integer[];
+  v_valid boolean;
+BEGIN
+  SELECT * INTO v_tag_ids, v_exclude_tag_ids, v_valid FROM resolve_search_tags(p_include_tags, p_exclude_tags);
+  IF NOT v_valid THEN
+    RETURN;
+  END IF;
+
+  RETURN QUERY
+  SELECT p.*
+  FROM post_tag_id_cache AS ptic
+  JOIN view_post AS p ON p.id = ptic.post_id
+  WHERE
+    -- Only scan forward from the origin
+    ptic.post_id <= p_start_id
+    -- Posts with fewer tags than the required tags cannot qualify
+    AND icount(ptic.tag_ids) >= icount(v_tag_ids)
+    -- Post must have all the included tags
+    AND ptic.tag_ids @> v_tag_ids
+    -- Post must not have any of the excluded tags
+    AND NOT ptic.tag_ids && v_exclude_tag_ids
+  ORDER BY ptic.post_id DESC
+  LIMIT p_limit;
+END;
+$BODY$ STABLE;
+
+CREATE FUNCTION set_pool_posts(
+  IN p_pool_id integer,
+  IN p_post_ids integer[],
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_old_post_ids integer[];
+  v_new_post_ids integer[];
+  v_post_ids_added integer[];
+  v_post_ids_removed integer[];
+BEGIN
+  -- Lock the pool, so that concurrent changes are tracked correctly
+  PERFORM 1 FROM pool WHERE id = p_pool_id AND NOT is_deleted FOR UPDATE;
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  SELECT COALESCE(array_agg(post_id), '{}') INTO v_old_post_ids
+  FROM pool_post
+  WHERE pool_id = p_pool_id;
+
+  -- Keep only the first occurrence of each existing post, in the specified order
+  SELECT COALESCE(array_agg(x.post_id ORDER BY x.position), '{}') INTO v_new_post_ids
+  FROM (
+    SELECT u.post_id, MIN(u.position) AS position
+    FROM unnest(p_post_ids) WITH ORDINALITY AS u(post_id, position)
+    JOIN post AS p ON p.id = u.post_id
+    WHERE NOT p.is_deleted
+    GROUP BY u.post_id
+  ) AS x;
+
+  v_post_ids_added := v_new_post_ids - v_old_post_ids;
+  v_post_ids_removed := v_old_post_ids - v_new_post_ids;
+
+  DELETE FROM pool_post
+  WHERE pool_id = p_pool_id;
+
+  INSERT INTO pool_post (pool_id, post_id, position)
+  SELECT p_pool_id, u.post_id, u.position
+  FROM unnest(v_new_post_ids) WITH ORDINALITY AS u(post_id, position);
+
+  -- Track membership changes
+  IF icount(v_post_ids_added) > 0 OR icount(v_post_ids_removed) > 0 THEN
+    INSERT INTO pool_post_change (pool_id, user_id, post_ids_added, post_ids_removed)
+    VALUES (p_pool_id, p_user_id, v_post_ids_added, v_post_ids_removed);
+  END IF;
+
+  UPDATE pool
+  SET updated_at = CURRENT_TIMESTAMP
+  WHERE id = p_pool_id;
+
+  RETURN true;
+END;
+$BODY$;
+
+CREATE FUNCTION create_pool(
+  IN p_pool new_pool,
+  IN p_user_id integer
+)
+RETURNS integer
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_pool_id integer;
+BEGIN
+  -- Insert pool
+  INSERT INTO pool (user_id, name, description)
+  VALUES (p_user_id, p_pool.name, p_pool.description)
+  RETURNING id INTO v_pool_id;
+
+  -- Insert pool posts
+  PERFORM set_pool_posts(v_pool_id, COALESCE(p_pool.post_ids, '{}'), p_user_id);
+
+  RETURN v_pool_id;
+END;
+$BODY$;
+
+CREATE FUNCTION update_pool(
+  IN p_update_pool update_pool
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  UPDATE pool
+  SET
+    name = COALESCE(p_update_pool.name, name),
+    description = p_update_pool.description
+  WHERE id = p_update_pool.id
+    AND NOT is_deleted;
+
+  RETURN FOUND;
+END;
+$BODY$;
+
+CREATE FUNCTION delete_pool(
+  IN p_pool_id integer,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  -- Only the creator of the pool or some sort of admin can delete it
+  UPDATE pool
+  SET is_deleted = true
+  WHERE id = p_pool_id
+    AND NOT is_deleted
+    AND (user_id = p_user_id OR (SELECT rank FROM users WHERE id = p_user_id) > 0);
+
+  RETURN FOUND;
+END;
+$BODY$;
+
+---- INDEXES ----
+
+-- Pool names only need to be unique among pools that are not deleted
+CREATE UNIQUE INDEX pool_name_idx ON pool
+  USING btree
+  (name ASC NULLS LAST)
+  WHERE NOT is_deleted;
+
+CREATE INDEX pool_post_post_id_idx ON pool_post
+  USING btree
+  (post_id ASC NULLS LAST);
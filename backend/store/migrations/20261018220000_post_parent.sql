@@ -0,0 +1,158 @@
+---- DROP OLD ----
+
+DROP FUNCTION get_view_posts;
+DROP VIEW view_post;
+DROP FUNCTION update_post;
+DROP TYPE update_post;
+
+---- TABLES ----
+
+-- Add parent post, to link variants, alternate versions and edits
+ALTER TABLE post
+  ADD COLUMN parent_id integer,
+  ADD FOREIGN KEY (parent_id)
+    REFERENCES post (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE SET NULL
+    NOT VALID;
+
+---- TYPES ----
+
+CREATE TYPE update_post AS (
+  id integer,
+
+  title text,
+  description text,
+  source text,
+  add_tags text[],
+  remove_tags text[],
+  rating text,
+  parent_id integer
+);
+
+---- VIEWS ----
+
+CREATE VIEW view_post
+AS
+SELECT
+  p.id,
+  p.created_at,
+  p.user_id,
+  u.name AS user_name,
+  p.title,
+  p.description,
+  p.source,
+  p.filename,
+  p.size,
+  p.width,
+  p.height,
+  p.hash,
+  p.ext,
+  p.tn_ext,
+  p.tags,
+  p.rating,
+  p.fav_count,
+  p.score,
+  ARRAY(
+    SELECT pp.pool_id
+    FROM pool_post AS pp
+    JOIN pool AS pl ON pl.id = pp.pool_id
+    WHERE pp.post_id = p.id
+      AND NOT pl.is_deleted
+    ORDER BY pp.pool_id ASC
+  ) AS pools,
+  (
+    SELECT pa.id
+    FROM post AS pa
+    WHERE pa.id = p.parent_id
+      AND NOT pa.is_deleted
+  ) AS parent_id,
+  ARRAY(
+    SELECT c.id
+    FROM post AS c
+    WHERE c.parent_id = p.id
+      AND NOT c.is_deleted
+    ORDER BY c.id ASC
+  ) AS child_ids
+FROM post AS p
+JOIN users AS u ON u.id = p.user_id
+WHERE NOT is_deleted;
+
+---- FUNCTIONS ----
+
+CREATE FUNCTION get_view_posts(
+  IN p_include_tags text[],
+  IN p_exclude_tags text[],
+  IN p_start_id integer,
+  IN p_limit integer
+)
+RETURNS SETOF view_post
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_tag_ids integer[];
+  v_exclude_tag_ids integer[];
+  v_valid boolean;
+BEGIN
+  SELECT * INTO v_tag_ids, v_exclude_tag_ids, v_valid FROM resolve_search_tags(p_include_tags, p_exclude_tags);
+  IF NOT v_valid THEN
+    RETURN;
+  END IF;
+
+  RETURN QUERY
+  SELECT p.*
+  FROM post_tag_id_cache AS ptic
+  JOIN view_post AS p ON p.id = ptic.post_id
+  WHERE
+    -- Only scan forward from the origin
+    ptic.post_id <= p_start_id
+    -- Posts with fewer tags than the required tags cannot qualify
+    AND icount(ptic.tag_ids) >= icount(v_tag_ids)
+    -- Post must have all the included tags
+    AND ptic.tag_ids @> v_tag_ids
+    -- Post must not have any of the excluded tags
+    AND NOT ptic.tag_ids && v_exclude_tag_ids
+  ORDER BY ptic.post_id DESC
+  LIMIT p_limit;
+END;
+$BODY$ STABLE;
+
+CREATE FUNCTION update_post(
+  IN p_update_post update_post,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_can_edit boolean;
+BEGIN
+  -- Check if user is allowed to edit the post
+  v_can_edit := can_user_edit_post(p_update_post.id, p_user_id);
+
+  -- If user is allowed to edit, update post
+  IF v_can_edit THEN
+    UPDATE post
+    SET
+      title = p_update_post.title,
+      description = p_update_post.description,
+      source = p_update_post.source,
+      rating = COALESCE(p_update_post.rating, rating),
+      parent_id = p_update_post.parent_id
+    WHERE id = p_update_post.id;
+  END IF;
+
+  -- Update post tags
+  PERFORM update_post_tags(p_update_post.id, p_update_post.add_tags, p_update_post.remove_tags, p_user_id, false);
+
+  RETURN true;
+END;
+$BODY$;
+
+---- INDEXES ----
+
+CREATE INDEX post_parent_id_idx ON post
+  USING btree
+  (parent_id ASC NULLS LAST);
@@ -0,0 +1,152 @@
+---- DROP OLD ----
+
+DROP FUNCTION update_post;
+
+---- TABLES ----
+
+-- Post metadata changes, similar to post_tag_change
+CREATE TABLE post_metadata_change
+(
+  id serial NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+  post_id integer NOT NULL,
+  user_id integer NOT NULL,
+
+  old_title text,
+  new_title text,
+  old_description text,
+  new_description text,
+  old_source text,
+  new_source text,
+  old_rating text,
+  new_rating text,
+  old_parent_id integer,
+  new_parent_id integer,
+
+  PRIMARY KEY (id),
+
+  FOREIGN KEY (post_id)
+    REFERENCES post (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID
+);
+
+---- VIEWS ----
+
+CREATE VIEW view_post_metadata_change
+AS
+SELECT
+  pmc.id,
+  pmc.created_at,
+  pmc.post_id,
+  pmc.user_id,
+  u.name AS user_name,
+  pmc.old_title,
+  pmc.new_title,
+  pmc.old_description,
+  pmc.new_description,
+  pmc.old_source,
+  pmc.new_source,
+  pmc.old_rating,
+  pmc.new_rating,
+  pmc.old_parent_id,
+  pmc.new_parent_id
+FROM post_metadata_change AS pmc
+JOIN users AS u ON u.id = pmc.user_id;
+
+CREATE VIEW view_post_tag_change
+AS
+SELECT
+  ptc.id,
+  ptc.created_at,
+  ptc.post_id,
+  ptc.user_id,
+  u.name AS user_name,
+  ARRAY(SELECT t.tag FROM tag AS t WHERE t.id = ANY(ptc.tag_ids_added) ORDER BY t.tag ASC) AS tags_added,
+  ARRAY(SELECT t.tag FROM tag AS t WHERE t.id = ANY(ptc.tag_ids_removed) ORDER BY t.tag ASC) AS tags_removed
+FROM post_tag_change AS ptc
+JOIN users AS u ON u.id = ptc.user_id;
+
+---- FUNCTIONS ----
+
+CREATE FUNCTION update_post(
+  IN p_update_post update_post,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_can_edit boolean;
+  v_old post;
+  v_new post;
+BEGIN
+  -- Check if user is allowed to edit the post
+  v_can_edit := can_user_edit_post(p_update_post.id, p_user_id);
+
+  -- If user is allowed to edit, update post
+  IF v_can_edit THEN
+    SELECT * INTO v_old FROM post WHERE id = p_update_post.id FOR UPDATE;
+
+    UPDATE post
+    SET
+      title = p_update_post.title,
+      description = p_update_post.description,
+      source = p_update_post.source,
+      rating = COALESCE(p_update_post.rating, rating),
+      parent_id = p_update_post.parent_id
+    WHERE id = p_update_post.id
+    RETURNING * INTO v_new;
+
+    -- Track metadata changes
+    IF (v_old.title, v_old.description, v_old.source, v_old.rating, v_old.parent_id)
+       IS DISTINCT FROM (v_new.title, v_new.description, v_new.source, v_new.rating, v_new.parent_id) THEN
+      INSERT INTO post_metadata_change (
+        post_id,
+        user_id,
+        old_title,
+        new_title,
+        old_description,
+        new_description,
+        old_source,
+        new_source,
+        old_rating,
+        new_rating,
+        old_parent_id,
+        new_parent_id
+      ) VALUES (
+        p_update_post.id,
+        p_user_id,
+        v_old.title,
+        v_new.title,
+        v_old.description,
+        v_new.description,
+        v_old.source,
+        v_new.source,
+        v_old.rating,
+        v_new.rating,
+        v_old.parent_id,
+        v_new.parent_id
+      );
+    END IF;
+  END IF;
+
+  -- Update post tags
+  PERFORM update_post_tags(p_update_post.id, p_update_post.add_tags, p_update_post.remove_tags, p_user_id, false);
+
+  RETURN true;
+END;
+$BODY$;
+
+---- INDEXES ----
+
+CREATE INDEX post_metadata_change_post_id_idx ON post_metadata_change
+  USING btree
+  (post_id ASC NULLS LAST);
+
+CREATE INDEX post_tag_change_post_id_idx ON post_tag_change
+  USING btree
+  (post_id ASC NULLS LAST);
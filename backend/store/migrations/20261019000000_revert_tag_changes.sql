@@ -0,0 +1,84 @@
+---- FUNCTIONS ----
+
+CREATE FUNCTION revert_post_tag_change(
+  IN p_change_id integer,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_change post_tag_change;
+BEGIN
+  SELECT * INTO v_change
+  FROM post_tag_change
+  WHERE id = p_change_id;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Tags of deleted posts are no longer maintained
+  IF NOT EXISTS (SELECT 1 FROM post WHERE id = v_change.post_id AND NOT is_deleted) THEN
+    RETURN false;
+  END IF;
+
+  -- Nothing to revert
+  IF COALESCE(icount(v_change.tag_ids_added), 0) = 0 AND COALESCE(icount(v_change.tag_ids_removed), 0) = 0 THEN
+    RETURN true;
+  END IF;
+
+  -- Re-add the removed tags and remove the added tags.
+  -- This is tracked as a new change by the reverting user.
+  PERFORM update_post_tags(
+    v_change.post_id,
+    ARRAY(SELECT tag FROM tag WHERE id = ANY(v_change.tag_ids_removed)),
+    ARRAY(SELECT tag FROM tag WHERE id = ANY(v_change.tag_ids_added)),
+    p_user_id,
+    false
+  );
+
+  RETURN true;
+END;
+$BODY$;
+
+CREATE FUNCTION revert_user_tag_changes(
+  IN p_change_user_id integer,
+  IN p_since timestamp with time zone,
+  IN p_until timestamp with time zone,
+  IN p_user_id integer
+)
+RETURNS integer
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_change_id integer;
+  v_reverted_count integer := 0;
+BEGIN
+  -- Revert newest changes first, so that each post ends up
+  -- with the tags it had before the user's first change
+  FOR v_change_id IN
+    SELECT id
+    FROM post_tag_change
+    WHERE user_id = p_change_user_id
+      AND created_at >= p_since
+      AND created_at < p_until
+      AND (COALESCE(icount(tag_ids_added), 0) > 0 OR COALESCE(icount(tag_ids_removed), 0) > 0)
+    ORDER BY id DESC
+  LOOP
+    IF revert_post_tag_change(v_change_id, p_user_id) THEN
+      v_reverted_count := v_reverted_count + 1;
+    END IF;
+  END LOOP;
+
+  RETURN v_reverted_count;
+END;
+$BODY$;
+
+---- INDEXES ----
+
+CREATE INDEX post_tag_change_user_id_idx ON post_tag_change
+  USING btree
+  (user_id ASC NULLS LAST, id DESC NULLS LAST);
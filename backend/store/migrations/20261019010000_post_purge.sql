@@ -0,0 +1,174 @@
+---- DROP OLD ----
+
+DROP FUNCTION delete_post;
+
+---- TABLES ----
+
+ALTER TABLE post
+  ADD COLUMN deleted_at timestamp with time zone;
+
+-- Deletion time of already deleted posts is unknown, use the last update time
+UPDATE post
+SET deleted_at = updated_at
+WHERE is_deleted;
+
+---- VIEWS ----
+
+CREATE VIEW view_deleted_post
+AS
+SELECT
+  p.id,
+  p.created_at,
+  p.deleted_at,
+  p.user_id,
+  u.name AS user_name,
+  p.title,
+  p.filename,
+  p.hash,
+  p.ext,
+  p.tn_ext,
+  p.tags,
+  p.rating
+FROM post AS p
+JOIN users AS u ON u.id = p.user_id
+WHERE is_deleted;
+
+---- FUNCTIONS ----
+
+CREATE FUNCTION delete_post(
+  IN p_post_id integer,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_success boolean;
+  v_tag_ids integer[];
+BEGIN
+  -- Update post
+  UPDATE post
+  SET is_deleted = true,
+      deleted_at = CURRENT_TIMESTAMP
+  WHERE id = p_post_id
+    AND user_id = p_user_id
+  RETURNING true INTO v_success;
+
+  IF NOT v_success THEN
+    RETURN v_success;
+  END IF;
+
+  -- Get post tag IDs for later use
+  SELECT tag_ids INTO v_tag_ids FROM post_tag_id_cache WHERE post_id = p_post_id;
+
+  -- Delete post_tag_id_cache so that the post
+  -- will no longer be scanned for tag matches
+  DELETE FROM post_tag_id_cache
+  WHERE post_id = p_post_id;
+
+  -- Update tag post counts to reflect deleted post
+  UPDATE tag
+  SET post_count = post_count - 1
+  WHERE id = ANY(v_tag_ids);
+
+  -- Update search cache to reflect deleted post
+  UPDATE search_cache AS sc
+  SET post_count = post_count - 1,
+      first_post_id = (CASE WHEN p_post_id = first_post_id
+                       THEN (SELECT COALESCE(MAX(post_id), 0)
+                             FROM post_tag_id_cache AS ptic
+                             WHERE ptic.tag_ids @> sc.tag_ids
+                               AND NOT ptic.tag_ids && sc.exclude_tag_ids)
+                       ELSE first_post_id
+                       END),
+      last_page_post_ids = (CASE WHEN (SELECT p_post_id BETWEEN MIN(id) AND MAX(id) FROM unnest(last_page_post_ids) AS id)
+                            THEN last_page_post_ids - p_post_id
+                            ELSE last_page_post_ids
+                            END)
+  WHERE v_tag_ids @> tag_ids
+    AND NOT v_tag_ids && exclude_tag_ids;
+
+  RETURN v_success;
+END;
+$BODY$;
+
+CREATE FUNCTION undelete_post(
+  IN p_post_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_tag_ids integer[];
+BEGIN
+  -- Update post
+  UPDATE post
+  SET is_deleted = false,
+      deleted_at = NULL
+  WHERE id = p_post_id
+    AND is_deleted;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  v_tag_ids := compute_post_tag_ids(array(SELECT tag_id FROM post_tag AS pt WHERE pt.post_id = p_post_id ORDER BY tag_id ASC));
+
+  -- Restore post_tag_id_cache so that the post
+  -- will be scanned for tag matches again
+  INSERT INTO post_tag_id_cache (post_id, tag_ids)
+  VALUES (p_post_id, v_tag_ids);
+
+  -- Update tag post counts to reflect restored post
+  UPDATE tag
+  SET post_count = post_count + 1
+  WHERE id = ANY(v_tag_ids);
+
+  -- Update search cache to reflect restored post
+  UPDATE search_cache
+  SET post_count = post_count + 1,
+      first_post_id = (CASE WHEN p_post_id > first_post_id THEN p_post_id ELSE first_post_id END),
+      last_page_post_ids = (CASE WHEN p_post_id < (SELECT MAX(id) FROM unnest(last_page_post_ids) AS id)
+                            THEN last_page_post_ids | p_post_id
+                            ELSE last_page_post_ids
+                            END)
+  WHERE v_tag_ids @> tag_ids
+    AND NOT v_tag_ids && exclude_tag_ids;
+
+  RETURN true;
+END;
+$BODY$;
+
+CREATE FUNCTION purge_post(
+  IN p_post_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  -- Only deleted posts can be purged
+  IF NOT EXISTS (SELECT 1 FROM post WHERE id = p_post_id AND is_deleted) THEN
+    RETURN false;
+  END IF;
+
+  -- Tag changes are not linked to the post by a foreign key
+  DELETE FROM post_tag_change
+  WHERE post_id = p_post_id;
+
+  -- Everything else linked to the post is removed by cascade
+  DELETE FROM post
+  WHERE id = p_post_id;
+
+  RETURN true;
+END;
+$BODY$;
+
+---- INDEXES ----
+
+CREATE INDEX post_deleted_at_idx ON post
+  USING btree
+  (deleted_at ASC NULLS LAST)
+  WHERE is_deleted;
@@ -0,0 +1,149 @@
+---- TABLES ----
+
+CREATE TABLE tag_edit_job
+(
+  id serial NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  user_id integer NOT NULL,
+  include_tags text[] NOT NULL DEFAULT '{}',
+  exclude_tags text[] NOT NULL DEFAULT '{}',
+  add_tags text[] NOT NULL DEFAULT '{}',
+  remove_tags text[] NOT NULL DEFAULT '{}',
+  post_ids integer[] NOT NULL DEFAULT '{}',
+  processed_count integer NOT NULL DEFAULT 0,
+  status text NOT NULL DEFAULT 'running' CHECK (status IN ('running', 'completed', 'failed')),
+
+  PRIMARY KEY (id),
+
+  FOREIGN KEY (user_id)
+    REFERENCES users (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID
+);
+
+SELECT manage_updated_at('tag_edit_job'); -- Automatically manage updated_at
+
+---- TYPES ----
+
+CREATE TYPE new_tag_edit_job AS (
+  include_tags text[],
+  exclude_tags text[],
+  add_tags text[],
+  remove_tags text[]
+);
+
+---- VIEWS ----
+
+CREATE VIEW view_tag_edit_job
+AS
+SELECT
+  j.id,
+  j.created_at,
+  j.updated_at,
+  j.user_id,
+  u.name AS user_name,
+  j.include_tags,
+  j.exclude_tags,
+  j.add_tags,
+  j.remove_tags,
+  cardinality(j.post_ids) AS post_count,
+  j.processed_count,
+  j.status
+FROM tag_edit_job AS j
+JOIN users AS u ON u.id = j.user_id;
+
+---- FUNCTIONS ----
+
+CREATE FUNCTION create_tag_edit_job(
+  IN p_job new_tag_edit_job,
+  IN p_post_ids integer[],
+  IN p_user_id integer
+)
+RETURNS integer
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_job_id integer;
+BEGIN
+  INSERT INTO tag_edit_job (
+    user_id,
+    include_tags,
+    exclude_tags,
+    add_tags,
+    remove_tags,
+    post_ids
+  ) VALUES (
+    p_user_id,
+    COALESCE(p_job.include_tags, '{}'),
+    COALESCE(p_job.exclude_tags, '{}'),
+    COALESCE(p_job.add_tags, '{}'),
+    COALESCE(p_job.remove_tags, '{}'),
+    p_post_ids
+  )
+  RETURNING id INTO v_job_id;
+
+  RETURN v_job_id;
+END;
+$BODY$;
+
+CREATE FUNCTION run_tag_edit_job_batch(
+  IN p_job_id integer,
+  IN p_batch_size integer
+)
+RETURNS integer
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_job tag_edit_job;
+  v_post_ids integer[];
+  v_post_id integer;
+  v_processed_count integer;
+BEGIN
+  SELECT * INTO v_job
+  FROM tag_edit_job
+  WHERE id = p_job_id
+    AND status = 'running'
+  FOR UPDATE;
+
+  IF NOT FOUND THEN
+    RETURN 0;
+  END IF;
+
+  v_post_ids := v_job.post_ids[v_job.processed_count + 1 : v_job.processed_count + p_batch_size];
+
+  FOREACH v_post_id IN ARRAY v_post_ids LOOP
+    -- Skip posts deleted since the job was created
+    IF EXISTS (SELECT 1 FROM post WHERE id = v_post_id AND NOT is_deleted) THEN
+      PERFORM update_post_tags(v_post_id, v_job.add_tags, v_job.remove_tags, v_job.user_id, false);
+    END IF;
+  END LOOP;
+
+  v_processed_count := v_job.processed_count + cardinality(v_post_ids);
+
+  UPDATE tag_edit_job
+  SET processed_count = v_processed_count,
+      status = (CASE WHEN v_processed_count >= cardinality(post_ids) THEN 'completed' ELSE status END)
+  WHERE id = p_job_id;
+
+  RETURN cardinality(v_post_ids);
+END;
+$BODY$;
+
+CREATE FUNCTION fail_tag_edit_job(
+  IN p_job_id integer
+)
+RETURNS VOID
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  UPDATE tag_edit_job
+  SET status = 'failed'
+  WHERE id = p_job_id
+    AND status = 'running';
+END;
+$BODY$;
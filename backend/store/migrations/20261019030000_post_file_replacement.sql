@@ -0,0 +1,212 @@
+---- TABLES ----
+
+CREATE TABLE post_file_replacement
+(
+  id serial NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+  post_id integer NOT NULL,
+  user_id integer NOT NULL,
+
+  old_filename text NOT NULL,
+  new_filename text NOT NULL,
+  old_size integer NOT NULL,
+  new_size integer NOT NULL,
+  old_width integer NOT NULL,
+  new_width integer NOT NULL,
+  old_height integer NOT NULL,
+  new_height integer NOT NULL,
+  old_hash text NOT NULL,
+  new_hash text NOT NULL,
+  old_ext text NOT NULL,
+  new_ext text NOT NULL,
+  old_tn_ext text NOT NULL,
+  new_tn_ext text NOT NULL,
+  old_phashes bigint[] NOT NULL DEFAULT '{}',
+  new_phashes bigint[] NOT NULL DEFAULT '{}',
+
+  PRIMARY KEY (id),
+
+  FOREIGN KEY (post_id)
+    REFERENCES post (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID
+);
+
+---- TYPES ----
+
+CREATE TYPE post_file AS (
+  filename text,
+  size integer,
+  width integer,
+  height integer,
+  hash text,
+  ext text,
+  tn_ext text
+);
+
+---- VIEWS ----
+
+CREATE VIEW view_post_file_replacement
+AS
+SELECT
+  pfr.id,
+  pfr.created_at,
+  pfr.post_id,
+  pfr.user_id,
+  u.name AS user_name,
+  pfr.old_filename,
+  pfr.new_filename,
+  pfr.old_size,
+  pfr.new_size,
+  pfr.old_width,
+  pfr.new_width,
+  pfr.old_height,
+  pfr.new_height,
+  pfr.old_hash,
+  pfr.new_hash,
+  pfr.old_ext,
+  pfr.new_ext,
+  pfr.old_tn_ext,
+  pfr.new_tn_ext
+FROM post_file_replacement AS pfr
+JOIN users AS u ON u.id = pfr.user_id;
+
+---- FUNCTIONS ----
+
+CREATE FUNCTION replace_post_file(
+  IN p_post_id integer,
+  IN p_file post_file,
+  IN p_phashes bigint[],
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_post post;
+BEGIN
+  SELECT * INTO v_post
+  FROM post
+  WHERE id = p_post_id
+    AND NOT is_deleted
+  FOR UPDATE;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Track file replacement, keeping the old file details to allow reverting
+  INSERT INTO post_file_replacement (
+    post_id,
+    user_id,
+    old_filename,
+    new_filename,
+    old_size,
+    new_size,
+    old_width,
+    new_width,
+    old_height,
+    new_height,
+    old_hash,
+    new_hash,
+    old_ext,
+    new_ext,
+    old_tn_ext,
+    new_tn_ext,
+    old_phashes,
+    new_phashes
+  ) VALUES (
+    p_post_id,
+    p_user_id,
+    v_post.filename,
+    p_file.filename,
+    v_post.size,
+    p_file.size,
+    v_post.width,
+    p_file.width,
+    v_post.height,
+    p_file.height,
+    v_post.hash,
+    p_file.hash,
+    v_post.ext,
+    p_file.ext,
+    v_post.tn_ext,
+    p_file.tn_ext,
+    array(SELECT phash FROM post_phash WHERE post_id = p_post_id ORDER BY phash ASC),
+    p_phashes
+  );
+
+  -- Update post file
+  UPDATE post
+  SET filename = p_file.filename,
+      size = p_file.size,
+      width = p_file.width,
+      height = p_file.height,
+      hash = p_file.hash,
+      ext = p_file.ext,
+      tn_ext = p_file.tn_ext
+  WHERE id = p_post_id;
+
+  -- Replace perceptual hashes
+  DELETE FROM post_phash
+  WHERE post_id = p_post_id;
+
+  INSERT INTO post_phash (post_id, phash)
+    SELECT DISTINCT p_post_id, phash
+    FROM unnest(p_phashes) AS phash;
+
+  RETURN true;
+END;
+$BODY$;
+
+CREATE FUNCTION revert_post_file_replacement(
+  IN p_post_id integer,
+  IN p_replacement_id integer,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_replacement post_file_replacement;
+BEGIN
+  SELECT * INTO v_replacement
+  FROM post_file_replacement
+  WHERE id = p_replacement_id
+    AND post_id = p_post_id;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Restore the file from before the replacement, recording it as a new replacement
+  RETURN replace_post_file(
+    p_post_id,
+    ROW(
+      v_replacement.old_filename,
+      v_replacement.old_size,
+      v_replacement.old_width,
+      v_replacement.old_height,
+      v_replacement.old_hash,
+      v_replacement.old_ext,
+      v_replacement.old_tn_ext
+    )::post_file,
+    v_replacement.old_phashes,
+    p_user_id
+  );
+END;
+$BODY$;
+
+---- INDEXES ----
+
+CREATE INDEX post_file_replacement_post_id_idx ON post_file_replacement
+  USING btree
+  (post_id ASC NULLS LAST);
+
+CREATE INDEX post_file_replacement_old_hash_idx ON post_file_replacement
+  USING btree
+  (old_hash ASC NULLS LAST);
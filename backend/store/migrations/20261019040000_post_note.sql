@@ -0,0 +1,235 @@
+---- TABLES ----
+
+-- Image annotations, such as translations of text in the image
+CREATE TABLE post_note
+(
+  id serial NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+  post_id integer NOT NULL,
+  user_id integer NOT NULL,
+
+  x integer NOT NULL,
+  y integer NOT NULL,
+  width integer NOT NULL,
+  height integer NOT NULL,
+  body text NOT NULL,
+  version integer NOT NULL DEFAULT 1,
+  is_deleted boolean NOT NULL DEFAULT false,
+
+  PRIMARY KEY (id),
+
+  FOREIGN KEY (post_id)
+    REFERENCES post (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID,
+
+  FOREIGN KEY (user_id)
+    REFERENCES users (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID
+);
+
+SELECT manage_updated_at('post_note'); -- Automatically manage updated_at
+
+-- Every version of a note, including the first one and its deletion
+CREATE TABLE post_note_version
+(
+  id serial NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+  note_id integer NOT NULL,
+  post_id integer NOT NULL,
+  user_id integer NOT NULL,
+
+  version integer NOT NULL,
+  x integer NOT NULL,
+  y integer NOT NULL,
+  width integer NOT NULL,
+  height integer NOT NULL,
+  body text NOT NULL,
+  is_deleted boolean NOT NULL,
+
+  PRIMARY KEY (id),
+
+  FOREIGN KEY (note_id)
+    REFERENCES post_note (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID
+);
+
+---- TYPES ----
+
+CREATE TYPE new_post_note AS (
+  post_id integer,
+
+  x integer,
+  y integer,
+  width integer,
+  height integer,
+  body text
+);
+
+CREATE TYPE update_post_note AS (
+  id integer,
+
+  x integer,
+  y integer,
+  width integer,
+  height integer,
+  body text
+);
+
+---- VIEWS ----
+
+CREATE VIEW view_post_note
+AS
+SELECT
+  n.id,
+  n.created_at,
+  n.updated_at,
+  n.post_id,
+  n.user_id,
+  u.name AS user_name,
+  n.x,
+  n.y,
+  n.width,
+  n.height,
+  n.body,
+  n.version
+FROM post_note AS n
+JOIN users AS u ON u.id = n.user_id
+WHERE NOT n.is_deleted;
+
+CREATE VIEW view_post_note_version
+AS
+SELECT
+  nv.id,
+  nv.created_at,
+  nv.note_id,
+  nv.post_id,
+  nv.user_id,
+  u.name AS user_name,
+  nv.version,
+  nv.x,
+  nv.y,
+  nv.width,
+  nv.height,
+  nv.body,
+  nv.is_deleted
+FROM post_note_version AS nv
+JOIN users AS u ON u.id = nv.user_id;
+
+---- FUNCTIONS ----
+
+CREATE FUNCTION create_post_note(
+  IN p_note new_post_note,
+  IN p_user_id integer
+)
+RETURNS integer
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_note_id integer;
+BEGIN
+  -- Notes can only be added to posts that are not deleted
+  IF NOT EXISTS (SELECT 1 FROM post WHERE id = p_note.post_id AND NOT is_deleted) THEN
+    RETURN NULL;
+  END IF;
+
+  -- Insert note
+  INSERT INTO post_note (post_id, user_id, x, y, width, height, body)
+  VALUES (p_note.post_id, p_user_id, p_note.x, p_note.y, p_note.width, p_note.height, p_note.body)
+  RETURNING id INTO v_note_id;
+
+  -- Track first version
+  INSERT INTO post_note_version (note_id, post_id, user_id, version, x, y, width, height, body, is_deleted)
+    SELECT id, post_id, p_user_id, version, x, y, width, height, body, is_deleted
+    FROM post_note
+    WHERE id = v_note_id;
+
+  RETURN v_note_id;
+END;
+$BODY$;
+
+CREATE FUNCTION update_post_note(
+  IN p_update_note update_post_note,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  UPDATE post_note
+  SET
+    x = p_update_note.x,
+    y = p_update_note.y,
+    width = p_update_note.width,
+    height = p_update_note.height,
+    body = p_update_note.body,
+    version = version + 1
+  WHERE id = p_update_note.id
+    AND NOT is_deleted;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Track new version
+  INSERT INTO post_note_version (note_id, post_id, user_id, version, x, y, width, height, body, is_deleted)
+    SELECT id, post_id, p_user_id, version, x, y, width, height, body, is_deleted
+    FROM post_note
+    WHERE id = p_update_note.id;
+
+  RETURN true;
+END;
+$BODY$;
+
+CREATE FUNCTION delete_post_note(
+  IN p_note_id integer,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  -- Only the creator of the note or some sort of admin can delete it
+  UPDATE post_note
+  SET
+    is_deleted = true,
+    version = version + 1
+  WHERE id = p_note_id
+    AND NOT is_deleted
+    AND (user_id = p_user_id OR (SELECT rank FROM users WHERE id = p_user_id) > 0);
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Track deletion as a new version
+  INSERT INTO post_note_version (note_id, post_id, user_id, version, x, y, width, height, body, is_deleted)
+    SELECT id, post_id, p_user_id, version, x, y, width, height, body, is_deleted
+    FROM post_note
+    WHERE id = p_note_id;
+
+  RETURN true;
+END;
+$BODY$;
+
+---- INDEXES ----
+
+CREATE INDEX post_note_post_id_idx ON post_note
+  USING btree
+  (post_id ASC NULLS LAST)
+  WHERE NOT is_deleted;
+
+CREATE INDEX post_note_version_note_id_idx ON post_note_version
+  USING btree
+  (note_id ASC NULLS LAST);
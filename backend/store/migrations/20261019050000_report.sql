@@ -0,0 +1,243 @@
+---- DROP OLD ----
+
+DROP FUNCTION delete_post;
+
+---- TABLES ----
+
+-- Comments removed by moderators are kept, but no longer listed
+ALTER TABLE comment
+  ADD COLUMN is_deleted boolean NOT NULL DEFAULT false;
+
+-- Reports of posts, comments, wiki pages and users for moderators to handle
+CREATE TABLE report
+(
+  id serial NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  user_id integer NOT NULL,
+
+  target_type text NOT NULL CHECK (target_type IN ('post', 'comment', 'wiki', 'user')),
+  target_id integer NOT NULL,
+  reason text NOT NULL,
+
+  status text NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'resolved', 'dismissed')),
+  resolver_id integer,
+  resolved_at timestamp with time zone,
+  resolution_note text,
+  target_deleted boolean NOT NULL DEFAULT false,
+
+  PRIMARY KEY (id),
+
+  FOREIGN KEY (user_id)
+    REFERENCES users (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID,
+
+  FOREIGN KEY (resolver_id)
+    REFERENCES users (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE SET NULL
+    NOT VALID
+);
+
+SELECT manage_updated_at('report'); -- Automatically manage updated_at
+
+---- TYPES ----
+
+CREATE TYPE new_report AS (
+  target_type text,
+  target_id integer,
+  reason text
+);
+
+CREATE TYPE resolve_report AS (
+  id integer,
+
+  status text,
+  resolution_note text,
+  delete_target boolean
+);
+
+---- VIEWS ----
+
+CREATE VIEW view_report
+AS
+SELECT
+  r.id,
+  r.created_at,
+  r.updated_at,
+  r.user_id,
+  u.name AS user_name,
+  r.target_type,
+  r.target_id,
+  -- Post of the reported post or comment
+  (CASE r.target_type
+   WHEN 'post' THEN r.target_id
+   WHEN 'comment' THEN (SELECT pc.post_id FROM post_comment AS pc WHERE pc.id = r.target_id)
+   END) AS target_post_id,
+  -- Title of the reported wiki page, or name of the reported user
+  (CASE r.target_type
+   WHEN 'wiki' THEN (SELECT w.title FROM wiki_pages AS w WHERE w.id = r.target_id)
+   WHEN 'user' THEN (SELECT tu.name FROM users AS tu WHERE tu.id = r.target_id)
+   END) AS target_name,
+  r.reason,
+  r.status,
+  r.resolver_id,
+  ru.name AS resolver_name,
+  r.resolved_at,
+  r.resolution_note,
+  r.target_deleted
+FROM report AS r
+JOIN users AS u ON u.id = r.user_id
+LEFT JOIN users AS ru ON ru.id = r.resolver_id;
+
+---- FUNCTIONS ----
+
+CREATE FUNCTION delete_post(
+  IN p_post_id integer,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_success boolean;
+  v_tag_ids integer[];
+BEGIN
+  -- Only the uploader of the post or some sort of admin can delete it
+  UPDATE post
+  SET is_deleted = true,
+      deleted_at = CURRENT_TIMESTAMP
+  WHERE id = p_post_id
+    AND NOT is_deleted
+    AND (user_id = p_user_id OR (SELECT rank FROM users WHERE id = p_user_id) > 0)
+  RETURNING true INTO v_success;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Get post tag IDs for later use
+  SELECT tag_ids INTO v_tag_ids FROM post_tag_id_cache WHERE post_id = p_post_id;
+
+  -- Delete post_tag_id_cache so that the post
+  -- will no longer be scanned for tag matches
+  DELETE FROM post_tag_id_cache
+  WHERE post_id = p_post_id;
+
+  -- Update tag post counts to reflect deleted post
+  UPDATE tag
+  SET post_count = post_count - 1
+  WHERE id = ANY(v_tag_ids);
+
+  -- Update search cache to reflect deleted post
+  UPDATE search_cache AS sc
+  SET post_count = post_count - 1,
+      first_post_id = (CASE WHEN p_post_id = first_post_id
+                       THEN (SELECT COALESCE(MAX(post_id), 0)
+                             FROM post_tag_id_cache AS ptic
+                             WHERE ptic.tag_ids @> sc.tag_ids
+                               AND NOT ptic.tag_ids && sc.exclude_tag_ids)
+                       ELSE first_post_id
+                       END),
+      last_page_post_ids = (CASE WHEN (SELECT p_post_id BETWEEN MIN(id) AND MAX(id) FROM unnest(last_page_post_ids) AS id)
+                            THEN last_page_post_ids - p_post_id
+                            ELSE last_page_post_ids
+                            END)
+  WHERE v_tag_ids @> tag_ids
+    AND NOT v_tag_ids && exclude_tag_ids;
+
+  RETURN v_success;
+END;
+$BODY$;
+
+CREATE FUNCTION create_report(
+  IN p_report new_report,
+  IN p_user_id integer
+)
+RETURNS integer
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_report_id integer;
+BEGIN
+  -- The reported item must exist
+  IF NOT (CASE p_report.target_type
+          WHEN 'post' THEN EXISTS (SELECT 1 FROM post WHERE id = p_report.target_id AND NOT is_deleted)
+          WHEN 'comment' THEN EXISTS (SELECT 1 FROM post_comment WHERE id = p_report.target_id AND NOT is_deleted)
+          WHEN 'wiki' THEN EXISTS (SELECT 1 FROM wiki_pages WHERE id = p_report.target_id AND NOT deleted)
+          WHEN 'user' THEN EXISTS (SELECT 1 FROM users WHERE id = p_report.target_id)
+          ELSE false
+          END) THEN
+    RETURN NULL;
+  END IF;
+
+  -- Insert report
+  INSERT INTO report (user_id, target_type, target_id, reason)
+  VALUES (p_user_id, p_report.target_type, p_report.target_id, p_report.reason)
+  RETURNING id INTO v_report_id;
+
+  RETURN v_report_id;
+END;
+$BODY$;
+
+CREATE FUNCTION resolve_report(
+  IN p_resolve_report resolve_report,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_report report;
+BEGIN
+  SELECT * INTO v_report
+  FROM report
+  WHERE id = p_resolve_report.id
+    AND status = 'open'
+  FOR UPDATE;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Delete the reported item along with resolving the report
+  IF p_resolve_report.delete_target THEN
+    CASE v_report.target_type
+    WHEN 'post' THEN
+      PERFORM delete_post(v_report.target_id, p_user_id);
+    WHEN 'comment' THEN
+      UPDATE comment
+      SET is_deleted = true
+      WHERE id = v_report.target_id;
+    WHEN 'wiki' THEN
+      UPDATE wiki_pages
+      SET deleted = true
+      WHERE id = v_report.target_id;
+    ELSE
+      RAISE EXCEPTION 'Reported % can not be deleted through a report', v_report.target_type;
+    END CASE;
+  END IF;
+
+  -- Update report
+  UPDATE report
+  SET status = p_resolve_report.status,
+      resolver_id = p_user_id,
+      resolved_at = CURRENT_TIMESTAMP,
+      resolution_note = p_resolve_report.resolution_note,
+      target_deleted = COALESCE(p_resolve_report.delete_target, false)
+  WHERE id = v_report.id;
+
+  RETURN true;
+END;
+$BODY$;
+
+---- INDEXES ----
+
+CREATE INDEX report_status_idx ON report
+  USING btree
+  (status ASC NULLS LAST, id DESC NULLS LAST);
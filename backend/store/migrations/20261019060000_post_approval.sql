@@ -0,0 +1,394 @@
+---- DROP OLD ----
+
+DROP FUNCTION get_view_posts;
+DROP VIEW view_post;
+DROP FUNCTION create_post;
+DROP TYPE new_post;
+DROP FUNCTION delete_post;
+DROP FUNCTION undelete_post;
+
+---- TABLES ----
+
+-- Posts awaiting approval are hidden like deleted posts, until they are approved
+ALTER TABLE post
+  ADD COLUMN is_pending boolean NOT NULL DEFAULT false;
+
+---- TYPES ----
+
+CREATE TYPE new_post AS (
+  user_id integer,
+  title text,
+  description text,
+  source text,
+  filename text,
+  size integer,
+  width integer,
+  height integer,
+  hash text,
+  ext text,
+  tn_ext text,
+  rating text,
+  is_pending boolean
+);
+
+---- VIEWS ----
+
+CREATE VIEW view_post
+AS
+SELECT
+  p.id,
+  p.created_at,
+  p.user_id,
+  u.name AS user_name,
+  p.title,
+  p.description,
+  p.source,
+  p.filename,
+  p.size,
+  p.width,
+  p.height,
+  p.hash,
+  p.ext,
+  p.tn_ext,
+  p.tags,
+  p.rating,
+  p.fav_count,
+  p.score,
+  ARRAY(
+    SELECT pp.pool_id
+    FROM pool_post AS pp
+    JOIN pool AS pl ON pl.id = pp.pool_id
+    WHERE pp.post_id = p.id
+      AND NOT pl.is_deleted
+    ORDER BY pp.pool_id ASC
+  ) AS pools,
+  (
+    SELECT pa.id
+    FROM post AS pa
+    WHERE pa.id = p.parent_id
+      AND NOT pa.is_deleted
+  ) AS parent_id,
+  ARRAY(
+    SELECT c.id
+    FROM post AS c
+    WHERE c.parent_id = p.id
+      AND NOT c.is_deleted
+    ORDER BY c.id ASC
+  ) AS child_ids
+FROM post AS p
+JOIN users AS u ON u.id = p.user_id
+WHERE NOT p.is_deleted
+  AND NOT p.is_pending;
+
+CREATE VIEW view_pending_post
+AS
+SELECT
+  p.id,
+  p.created_at,
+  p.user_id,
+  u.name AS user_name,
+  p.title,
+  p.filename,
+  p.hash,
+  p.ext,
+  p.tn_ext,
+  p.tags,
+  p.rating
+FROM post AS p
+JOIN users AS u ON u.id = p.user_id
+WHERE p.is_pending
+  AND NOT p.is_deleted;
+
+---- FUNCTIONS ----
+
+-- Hide a post from searches, page calculations and tag post counts,
+-- for posts that are deleted or awaiting approval
+CREATE FUNCTION remove_post_from_search(
+  IN p_post_id integer
+)
+RETURNS void
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_tag_ids integer[];
+BEGIN
+  -- Get post tag IDs for later use
+  SELECT tag_ids INTO v_tag_ids FROM post_tag_id_cache WHERE post_id = p_post_id;
+
+  -- Delete post_tag_id_cache so that the post
+  -- will no longer be scanned for tag matches
+  DELETE FROM post_tag_id_cache
+  WHERE post_id = p_post_id;
+
+  -- Update tag post counts to reflect hidden post
+  UPDATE tag
+  SET post_count = post_count - 1
+  WHERE id = ANY(v_tag_ids);
+
+  -- Update search cache to reflect hidden post
+  UPDATE search_cache AS sc
+  SET post_count = post_count - 1,
+      first_post_id = (CASE WHEN p_post_id = first_post_id
+                       THEN (SELECT COALESCE(MAX(post_id), 0)
+                             FROM post_tag_id_cache AS ptic
+                             WHERE ptic.tag_ids @> sc.tag_ids
+                               AND NOT ptic.tag_ids && sc.exclude_tag_ids)
+                       ELSE first_post_id
+                       END),
+      last_page_post_ids = (CASE WHEN (SELECT p_post_id BETWEEN MIN(id) AND MAX(id) FROM unnest(last_page_post_ids) AS id)
+                            THEN last_page_post_ids - p_post_id
+                            ELSE last_page_post_ids
+                            END)
+  WHERE v_tag_ids @> tag_ids
+    AND NOT v_tag_ids && exclude_tag_ids;
+END;
+$BODY$;
+
+-- Show a post hidden by remove_post_from_search in searches and tag post counts again
+CREATE FUNCTION add_post_to_search(
+  IN p_post_id integer
+)
+RETURNS void
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_tag_ids integer[];
+BEGIN
+  v_tag_ids := compute_post_tag_ids(array(SELECT tag_id FROM post_tag AS pt WHERE pt.post_id = p_post_id ORDER BY tag_id ASC));
+
+  -- Restore post_tag_id_cache so that the post
+  -- will be scanned for tag matches again
+  INSERT INTO post_tag_id_cache (post_id, tag_ids)
+  VALUES (p_post_id, v_tag_ids);
+
+  -- Update tag post counts to reflect restored post
+  UPDATE tag
+  SET post_count = post_count + 1
+  WHERE id = ANY(v_tag_ids);
+
+  -- Update search cache to reflect restored post
+  UPDATE search_cache
+  SET post_count = post_count + 1,
+      first_post_id = (CASE WHEN p_post_id > first_post_id THEN p_post_id ELSE first_post_id END),
+      last_page_post_ids = (CASE WHEN p_post_id < (SELECT MAX(id) FROM unnest(last_page_post_ids) AS id)
+                            THEN last_page_post_ids | p_post_id
+                            ELSE last_page_post_ids
+                            END)
+  WHERE v_tag_ids @> tag_ids
+    AND NOT v_tag_ids && exclude_tag_ids;
+END;
+$BODY$;
+
+CREATE FUNCTION get_view_posts(
+  IN p_include_tags text[],
+  IN p_exclude_tags text[],
+  IN p_start_id integer,
+  IN p_limit integer
+)
+RETURNS SETOF view_post
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_tag_ids integer[];
+  v_exclude_tag_ids integer[];
+  v_valid boolean;
+BEGIN
+  SELECT * INTO v_tag_ids, v_exclude_tag_ids, v_valid FROM resolve_search_tags(p_include_tags, p_exclude_tags);
+  IF NOT v_valid THEN
+    RETURN;
+  END IF;
+
+  RETURN QUERY
+  SELECT p.*
+  FROM post_tag_id_cache AS ptic
+  JOIN view_post AS p ON p.id = ptic.post_id
+  WHERE
+    -- Only scan forward from the origin
+    ptic.post_id <= p_start_id
+    -- Posts with fewer tags than the required tags cannot qualify
+    AND icount(ptic.tag_ids) >= icount(v_tag_ids)
+    -- Post must have all the included tags
+    AND ptic.tag_ids @> v_tag_ids
+    -- Post must not have any of the excluded tags
+    AND NOT ptic.tag_ids && v_exclude_tag_ids
+  ORDER BY ptic.post_id DESC
+  LIMIT p_limit;
+END;
+$BODY$ STABLE;
+
+CREATE FUNCTION create_post(
+  IN p_post new_post,
+  IN p_tags text[],
+  IN p_phashes bigint[]
+)
+RETURNS integer
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_post_id integer;
+BEGIN
+  -- Insert post
+  INSERT INTO post (
+    user_id,
+    title,
+    description,
+    source,
+    filename,
+    size,
+    width,
+    height,
+    hash,
+    ext,
+    tn_ext,
+    rating,
+    is_pending
+  )
+  SELECT
+    p_post.user_id, -- user_id
+    p_post.title, -- title
+    p_post.description, -- description
+    p_post.source, -- source
+    p_post.filename, -- filename
+    p_post.size, -- size
+    p_post.width, -- width
+    p_post.height, -- height
+    p_post.hash, -- hash
+    p_post.ext, -- ext
+    p_post.tn_ext, -- tn_ext
+    COALESCE(p_post.rating, 'general'), -- rating
+    COALESCE(p_post.is_pending, false) -- is_pending
+  RETURNING id INTO v_post_id;
+
+  -- Create post_tag_id_cache
+  INSERT INTO post_tag_id_cache (post_id) VALUES (v_post_id);
+
+  -- Store perceptual hashes
+  INSERT INTO post_phash (post_id, phash)
+    SELECT DISTINCT v_post_id, phash
+    FROM unnest(p_phashes) AS phash;
+
+  -- Add post tags
+  PERFORM update_post_tags(v_post_id, p_tags, '{}', p_post.user_id, true);
+
+  -- Hide the post until it is approved
+  IF p_post.is_pending THEN
+    PERFORM remove_post_from_search(v_post_id);
+  END IF;
+
+  RETURN v_post_id;
+END;
+$BODY$;
+
+CREATE FUNCTION delete_post(
+  IN p_post_id integer,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  -- Only the uploader of the post or some sort of admin can delete it
+  UPDATE post
+  SET is_deleted = true,
+      deleted_at = CURRENT_TIMESTAMP
+  WHERE id = p_post_id
+    AND NOT is_deleted
+    AND (user_id = p_user_id OR (SELECT rank FROM users WHERE id = p_user_id) > 0);
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Hide the post from searches and tag post counts
+  PERFORM remove_post_from_search(p_post_id);
+
+  RETURN true;
+END;
+$BODY$;
+
+CREATE FUNCTION undelete_post(
+  IN p_post_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  -- Update post
+  UPDATE post
+  SET is_deleted = false,
+      deleted_at = NULL
+  WHERE id = p_post_id
+    AND is_deleted;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Show the post in searches and tag post counts again, unless it still awaits approval
+  IF NOT (SELECT is_pending FROM post WHERE id = p_post_id) THEN
+    PERFORM add_post_to_search(p_post_id);
+  END IF;
+
+  RETURN true;
+END;
+$BODY$;
+
+CREATE FUNCTION approve_post(
+  IN p_post_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  UPDATE post
+  SET is_pending = false
+  WHERE id = p_post_id
+    AND is_pending
+    AND NOT is_deleted;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Make the post public
+  PERFORM add_post_to_search(p_post_id);
+
+  RETURN true;
+END;
+$BODY$;
+
+CREATE FUNCTION reject_post(
+  IN p_post_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  -- Rejected posts are deleted, so that they can be purged later.
+  -- They are no longer pending, so undeleting one makes it public.
+  UPDATE post
+  SET is_pending = false,
+      is_deleted = true,
+      deleted_at = CURRENT_TIMESTAMP
+  WHERE id = p_post_id
+    AND is_pending
+    AND NOT is_deleted;
+
+  RETURN FOUND;
+END;
+$BODY$;
+
+---- INDEXES ----
+
+CREATE INDEX post_is_pending_idx ON post
+  USING btree
+  (id DESC NULLS LAST)
+  WHERE is_pending;
@@ -0,0 +1,342 @@
+---- DROP OLD ----
+
+DROP FUNCTION get_view_posts;
+DROP VIEW view_post;
+DROP FUNCTION create_post;
+DROP TYPE new_post;
+DROP FUNCTION revert_post_file_replacement;
+DROP FUNCTION replace_post_file;
+DROP TYPE post_file;
+
+---- TABLES ----
+
+-- Names of the resized renditions generated for the post file.
+-- Existing posts only have the original thumbnail rendition.
+ALTER TABLE post
+  ADD COLUMN renditions text[] NOT NULL DEFAULT '{}';
+
+UPDATE post SET renditions = '{t}';
+
+ALTER TABLE post_file_replacement
+  ADD COLUMN old_renditions text[] NOT NULL DEFAULT '{}',
+  ADD COLUMN new_renditions text[] NOT NULL DEFAULT '{}';
+
+UPDATE post_file_replacement SET old_renditions = '{t}', new_renditions = '{t}';
+
+---- TYPES ----
+
+CREATE TYPE new_post AS (
+  user_id integer,
+  title text,
+  description text,
+  source text,
+  filename text,
+  size integer,
+  width integer,
+  height integer,
+  hash text,
+  ext text,
+  tn_ext text,
+  renditions text[],
+  rating text,
+  is_pending boolean
+);
+
+CREATE TYPE post_file AS (
+  filename text,
+  size integer,
+  width integer,
+  height integer,
+  hash text,
+  ext text,
+  tn_ext text,
+  renditions text[]
+);
+
+---- VIEWS ----
+
+CREATE VIEW view_post
+AS
+SELECT
+  p.id,
+  p.created_at,
+  p.user_id,
+  u.name AS user_name,
+  p.title,
+  p.description,
+  p.source,
+  p.filename,
+  p.size,
+  p.width,
+  p.height,
+  p.hash,
+  p.ext,
+  p.tn_ext,
+  p.renditions,
+  p.tags,
+  p.rating,
+  p.fav_count,
+  p.score,
+  ARRAY(
+    SELECT pp.pool_id
+    FROM pool_post AS pp
+    JOIN pool AS pl ON pl.id = pp.pool_id
+    WHERE pp.post_id = p.id
+      AND NOT pl.is_deleted
+    ORDER BY pp.pool_id ASC
+  ) AS pools,
+  (
+    SELECT pa.id
+    FROM post AS pa
+    WHERE pa.id = p.parent_id
+      AND NOT pa.is_deleted
+  ) AS parent_id,
+  ARRAY(
+    SELECT c.id
+    FROM post AS c
+    WHERE c.parent_id = p.id
+      AND NOT c.is_deleted
+    ORDER BY c.id ASC
+  ) AS child_ids
+FROM post AS p
+JOIN users AS u ON u.id = p.user_id
+WHERE NOT p.is_deleted
+  AND NOT p.is_pending;
+
+---- FUNCTIONS ----
+
+CREATE FUNCTION get_view_posts(
+  IN p_include_tags text[],
+  IN p_exclude_tags text[],
+  IN p_start_id integer,
+  IN p_limit integer
+)
+RETURNS SETOF view_post
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_tag_ids integer[];
+  v_exclude_tag_ids integer[];
+  v_valid boolean;
+BEGIN
+  SELECT * INTO v_tag_ids, v_exclude_tag_ids, v_valid FROM resolve_search_tags(p_include_tags, p_exclude_tags);
+  IF NOT v_valid THEN
+    RETURN;
+  END IF;
+
+  RETURN QUERY
+  SELECT p.*
+  FROM post_tag_id_cache AS ptic
+  JOIN view_post AS p ON p.id = ptic.post_id
+  WHERE
+    -- Only scan forward from the origin
+    ptic.post_id <= p_start_id
+    -- Posts with fewer tags than the required tags cannot qualify
+    AND icount(ptic.tag_ids) >= icount(v_tag_ids)
+    -- Post must have all the included tags
+    AND ptic.tag_ids @> v_tag_ids
+    -- Post must not have any of the excluded tags
+    AND NOT ptic.tag_ids && v_exclude_tag_ids
+  ORDER BY ptic.post_id DESC
+  LIMIT p_limit;
+END;
+$BODY$ STABLE;
+
+CREATE FUNCTION create_post(
+  IN p_post new_post,
+  IN p_tags text[],
+  IN p_phashes bigint[]
+)
+RETURNS integer
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_post_id integer;
+BEGIN
+  -- Insert post
+  INSERT INTO post (
+    user_id,
+    title,
+    description,
+    source,
+    filename,
+    size,
+    width,
+    height,
+    hash,
+    ext,
+    tn_ext,
+    renditions,
+    rating,
+    is_pending
+  )
+  SELECT
+    p_post.user_id, -- user_id
+    p_post.title, -- title
+    p_post.description, -- description
+    p_post.source, -- source
+    p_post.filename, -- filename
+    p_post.size, -- size
+    p_post.width, -- width
+    p_post.height, -- height
+    p_post.hash, -- hash
+    p_post.ext, -- ext
+    p_post.tn_ext, -- tn_ext
+    COALESCE(p_post.renditions, '{}'), -- renditions
+    COALESCE(p_post.rating, 'general'), -- rating
+    COALESCE(p_post.is_pending, false) -- is_pending
+  RETURNING id INTO v_post_id;
+
+  -- Create post_tag_id_cache
+  INSERT INTO post_tag_id_cache (post_id) VALUES (v_post_id);
+
+  -- Store perceptual hashes
+  INSERT INTO post_phash (post_id, phash)
+    SELECT DISTINCT v_post_id, phash
+    FROM unnest(p_phashes) AS phash;
+
+  -- Add post tags
+  PERFORM update_post_tags(v_post_id, p_tags, '{}', p_post.user_id, true);
+
+  -- Hide the post until it is approved
+  IF p_post.is_pending THEN
+    PERFORM remove_post_from_search(v_post_id);
+  END IF;
+
+  RETURN v_post_id;
+END;
+$BODY$;
+
+CREATE FUNCTION replace_post_file(
+  IN p_post_id integer,
+  IN p_file post_file,
+  IN p_phashes bigint[],
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_post post;
+BEGIN
+  SELECT * INTO v_post
+  FROM post
+  WHERE id = p_post_id
+    AND NOT is_deleted
+  FOR UPDATE;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Track file replacement, keeping the old file details to allow reverting
+  INSERT INTO post_file_replacement (
+    post_id,
+    user_id,
+    old_filename,
+    new_filename,
+    old_size,
+    new_size,
+    old_width,
+    new_width,
+    old_height,
+    new_height,
+    old_hash,
+    new_hash,
+    old_ext,
+    new_ext,
+    old_tn_ext,
+    new_tn_ext,
+    old_renditions,
+    new_renditions,
+    old_phashes,
+    new_phashes
+  ) VALUES (
+    p_post_id,
+    p_user_id,
+    v_post.filename,
+    p_file.filename,
+    v_post.size,
+    p_file.size,
+    v_post.width,
+    p_file.width,
+    v_post.height,
+    p_file.height,
+    v_post.hash,
+    p_file.hash,
+    v_post.ext,
+    p_file.ext,
+    v_post.tn_ext,
+    p_file.tn_ext,
+    v_post.renditions,
+    p_file.renditions,
+    array(SELECT phash FROM post_phash WHERE post_id = p_post_id ORDER BY phash ASC),
+    p_phashes
+  );
+
+  -- Update post file
+  UPDATE post
+  SET filename = p_file.filename,
+      size = p_file.size,
+      width = p_file.width,
+      height = p_file.height,
+      hash = p_file.hash,
+      ext = p_file.ext,
+      tn_ext = p_file.tn_ext,
+      renditions = p_file.renditions
+  WHERE id = p_post_id;
+
+  -- Replace perceptual hashes
+  DELETE FROM post_phash
+  WHERE post_id = p_post_id;
+
+  INSERT INTO post_phash (post_id, phash)
+    SELECT DISTINCT p_post_id, phash
+    FROM unnest(p_phashes) AS phash;
+
+  RETURN true;
+END;
+$BODY$;
+
+CREATE FUNCTION revert_post_file_replacement(
+  IN p_post_id integer,
+  IN p_replacement_id integer,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_replacement post_file_replacement;
+BEGIN
+  SELECT * INTO v_replacement
+  FROM post_file_replacement
+  WHERE id = p_replacement_id
+    AND post_id = p_post_id;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Restore the file from before the replacement, recording it as a new replacement
+  RETURN replace_post_file(
+    p_post_id,
+    ROW(
+      v_replacement.old_filename,
+      v_replacement.old_size,
+      v_replacement.old_width,
+      v_replacement.old_height,
+      v_replacement.old_hash,
+      v_replacement.old_ext,
+      v_replacement.old_tn_ext,
+      v_replacement.old_renditions
+    )::post_file,
+    v_replacement.old_phashes,
+    p_user_id
+  );
+END;
+$BODY$;
@@ -0,0 +1,25 @@
+---- FUNCTIONS ----
+
+-- Update the metadata of a post file after its thumbnails have been regenerated
+CREATE FUNCTION update_post_file_metadata(
+  IN p_post_id integer,
+  IN p_width integer,
+  IN p_height integer,
+  IN p_tn_ext text,
+  IN p_renditions text[]
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  UPDATE post
+  SET width = p_width,
+      height = p_height,
+      tn_ext = p_tn_ext,
+      renditions = p_renditions
+  WHERE id = p_post_id;
+
+  RETURN FOUND;
+END;
+$BODY$;
@@ -0,0 +1,76 @@
+---- DROP OLD ----
+
+DROP FUNCTION update_post_file_metadata;
+
+---- FUNCTIONS ----
+
+-- Get one of the four 16-bit bands of a perceptual hash.
+-- Hashes within a Hamming distance of d have at least one band
+-- within a distance of d / 4, which allows finding similar hashes using indexes.
+CREATE FUNCTION phash_band(
+  IN p_phash bigint,
+  IN p_band integer
+)
+RETURNS integer
+LANGUAGE sql
+
+AS $BODY$
+  SELECT ((p_phash >> (p_band * 16)) & 65535)::integer;
+$BODY$ IMMUTABLE;
+
+-- Update the metadata of a post file after its thumbnails have been regenerated.
+-- Perceptual hashes are only replaced if any could be computed.
+CREATE FUNCTION update_post_file_metadata(
+  IN p_post_id integer,
+  IN p_width integer,
+  IN p_height integer,
+  IN p_tn_ext text,
+  IN p_renditions text[],
+  IN p_phashes bigint[]
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  UPDATE post
+  SET width = p_width,
+      height = p_height,
+      tn_ext = p_tn_ext,
+      renditions = p_renditions
+  WHERE id = p_post_id;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  IF cardinality(p_phashes) > 0 THEN
+    DELETE FROM post_phash WHERE post_id = p_post_id;
+
+    INSERT INTO post_phash (post_id, phash)
+      SELECT DISTINCT p_post_id, phash
+      FROM unnest(p_phashes) AS phash;
+  END IF;
+
+  RETURN true;
+END;
+$BODY$;
+
+---- INDEXES ----
+
+-- Perceptual hash band indexes, for finding similar posts
+CREATE INDEX post_phash_band_0_idx ON post_phash
+  USING btree
+  (phash_band(phash, 0));
+
+CREATE INDEX post_phash_band_1_idx ON post_phash
+  USING btree
+  (phash_band(phash, 1));
+
+CREATE INDEX post_phash_band_2_idx ON post_phash
+  USING btree
+  (phash_band(phash, 2));
+
+CREATE INDEX post_phash_band_3_idx ON post_phash
+  USING btree
+  (phash_band(phash, 3));
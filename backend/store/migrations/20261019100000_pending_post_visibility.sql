@@ -0,0 +1,515 @@
+---- DROP OLD ----
+
+DROP FUNCTION set_pool_posts;
+DROP FUNCTION favorite_post;
+DROP FUNCTION unfavorite_post;
+DROP FUNCTION vote_post;
+DROP FUNCTION create_post_note;
+DROP FUNCTION create_report;
+DROP FUNCTION revert_post_tag_change;
+DROP FUNCTION run_tag_edit_job_batch;
+DROP FUNCTION update_post_tags;
+
+---- VIEWS ----
+
+-- Posts awaiting approval are not listed as parents, children or pool posts
+CREATE OR REPLACE VIEW view_post
+AS
+SELECT
+  p.id,
+  p.created_at,
+  p.user_id,
+  u.name AS user_name,
+  p.title,
+  p.description,
+  p.source,
+  p.filename,
+  p.size,
+  p.width,
+  p.height,
+  p.hash,
+  p.ext,
+  p.tn_ext,
+  p.renditions,
+  p.tags,
+  p.rating,
+  p.fav_count,
+  p.score,
+  ARRAY(
+    SELECT pp.pool_id
+    FROM pool_post AS pp
+    JOIN pool AS pl ON pl.id = pp.pool_id
+    WHERE pp.post_id = p.id
+      AND NOT pl.is_deleted
+    ORDER BY pp.pool_id ASC
+  ) AS pools,
+  (
+    SELECT pa.id
+    FROM post AS pa
+    WHERE pa.id = p.parent_id
+      AND NOT pa.is_deleted
+      AND NOT pa.is_pending
+  ) AS parent_id,
+  ARRAY(
+    SELECT c.id
+    FROM post AS c
+    WHERE c.parent_id = p.id
+      AND NOT c.is_deleted
+      AND NOT c.is_pending
+    ORDER BY c.id ASC
+  ) AS child_ids
+FROM post AS p
+JOIN users AS u ON u.id = p.user_id
+WHERE NOT p.is_deleted
+  AND NOT p.is_pending;
+
+CREATE OR REPLACE VIEW view_pool
+AS
+SELECT
+  pl.id,
+  pl.created_at,
+  pl.updated_at,
+  pl.user_id,
+  u.name AS user_name,
+  pl.name,
+  pl.description,
+  ARRAY(
+    SELECT pp.post_id
+    FROM pool_post AS pp
+    JOIN post AS p ON p.id = pp.post_id
+    WHERE pp.pool_id = pl.id
+      AND NOT p.is_deleted
+      AND NOT p.is_pending
+    ORDER BY pp.position ASC
+  ) AS post_ids
+FROM pool AS pl
+JOIN users AS u ON u.id = pl.user_id
+WHERE NOT pl.is_deleted;
+
+---- FUNCTIONS ----
+
+CREATE FUNCTION set_pool_posts(
+  IN p_pool_id integer,
+  IN p_post_ids integer[],
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_old_post_ids integer[];
+  v_new_post_ids integer[];
+  v_post_ids_added integer[];
+  v_post_ids_removed integer[];
+BEGIN
+  -- Lock the pool, so that concurrent changes are tracked correctly
+  PERFORM 1 FROM pool WHERE id = p_pool_id AND NOT is_deleted FOR UPDATE;
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  SELECT COALESCE(array_agg(post_id), '{}') INTO v_old_post_ids
+  FROM pool_post
+  WHERE pool_id = p_pool_id;
+
+  -- Keep only the first occurrence of each existing post, in the specified order
+  SELECT COALESCE(array_agg(x.post_id ORDER BY x.position), '{}') INTO v_new_post_ids
+  FROM (
+    SELECT u.post_id, MIN(u.position) AS position
+    FROM unnest(p_post_ids) WITH ORDINALITY AS u(post_id, position)
+    JOIN post AS p ON p.id = u.post_id
+    WHERE NOT p.is_deleted
+      AND NOT p.is_pending
+    GROUP BY u.post_id
+  ) AS x;
+
+  v_post_ids_added := v_new_post_ids - v_old_post_ids;
+  v_post_ids_removed := v_old_post_ids - v_new_post_ids;
+
+  DELETE FROM pool_post
+  WHERE pool_id = p_pool_id;
+
+  INSERT INTO pool_post (pool_id, post_id, position)
+  SELECT p_pool_id, u.post_id, u.position
+  FROM unnest(v_new_post_ids) WITH ORDINALITY AS u(post_id, position);
+
+  -- Track membership changes
+  IF icount(v_post_ids_added) > 0 OR icount(v_post_ids_removed) > 0 THEN
+    INSERT INTO pool_post_change (pool_id, user_id, post_ids_added, post_ids_removed)
+    VALUES (p_pool_id, p_user_id, v_post_ids_added, v_post_ids_removed);
+  END IF;
+
+  UPDATE pool
+  SET updated_at = CURRENT_TIMESTAMP
+  WHERE id = p_pool_id;
+
+  RETURN true;
+END;
+$BODY$;
+
+CREATE FUNCTION favorite_post(
+  IN p_post_id integer,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  -- Deleted posts and posts awaiting approval cannot be favorited
+  IF NOT EXISTS (SELECT 1 FROM post WHERE id = p_post_id AND NOT is_deleted AND NOT is_pending) THEN
+    RETURN false;
+  END IF;
+
+  INSERT INTO post_favorite (post_id, user_id)
+  VALUES (p_post_id, p_user_id)
+  ON CONFLICT DO NOTHING;
+
+  -- Only count the favorite if it did not already exist
+  IF FOUND THEN
+    UPDATE post
+    SET fav_count = fav_count + 1
+    WHERE id = p_post_id;
+  END IF;
+
+  RETURN true;
+END;
+$BODY$;
+
+CREATE FUNCTION unfavorite_post(
+  IN p_post_id integer,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  IF NOT EXISTS (SELECT 1 FROM post WHERE id = p_post_id AND NOT is_deleted AND NOT is_pending) THEN
+    RETURN false;
+  END IF;
+
+  DELETE FROM post_favorite
+  WHERE post_id = p_post_id
+    AND user_id = p_user_id;
+
+  IF FOUND THEN
+    UPDATE post
+    SET fav_count = fav_count - 1
+    WHERE id = p_post_id;
+  END IF;
+
+  RETURN true;
+END;
+$BODY$;
+
+CREATE FUNCTION vote_post(
+  IN p_post_id integer,
+  IN p_user_id integer,
+  IN p_score smallint
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_old_score smallint;
+BEGIN
+  -- Deleted posts and posts awaiting approval cannot be voted on
+  PERFORM 1 FROM post WHERE id = p_post_id AND NOT is_deleted AND NOT is_pending FOR UPDATE;
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  SELECT score INTO v_old_score
+  FROM post_vote
+  WHERE post_id = p_post_id
+    AND user_id = p_user_id;
+
+  IF p_score = 0 THEN
+    -- A score of 0 removes the vote
+    DELETE FROM post_vote
+    WHERE post_id = p_post_id
+      AND user_id = p_user_id;
+  ELSIF v_old_score IS DISTINCT FROM p_score THEN
+    -- Each user has at most one vote per post
+    INSERT INTO post_vote (post_id, user_id, score)
+    VALUES (p_post_id, p_user_id, p_score)
+    ON CONFLICT (post_id, user_id) DO UPDATE
+    SET score = EXCLUDED.score;
+  END IF;
+
+  -- Update cached score with the difference
+  UPDATE post
+  SET score = score + p_score - COALESCE(v_old_score, 0)
+  WHERE id = p_post_id
+    AND p_score IS DISTINCT FROM COALESCE(v_old_score, 0);
+
+  RETURN true;
+END;
+$BODY$;
+
+CREATE FUNCTION create_post_note(
+  IN p_note new_post_note,
+  IN p_user_id integer
+)
+RETURNS integer
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_note_id integer;
+BEGIN
+  -- Notes can only be added to posts that are not deleted or awaiting approval
+  IF NOT EXISTS (SELECT 1 FROM post WHERE id = p_note.post_id AND NOT is_deleted AND NOT is_pending) THEN
+    RETURN NULL;
+  END IF;
+
+  -- Insert note
+  INSERT INTO post_note (post_id, user_id, x, y, width, height, body)
+  VALUES (p_note.post_id, p_user_id, p_note.x, p_note.y, p_note.width, p_note.height, p_note.body)
+  RETURNING id INTO v_note_id;
+
+  -- Track first version
+  INSERT INTO post_note_version (note_id, post_id, user_id, version, x, y, width, height, body, is_deleted)
+    SELECT id, post_id, p_user_id, version, x, y, width, height, body, is_deleted
+    FROM post_note
+    WHERE id = v_note_id;
+
+  RETURN v_note_id;
+END;
+$BODY$;
+
+CREATE FUNCTION create_report(
+  IN p_report new_report,
+  IN p_user_id integer
+)
+RETURNS integer
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_report_id integer;
+BEGIN
+  -- The reported item must exist
+  IF NOT (CASE p_report.target_type
+          WHEN 'post' THEN EXISTS (SELECT 1 FROM post WHERE id = p_report.target_id AND NOT is_deleted AND NOT is_pending)
+          WHEN 'comment' THEN EXISTS (SELECT 1 FROM post_comment WHERE id = p_report.target_id AND NOT is_deleted)
+          WHEN 'wiki' THEN EXISTS (SELECT 1 FROM wiki_pages WHERE id = p_report.target_id AND NOT deleted)
+          WHEN 'user' THEN EXISTS (SELECT 1 FROM users WHERE id = p_report.target_id)
+          ELSE false
+          END) THEN
+    RETURN NULL;
+  END IF;
+
+  -- Insert report
+  INSERT INTO report (user_id, target_type, target_id, reason)
+  VALUES (p_user_id, p_report.target_type, p_report.target_id, p_report.reason)
+  RETURNING id INTO v_report_id;
+
+  RETURN v_report_id;
+END;
+$BODY$;
+
+CREATE FUNCTION revert_post_tag_change(
+  IN p_change_id integer,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_change post_tag_change;
+BEGIN
+  SELECT * INTO v_change
+  FROM post_tag_change
+  WHERE id = p_change_id;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Tags of deleted posts and posts awaiting approval are not changed
+  IF NOT EXISTS (SELECT 1 FROM post WHERE id = v_change.post_id AND NOT is_deleted AND NOT is_pending) THEN
+    RETURN false;
+  END IF;
+
+  -- Nothing to revert
+  IF COALESCE(icount(v_change.tag_ids_added), 0) = 0 AND COALESCE(icount(v_change.tag_ids_removed), 0) = 0 THEN
+    RETURN true;
+  END IF;
+
+  -- Re-add the removed tags and remove the added tags.
+  -- This is tracked as a new change by the reverting user.
+  PERFORM update_post_tags(
+    v_change.post_id,
+    ARRAY(SELECT tag FROM tag WHERE id = ANY(v_change.tag_ids_removed)),
+    ARRAY(SELECT tag FROM tag WHERE id = ANY(v_change.tag_ids_added)),
+    p_user_id,
+    false
+  );
+
+  RETURN true;
+END;
+$BODY$;
+
+CREATE FUNCTION run_tag_edit_job_batch(
+  IN p_job_id integer,
+  IN p_batch_size integer
+)
+RETURNS integer
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_job tag_edit_job;
+  v_post_ids integer[];
+  v_post_id integer;
+  v_processed_count integer;
+BEGIN
+  SELECT * INTO v_job
+  FROM tag_edit_job
+  WHERE id = p_job_id
+    AND status = 'running'
+  FOR UPDATE;
+
+  IF NOT FOUND THEN
+    RETURN 0;
+  END IF;
+
+  v_post_ids := v_job.post_ids[v_job.processed_count + 1 : v_job.processed_count + p_batch_size];
+
+  FOREACH v_post_id IN ARRAY v_post_ids LOOP
+    -- Skip posts deleted since the job was created, and posts awaiting approval
+    IF EXISTS (SELECT 1 FROM post WHERE id = v_post_id AND NOT is_deleted AND NOT is_pending) THEN
+      PERFORM update_post_tags(v_post_id, v_job.add_tags, v_job.remove_tags, v_job.user_id, false);
+    END IF;
+  END LOOP;
+
+  v_processed_count := v_job.processed_count + cardinality(v_post_ids);
+
+  UPDATE tag_edit_job
+  SET processed_count = v_processed_count,
+      status = (CASE WHEN v_processed_count >= cardinality(post_ids) THEN 'completed' ELSE status END)
+  WHERE id = p_job_id;
+
+  RETURN cardinality(v_post_ids);
+END;
+$BODY$;
+
+CREATE FUNCTION update_post_tags(
+  IN p_post_id integer,
+  IN p_add_tags text[],
+  IN p_remove_tags text[],
+  IN p_user_id integer,
+  IN p_new_post boolean
+)
+RETURNS VOID
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_add_tag_ids integer[];
+  v_remove_tag_ids integer[];
+  v_old_tag_ids integer[];
+  v_new_tag_ids integer[];
+BEGIN
+  -- Create missing tags
+  PERFORM create_missing_tags(p_add_tags);
+
+  v_add_tag_ids := get_tag_ids(p_add_tags);
+  v_remove_tag_ids := get_tag_ids(p_remove_tags);
+
+  -- Retrieve old tags
+  v_old_tag_ids := array(SELECT tag_id FROM post_tag AS pt WHERE pt.post_id = p_post_id ORDER BY tag_id ASC);
+
+  -- Compute new tags
+  v_new_tag_ids := (v_old_tag_ids | v_add_tag_ids) - v_remove_tag_ids;
+
+  -- Add links for added tags to post
+  INSERT INTO post_tag (post_id, tag_id)
+    SELECT p_post_id, tag_id
+    FROM unnest(v_add_tag_ids) AS tag_id
+    ON CONFLICT(post_id, tag_id)
+    DO NOTHING;
+
+  -- Remove removed tag links for post
+  DELETE FROM post_tag AS pt
+  USING unnest(v_remove_tag_ids) AS rtid
+  WHERE pt.post_id = p_post_id AND pt.tag_id = rtid;
+
+  -- Update post tags
+  UPDATE post
+  SET tags = array(SELECT tag
+                   FROM tag
+                   WHERE id = ANY(v_new_tag_ids)
+                   ORDER BY tag ASC)
+  WHERE id = p_post_id;
+
+  v_old_tag_ids := compute_post_tag_ids(v_old_tag_ids);
+  v_new_tag_ids := compute_post_tag_ids(v_new_tag_ids);
+
+  -- Update post_tag_id_cache
+  UPDATE post_tag_id_cache
+  SET tag_ids = v_new_tag_ids
+  WHERE post_id = p_post_id;
+
+  -- Update tag post counts and search cache,
+  -- unless the post is deleted or awaiting approval
+  IF FOUND THEN
+    UPDATE tag
+    SET post_count = post_count + 1
+    WHERE id = ANY(v_new_tag_ids - v_old_tag_ids);
+
+    UPDATE tag
+    SET post_count = post_count - 1
+    WHERE id = ANY(v_old_tag_ids - v_new_tag_ids);
+
+    -- Update search cache to reflect added post
+    UPDATE search_cache
+    SET post_count = post_count + 1,
+        first_post_id = (CASE WHEN p_post_id > first_post_id THEN p_post_id ELSE first_post_id END),
+        last_page_post_ids = (CASE WHEN p_post_id < (SELECT MAX(id) FROM unnest(last_page_post_ids) AS id)
+                              THEN last_page_post_ids | p_post_id
+                              ELSE last_page_post_ids
+                              END)
+    WHERE v_new_tag_ids @> tag_ids
+      AND NOT v_new_tag_ids && exclude_tag_ids
+      AND (p_new_post OR (NOT v_old_tag_ids @> tag_ids) OR v_old_tag_ids && exclude_tag_ids);
+
+    -- Update search cache to reflect removed post
+    UPDATE search_cache AS sc
+    SET post_count = post_count - 1,
+        first_post_id = (CASE WHEN p_post_id = first_post_id
+                         THEN (SELECT COALESCE(MAX(post_id), 0)
+                               FROM post_tag_id_cache AS ptic
+                               WHERE ptic.tag_ids @> sc.tag_ids
+                                 AND NOT ptic.tag_ids && sc.exclude_tag_ids)
+                         ELSE first_post_id
+                         END),
+        last_page_post_ids = (CASE WHEN (SELECT p_post_id BETWEEN MIN(id) AND MAX(id) FROM unnest(last_page_post_ids) AS id)
+                              THEN last_page_post_ids - p_post_id
+                              ELSE last_page_post_ids
+                              END)
+    WHERE NOT p_new_post
+      AND ((NOT v_new_tag_ids @> tag_ids) OR v_new_tag_ids && exclude_tag_ids)
+      AND v_old_tag_ids @> tag_ids
+      AND NOT v_old_tag_ids && exclude_tag_ids;
+  END IF;
+
+  -- Track tag changes
+  INSERT INTO post_tag_change (
+    post_id,
+    user_id,
+    tag_ids_added,
+    tag_ids_removed
+  ) VALUES (
+    p_post_id,
+    p_user_id,
+    v_add_tag_ids,
+    v_remove_tag_ids
+  );
+END;
+$BODY$;
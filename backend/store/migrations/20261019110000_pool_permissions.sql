@@ -0,0 +1,95 @@
+---- DROP OLD ----
+
+DROP FUNCTION update_pool;
+DROP FUNCTION set_pool_posts;
+
+---- FUNCTIONS ----
+
+CREATE FUNCTION update_pool(
+  IN p_update_pool update_pool,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  -- Only the creator of the pool or some sort of admin can edit it.
+  -- Omitted fields are kept, and an empty description clears it.
+  UPDATE pool
+  SET
+    name = COALESCE(p_update_pool.name, name),
+    description = NULLIF(COALESCE(p_update_pool.description, description), '')
+  WHERE id = p_update_pool.id
+    AND NOT is_deleted
+    AND (user_id = p_user_id OR (SELECT rank FROM users WHERE id = p_user_id) > 0);
+
+  RETURN FOUND;
+END;
+$BODY$;
+
+CREATE FUNCTION set_pool_posts(
+  IN p_pool_id integer,
+  IN p_post_ids integer[],
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_old_post_ids integer[];
+  v_new_post_ids integer[];
+  v_post_ids_added integer[];
+  v_post_ids_removed integer[];
+BEGIN
+  -- Lock the pool, so that concurrent changes are tracked correctly.
+  -- Only the creator of the pool or some sort of admin can change its posts.
+  PERFORM 1
+  FROM pool
+  WHERE id = p_pool_id
+    AND NOT is_deleted
+    AND (user_id = p_user_id OR (SELECT rank FROM users WHERE id = p_user_id) > 0)
+  FOR UPDATE;
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  SELECT COALESCE(array_agg(post_id), '{}') INTO v_old_post_ids
+  FROM pool_post
+  WHERE pool_id = p_pool_id;
+
+  -- Keep only the first occurrence of each existing post, in the specified order
+  SELECT COALESCE(array_agg(x.post_id ORDER BY x.position), '{}') INTO v_new_post_ids
+  FROM (
+    SELECT u.post_id, MIN(u.position) AS position
+    FROM unnest(p_post_ids) WITH ORDINALITY AS u(post_id, position)
+    JOIN post AS p ON p.id = u.post_id
+    WHERE NOT p.is_deleted
+      AND NOT p.is_pending
+    GROUP BY u.post_id
+  ) AS x;
+
+  v_post_ids_added := v_new_post_ids - v_old_post_ids;
+  v_post_ids_removed := v_old_post_ids - v_new_post_ids;
+
+  DELETE FROM pool_post
+  WHERE pool_id = p_pool_id;
+
+  INSERT INTO pool_post (pool_id, post_id, position)
+  SELECT p_pool_id, u.post_id, u.position
+  FROM unnest(v_new_post_ids) WITH ORDINALITY AS u(post_id, position);
+
+  -- Track membership changes
+  IF icount(v_post_ids_added) > 0 OR icount(v_post_ids_removed) > 0 THEN
+    INSERT INTO pool_post_change (pool_id, user_id, post_ids_added, post_ids_removed)
+    VALUES (p_pool_id, p_user_id, v_post_ids_added, v_post_ids_removed);
+  END IF;
+
+  UPDATE pool
+  SET updated_at = CURRENT_TIMESTAMP
+  WHERE id = p_pool_id;
+
+  RETURN true;
+END;
+$BODY$;
@@ -0,0 +1,95 @@
+---- DROP OLD ----
+
+DROP FUNCTION update_post;
+DROP TYPE update_post;
+
+---- TYPES ----
+
+-- Keep the parent when it is omitted, unless it is explicitly removed
+CREATE TYPE update_post AS (
+  id integer,
+
+  title text,
+  description text,
+  source text,
+  add_tags text[],
+  remove_tags text[],
+  rating text,
+  parent_id integer,
+  remove_parent boolean
+);
+
+---- FUNCTIONS ----
+
+CREATE FUNCTION update_post(
+  IN p_update_post update_post,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_can_edit boolean;
+  v_old post;
+  v_new post;
+BEGIN
+  -- Check if user is allowed to edit the post
+  v_can_edit := can_user_edit_post(p_update_post.id, p_user_id);
+
+  -- If user is allowed to edit, update post
+  IF v_can_edit THEN
+    SELECT * INTO v_old FROM post WHERE id = p_update_post.id FOR UPDATE;
+
+    UPDATE post
+    SET
+      title = p_update_post.title,
+      description = p_update_post.description,
+      source = p_update_post.source,
+      rating = COALESCE(p_update_post.rating, rating),
+      parent_id = CASE
+        WHEN p_update_post.remove_parent THEN NULL
+        ELSE COALESCE(p_update_post.parent_id, parent_id)
+      END
+    WHERE id = p_update_post.id
+    RETURNING * INTO v_new;
+
+    -- Track metadata changes
+    IF (v_old.title, v_old.description, v_old.source, v_old.rating, v_old.parent_id)
+       IS DISTINCT FROM (v_new.title, v_new.description, v_new.source, v_new.rating, v_new.parent_id) THEN
+      INSERT INTO post_metadata_change (
+        post_id,
+        user_id,
+        old_title,
+        new_title,
+        old_description,
+        new_description,
+        old_source,
+        new_source,
+        old_rating,
+        new_rating,
+        old_parent_id,
+        new_parent_id
+      ) VALUES (
+        p_update_post.id,
+        p_user_id,
+        v_old.title,
+        v_new.title,
+        v_old.description,
+        v_new.description,
+        v_old.source,
+        v_new.source,
+        v_old.rating,
+        v_new.rating,
+        v_old.parent_id,
+        v_new.parent_id
+      );
+    END IF;
+  END IF;
+
+  -- Update post tags
+  PERFORM update_post_tags(p_update_post.id, p_update_post.add_tags, p_update_post.remove_tags, p_user_id, false);
+
+  RETURN true;
+END;
+$BODY$;
@@ -0,0 +1,41 @@
+---- DROP OLD ----
+
+DROP FUNCTION update_post_note;
+
+---- FUNCTIONS ----
+
+CREATE FUNCTION update_post_note(
+  IN p_update_note update_post_note,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  -- Only the creator of the note or some sort of admin can edit it
+  UPDATE post_note
+  SET
+    x = p_update_note.x,
+    y = p_update_note.y,
+    width = p_update_note.width,
+    height = p_update_note.height,
+    body = p_update_note.body,
+    version = version + 1
+  WHERE id = p_update_note.id
+    AND NOT is_deleted
+    AND (user_id = p_user_id OR (SELECT rank FROM users WHERE id = p_user_id) > 0);
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Track new version
+  INSERT INTO post_note_version (note_id, post_id, user_id, version, x, y, width, height, body, is_deleted)
+    SELECT id, post_id, p_user_id, version, x, y, width, height, body, is_deleted
+    FROM post_note
+    WHERE id = p_update_note.id;
+
+  RETURN true;
+END;
+$BODY$;
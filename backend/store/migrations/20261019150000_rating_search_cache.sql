@@ -0,0 +1,680 @@
+---- DROP OLD ----
+
+DROP FUNCTION get_view_posts;
+DROP FUNCTION calculate_pages;
+DROP FUNCTION calculate_pages_reverse;
+DROP FUNCTION calculate_last_page;
+DROP FUNCTION resolve_search_tags;
+DROP FUNCTION add_post_to_search;
+DROP FUNCTION update_post_tags;
+DROP FUNCTION update_tag;
+DROP FUNCTION update_post;
+
+---- FUNCTIONS ----
+
+-- Pseudo tag ID of a content rating, stored in post_tag_id_cache alongside the post's tag IDs
+-- so that rating filters can use the cached tag searches.
+-- Negative, so that it can never collide with an actual tag ID.
+CREATE FUNCTION rating_tag_id(
+  IN p_rating text
+)
+RETURNS integer
+LANGUAGE sql
+
+AS $BODY$
+  SELECT -array_position(ARRAY['general', 'sensitive', 'questionable', 'explicit'], p_rating);
+$BODY$ IMMUTABLE;
+
+CREATE FUNCTION resolve_search_tags(
+  IN p_include_tags text[],
+  IN p_exclude_tags text[],
+  IN p_exclude_ratings text[],
+  OUT p_include_tag_ids integer[],
+  OUT p_exclude_tag_ids integer[],
+  OUT p_valid boolean
+)
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  p_include_tag_ids := get_tag_ids(p_include_tags);
+  p_exclude_tag_ids := get_tag_ids(p_exclude_tags);
+
+  p_valid := NOT (icount(p_include_tag_ids) < cardinality(p_include_tags) OR p_include_tag_ids && p_exclude_tag_ids);
+
+  p_include_tag_ids := compute_search_tag_ids(p_include_tag_ids);
+  p_exclude_tag_ids := compute_search_tag_ids(p_exclude_tag_ids);
+
+  -- Excluded ratings are excluded as their pseudo tag IDs
+  p_exclude_tag_ids := p_exclude_tag_ids | array(SELECT rating_tag_id(r) FROM unnest(p_exclude_ratings) AS r);
+END;
+$BODY$ STABLE;
+
+CREATE FUNCTION get_view_posts(
+  IN p_include_tags text[],
+  IN p_exclude_tags text[],
+  IN p_exclude_ratings text[],
+  IN p_start_id integer,
+  IN p_limit integer
+)
+RETURNS SETOF view_post
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_tag_ids integer[];
+  v_exclude_tag_ids integer[];
+  v_valid boolean;
+BEGIN
+  SELECT * INTO v_tag_ids, v_exclude_tag_ids, v_valid FROM resolve_search_tags(p_include_tags, p_exclude_tags, p_exclude_ratings);
+  IF NOT v_valid THEN
+    RETURN;
+  END IF;
+
+  RETURN QUERY
+  SELECT p.*
+  FROM post_tag_id_cache AS ptic
+  JOIN view_post AS p ON p.id = ptic.post_id
+  WHERE
+    -- Only scan forward from the origin
+    ptic.post_id <= p_start_id
+    -- Posts with fewer tags than the required tags cannot qualify
+    AND icount(ptic.tag_ids) >= icount(v_tag_ids)
+    -- Post must have all the included tags
+    AND ptic.tag_ids @> v_tag_ids
+    -- Post must not have any of the excluded tags
+    AND NOT ptic.tag_ids && v_exclude_tag_ids
+  ORDER BY ptic.post_id DESC
+  LIMIT p_limit;
+END;
+$BODY$ STABLE;
+
+-- Calculate the starting IDs of a range of pages,
+-- optionally starting from an already known page.
+CREATE FUNCTION calculate_pages(
+  IN p_include_tags text[],
+  IN p_exclude_tags text[],
+  IN p_exclude_ratings text[],
+  IN p_posts_per_page integer,
+  IN p_page_count integer,
+  IN p_origin_page page_info
+)
+RETURNS page_info[]
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_pages page_info[];
+  v_tag_ids integer[];
+  v_exclude_tag_ids integer[];
+  v_valid boolean;
+  v_post_count integer;
+  v_start_id integer;
+  v_last_id integer;
+BEGIN
+  SELECT * INTO v_tag_ids, v_exclude_tag_ids, v_valid FROM resolve_search_tags(p_include_tags, p_exclude_tags, p_exclude_ratings);
+  IF NOT v_valid THEN
+    RETURN v_pages;
+  END IF;
+
+  -- Make sure search cache is initialized
+  PERFORM initialize_search_cache(v_tag_ids, v_exclude_tag_ids);
+
+  SELECT post_count, first_post_id, last_page_post_ids[1]
+  INTO v_post_count, v_start_id, v_last_id
+  FROM search_cache
+  WHERE tag_ids = v_tag_ids
+    AND exclude_tag_ids = v_exclude_tag_ids;
+
+  -- If no posts exist in the search, return.
+  IF v_start_id IS NULL THEN
+    RETURN v_pages;
+  END IF;
+
+  IF p_origin_page.start_id IS NOT NULL THEN
+    v_start_id := p_origin_page.start_id;
+  END IF;
+
+  v_pages := array(
+    SELECT (no, start_id)::page_info
+    FROM (
+      SELECT
+        COALESCE(p_origin_page.no, 1) + ROW_NUMBER() OVER () - 1 AS no,
+        x.id AS start_id
+      FROM (
+        SELECT
+          ptic.post_id AS id,
+          ROW_NUMBER() OVER (ORDER BY ptic.post_id DESC) AS rn
+        FROM post_tag_id_cache AS ptic
+        WHERE
+          -- Only scan forward from start ID
+          ptic.post_id <= v_start_id
+          -- Post must have all the included tags
+          AND ptic.tag_ids @> v_tag_ids
+          -- Post must not have any of the excluded tags
+          AND NOT ptic.tag_ids && v_exclude_tag_ids
+        ORDER BY ptic.post_id DESC
+        LIMIT LEAST(p_page_count * p_posts_per_page, v_post_count) -- X pages at a time
+      ) AS x
+      WHERE MOD(x.rn - 1, p_posts_per_page) = 0
+    ) AS x
+    WHERE x.no > COALESCE(p_origin_page.no, 0)
+  );
+
+  RETURN v_pages;
+END;
+$BODY$;
+
+-- Like calculate_pages, but in reverse.
+-- (Calculates previous pages)
+CREATE FUNCTION calculate_pages_reverse(
+  IN p_include_tags text[],
+  IN p_exclude_tags text[],
+  IN p_exclude_ratings text[],
+  IN p_posts_per_page integer,
+  IN p_page_count integer,
+  IN p_origin_page page_info
+)
+RETURNS page_info[]
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_pages page_info[];
+  v_tag_ids integer[];
+  v_exclude_tag_ids integer[];
+  v_valid boolean;
+  v_start_id integer;
+  v_last_id integer;
+BEGIN
+  SELECT * INTO v_tag_ids, v_exclude_tag_ids, v_valid FROM resolve_search_tags(p_include_tags, p_exclude_tags, p_exclude_ratings);
+  IF NOT v_valid THEN
+    RETURN v_pages;
+  END IF;
+
+  -- Make sure search cache is initialized
+  PERFORM initialize_search_cache(v_tag_ids, v_exclude_tag_ids);
+
+  SELECT first_post_id, last_page_post_ids[1]
+  INTO v_start_id, v_last_id
+  FROM search_cache
+  WHERE tag_ids = v_tag_ids
+    AND exclude_tag_ids = v_exclude_tag_ids;
+
+  -- If no posts exist in the search, return.
+  IF v_start_id IS NULL THEN
+    RETURN v_pages;
+  END IF;
+
+  IF p_origin_page.start_id IS NOT NULL THEN
+    v_last_id := p_origin_page.start_id;
+  END IF;
+
+  v_pages := array(
+    SELECT (no, start_id)::page_info
+    FROM (
+      SELECT
+        COALESCE(p_origin_page.no, 0) - ROW_NUMBER() OVER () + 1 AS no,
+        x.id AS start_id
+      FROM (
+        SELECT
+          ptic.post_id AS id,
+          ROW_NUMBER() OVER (ORDER BY ptic.post_id ASC) AS rn
+        FROM post_tag_id_cache AS ptic
+        WHERE
+          -- Only scan backwards from the origin
+          ptic.post_id >= v_last_id
+          -- Post must have all the included tags
+          AND ptic.tag_ids @> v_tag_ids
+          -- Post must not have any of the excluded tags
+          AND NOT ptic.tag_ids && v_exclude_tag_ids
+        ORDER BY ptic.post_id ASC
+        LIMIT ((p_page_count + 1) * p_posts_per_page) -- X pages at a time
+      ) AS x
+      WHERE MOD(x.rn - 1, p_posts_per_page) = 0
+    ) AS x
+    WHERE x.no < p_origin_page.no
+  );
+
+  RETURN v_pages;
+END;
+$BODY$;
+
+CREATE FUNCTION calculate_last_page(
+  IN p_include_tags text[],
+  IN p_exclude_tags text[],
+  IN p_exclude_ratings text[],
+  IN p_posts_per_page integer
+)
+RETURNS page_info
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_tag_ids integer[];
+  v_exclude_tag_ids integer[];
+  v_valid boolean;
+  v_post_count integer;
+  v_page_count integer;
+  v_last_page_start_id integer;
+  v_last_page_post_ids integer[];
+BEGIN
+  SELECT * INTO v_tag_ids, v_exclude_tag_ids, v_valid FROM resolve_search_tags(p_include_tags, p_exclude_tags, p_exclude_ratings);
+  IF NOT v_valid THEN
+    RETURN (1, 0)::page_info;
+  END IF;
+
+  -- Make sure search cache is initialized
+  PERFORM initialize_search_cache(v_tag_ids, v_exclude_tag_ids);
+
+  -- Try to get cached search info
+  SELECT post_count, last_page_post_ids
+  INTO v_post_count, v_last_page_post_ids
+  FROM search_cache
+  WHERE tag_ids = v_tag_ids
+    AND exclude_tag_ids = v_exclude_tag_ids;
+
+  -- If no posts exist in the search, return.
+  IF v_post_count IS NULL THEN
+    RETURN (1, 0)::page_info;
+  END IF;
+
+  -- Calculate page count
+  v_page_count := CEIL(v_post_count::real / p_posts_per_page);
+
+  -- Calculate number of posts currently on last page
+  v_post_count := MOD(v_post_count, p_posts_per_page);
+
+  -- If last page is exactly full (p_posts_per_page number of posts),
+  -- the modulus will return 0, which is not what we want and will result
+  -- in broken behavior. In this case, set it to p_posts_per_page instead.
+  IF v_post_count = 0 THEN
+    v_post_count := p_posts_per_page;
+  END IF;
+
+  -- If necessary, get additional last page posts
+  IF icount(v_last_page_post_ids) < v_post_count THEN
+    v_last_page_post_ids := v_last_page_post_ids | array(
+      SELECT ptic.post_id
+      FROM post_tag_id_cache AS ptic
+      WHERE
+        ptic.post_id > (SELECT COALESCE(MAX(id), 0) FROM unnest(v_last_page_post_ids) AS id)
+        -- Posts with fewer tags than the required tags cannot qualify
+        AND icount(ptic.tag_ids) >= icount(v_tag_ids)
+        -- Post must have all the included tags
+        AND ptic.tag_ids @> v_tag_ids
+        -- Post must not have any of the excluded tags
+        AND NOT ptic.tag_ids && v_exclude_tag_ids
+      ORDER BY ptic.post_id ASC
+      LIMIT p_posts_per_page - icount(v_last_page_post_ids)
+    );
+
+    -- Update search cache with posts
+    UPDATE search_cache
+    SET last_page_post_ids = v_last_page_post_ids
+    WHERE tag_ids = v_tag_ids
+      AND exclude_tag_ids = v_exclude_tag_ids;
+  END IF;
+
+  -- Get last page start ID
+  v_last_page_start_id := v_last_page_post_ids[v_post_count];
+
+  RETURN (v_page_count, v_last_page_start_id)::page_info;
+END;
+$BODY$;
+
+-- Show a post hidden by remove_post_from_search in searches and tag post counts again
+CREATE FUNCTION add_post_to_search(
+  IN p_post_id integer
+)
+RETURNS void
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_tag_ids integer[];
+BEGIN
+  v_tag_ids := compute_post_tag_ids(array(SELECT tag_id FROM post_tag AS pt WHERE pt.post_id = p_post_id ORDER BY tag_id ASC))
+    | (SELECT rating_tag_id(rating) FROM post WHERE id = p_post_id);
+
+  -- Restore post_tag_id_cache so that the post
+  -- will be scanned for tag matches again
+  INSERT INTO post_tag_id_cache (post_id, tag_ids)
+  VALUES (p_post_id, v_tag_ids);
+
+  -- Update tag post counts to reflect restored post
+  UPDATE tag
+  SET post_count = post_count + 1
+  WHERE id = ANY(v_tag_ids);
+
+  -- Update search cache to reflect restored post
+  UPDATE search_cache
+  SET post_count = post_count + 1,
+      first_post_id = (CASE WHEN p_post_id > first_post_id THEN p_post_id ELSE first_post_id END),
+      last_page_post_ids = (CASE WHEN p_post_id < (SELECT MAX(id) FROM unnest(last_page_post_ids) AS id)
+                            THEN last_page_post_ids | p_post_id
+                            ELSE last_page_post_ids
+                            END)
+  WHERE v_tag_ids @> tag_ids
+    AND NOT v_tag_ids && exclude_tag_ids;
+END;
+$BODY$;
+
+CREATE FUNCTION update_post_tags(
+  IN p_post_id integer,
+  IN p_add_tags text[],
+  IN p_remove_tags text[],
+  IN p_user_id integer,
+  IN p_new_post boolean
+)
+RETURNS VOID
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_add_tag_ids integer[];
+  v_remove_tag_ids integer[];
+  v_old_tag_ids integer[];
+  v_new_tag_ids integer[];
+  v_rating_tag_id integer;
+BEGIN
+  -- Create missing tags
+  PERFORM create_missing_tags(p_add_tags);
+
+  v_add_tag_ids := get_tag_ids(p_add_tags);
+  v_remove_tag_ids := get_tag_ids(p_remove_tags);
+
+  -- Retrieve old tags
+  v_old_tag_ids := array(SELECT tag_id FROM post_tag AS pt WHERE pt.post_id = p_post_id ORDER BY tag_id ASC);
+
+  -- Compute new tags
+  v_new_tag_ids := (v_old_tag_ids | v_add_tag_ids) - v_remove_tag_ids;
+
+  -- Add links for added tags to post
+  INSERT INTO post_tag (post_id, tag_id)
+    SELECT p_post_id, tag_id
+    FROM unnest(v_add_tag_ids) AS tag_id
+    ON CONFLICT(post_id, tag_id)
+    DO NOTHING;
+
+  -- Remove removed tag links for post
+  DELETE FROM post_tag AS pt
+  USING unnest(v_remove_tag_ids) AS rtid
+  WHERE pt.post_id = p_post_id AND pt.tag_id = rtid;
+
+  -- Update post tags
+  UPDATE post
+  SET tags = array(SELECT tag
+                   FROM tag
+                   WHERE id = ANY(v_new_tag_ids)
+                   ORDER BY tag ASC)
+  WHERE id = p_post_id;
+
+  -- Include the rating pseudo tag ID, so that rating filters can use the search cache
+  SELECT rating_tag_id(rating) INTO v_rating_tag_id FROM post WHERE id = p_post_id;
+
+  v_old_tag_ids := compute_post_tag_ids(v_old_tag_ids) | v_rating_tag_id;
+  v_new_tag_ids := compute_post_tag_ids(v_new_tag_ids) | v_rating_tag_id;
+
+  -- Update post_tag_id_cache
+  UPDATE post_tag_id_cache
+  SET tag_ids = v_new_tag_ids
+  WHERE post_id = p_post_id;
+
+  -- Update tag post counts and search cache,
+  -- unless the post is deleted or awaiting approval
+  IF FOUND THEN
+    UPDATE tag
+    SET post_count = post_count + 1
+    WHERE id = ANY(v_new_tag_ids - v_old_tag_ids);
+
+    UPDATE tag
+    SET post_count = post_count - 1
+    WHERE id = ANY(v_old_tag_ids - v_new_tag_ids);
+
+    -- Update search cache to reflect added post
+    UPDATE search_cache
+    SET post_count = post_count + 1,
+        first_post_id = (CASE WHEN p_post_id > first_post_id THEN p_post_id ELSE first_post_id END),
+        last_page_post_ids = (CASE WHEN p_post_id < (SELECT MAX(id) FROM unnest(last_page_post_ids) AS id)
+                              THEN last_page_post_ids | p_post_id
+                              ELSE last_page_post_ids
+                              END)
+    WHERE v_new_tag_ids @> tag_ids
+      AND NOT v_new_tag_ids && exclude_tag_ids
+      AND (p_new_post OR (NOT v_old_tag_ids @> tag_ids) OR v_old_tag_ids && exclude_tag_ids);
+
+    -- Update search cache to reflect removed post
+    UPDATE search_cache AS sc
+    SET post_count = post_count - 1,
+        first_post_id = (CASE WHEN p_post_id = first_post_id
+                         THEN (SELECT COALESCE(MAX(post_id), 0)
+                               FROM post_tag_id_cache AS ptic
+                               WHERE ptic.tag_ids @> sc.tag_ids
+                                 AND NOT ptic.tag_ids && sc.exclude_tag_ids)
+                         ELSE first_post_id
+                         END),
+        last_page_post_ids = (CASE WHEN (SELECT p_post_id BETWEEN MIN(id) AND MAX(id) FROM unnest(last_page_post_ids) AS id)
+                              THEN last_page_post_ids - p_post_id
+                              ELSE last_page_post_ids
+                              END)
+    WHERE NOT p_new_post
+      AND ((NOT v_new_tag_ids @> tag_ids) OR v_new_tag_ids && exclude_tag_ids)
+      AND v_old_tag_ids @> tag_ids
+      AND NOT v_old_tag_ids && exclude_tag_ids;
+  END IF;
+
+  -- Track tag changes
+  INSERT INTO post_tag_change (
+    post_id,
+    user_id,
+    tag_ids_added,
+    tag_ids_removed
+  ) VALUES (
+    p_post_id,
+    p_user_id,
+    v_add_tag_ids,
+    v_remove_tag_ids
+  );
+END;
+$BODY$;
+
+CREATE FUNCTION update_tag(
+  IN p_tag_id integer,
+  IN p_update_tag update_tag,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_add_alias_ids integer[];
+  v_remove_alias_ids integer[];
+  v_old_alias_ids integer[];
+  v_add_implied_tag_ids integer[];
+  v_remove_implied_tag_ids integer[];
+  v_old_implied_tag_ids integer[];
+  v_new_implied_tag_ids integer[];
+  v_affected_tag_ids integer[];
+BEGIN
+  IF NOT can_user_edit_tag(p_tag_id, p_user_id) THEN
+    RETURN false;
+  END IF;
+
+  v_add_implied_tag_ids := get_tag_ids(p_update_tag.add_implied_tags);
+  v_remove_implied_tag_ids := get_tag_ids(p_update_tag.remove_implied_tags);
+
+  -- Retrieve implied tag ids
+  SELECT implied_tag_ids
+  INTO v_old_implied_tag_ids
+  FROM tag
+  WHERE id = p_tag_id;
+
+  -- Compute new implied tag ids
+  v_new_implied_tag_ids := (v_old_implied_tag_ids | v_add_implied_tag_ids) - v_remove_implied_tag_ids;
+
+  -- Update tag
+  UPDATE tag
+  SET implied_tag_ids = v_new_implied_tag_ids
+  WHERE id = p_tag_id;
+
+  -- Get ids of removed aliases
+  v_remove_alias_ids := get_tag_ids(p_update_tag.remove_aliases);
+
+  IF cardinality(p_update_tag.add_aliases) > 0 THEN
+    -- Create missing tags for added aliases
+    PERFORM create_missing_tags(p_update_tag.add_aliases);
+
+    -- Retrieve old alias ids
+    SELECT COALESCE(array_agg(id), '{}')
+    INTO v_old_alias_ids
+    FROM tag
+    WHERE alias_of_tag_id = p_tag_id;
+
+    -- Get ids of added aliases
+    v_add_alias_ids := get_tag_ids(p_update_tag.add_aliases) - v_old_alias_ids - v_remove_alias_ids;
+
+    -- Set alias_of_tag_id for added aliases
+    UPDATE tag
+    SET alias_of_tag_id = p_tag_id
+    WHERE id = ANY(v_add_alias_ids);
+
+    -- Set any aliases of added aliases to be aliases of this tag
+    UPDATE tag
+    SET alias_of_tag_id = p_tag_id
+    WHERE alias_of_tag_id = ANY(v_add_alias_ids);
+  END IF;
+
+  IF icount(v_remove_alias_ids) > 0 THEN
+    -- Get actual alias ids that will be removed
+    SELECT array_agg(id)
+    INTO v_remove_alias_ids
+    FROM tag
+    WHERE alias_of_tag_id = p_tag_id AND id = ANY(v_remove_alias_ids);
+
+    -- Clear alias_of_tag_id of removed aliases
+    UPDATE tag
+    SET alias_of_tag_id = NULL
+    WHERE id = ANY(v_remove_alias_ids);
+  END IF;
+
+  v_affected_tag_ids := v_add_alias_ids || v_remove_alias_ids;
+
+  IF v_new_implied_tag_ids <> v_old_implied_tag_ids THEN
+    v_affected_tag_ids := v_affected_tag_ids + p_tag_id | v_old_implied_tag_ids | v_new_implied_tag_ids;
+  END IF;
+
+  IF icount(v_affected_tag_ids) > 0 THEN
+    v_affected_tag_ids := v_affected_tag_ids | compute_post_tag_ids(v_affected_tag_ids);
+
+    -- Update pre-calculated post tag ID cache
+    UPDATE post_tag_id_cache AS ptic
+    SET tag_ids = compute_post_tag_ids(array(SELECT tag_id FROM post_tag AS pt WHERE pt.post_id = ptic.post_id))
+                  | (SELECT rating_tag_id(rating) FROM post WHERE id = ptic.post_id)
+    WHERE tag_ids && v_affected_tag_ids;
+
+    -- Recalculate post counts of affected tags
+    UPDATE tag AS t
+    SET post_count = (SELECT COUNT(*)
+                      FROM post_tag_id_cache AS ptic
+                      WHERE ptic.tag_ids @> ARRAY[t.id])
+    WHERE id = ANY(v_affected_tag_ids);
+
+    -- Delete cached searches affected by alias change
+    DELETE FROM search_cache
+    WHERE tag_ids && v_affected_tag_ids
+       OR exclude_tag_ids && v_affected_tag_ids;
+  END IF;
+
+  RETURN true;
+END;
+$BODY$;
+
+CREATE FUNCTION update_post(
+  IN p_update_post update_post,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_can_edit boolean;
+  v_old post;
+  v_new post;
+BEGIN
+  -- Check if user is allowed to edit the post
+  v_can_edit := can_user_edit_post(p_update_post.id, p_user_id);
+
+  -- If user is allowed to edit, update post
+  IF v_can_edit THEN
+    SELECT * INTO v_old FROM post WHERE id = p_update_post.id FOR UPDATE;
+
+    UPDATE post
+    SET
+      title = p_update_post.title,
+      description = p_update_post.description,
+      source = p_update_post.source,
+      rating = COALESCE(p_update_post.rating, rating),
+      parent_id = CASE
+        WHEN p_update_post.remove_parent THEN NULL
+        ELSE COALESCE(p_update_post.parent_id, parent_id)
+      END
+    WHERE id = p_update_post.id
+    RETURNING * INTO v_new;
+
+    -- Re-add the post to the search with its new rating pseudo tag ID,
+    -- unless the post is deleted or awaiting approval
+    IF v_old.rating <> v_new.rating AND EXISTS (SELECT 1 FROM post_tag_id_cache WHERE post_id = v_new.id) THEN
+      PERFORM remove_post_from_search(v_new.id);
+      PERFORM add_post_to_search(v_new.id);
+    END IF;
+
+    -- Track metadata changes
+    IF (v_old.title, v_old.description, v_old.source, v_old.rating, v_old.parent_id)
+       IS DISTINCT FROM (v_new.title, v_new.description, v_new.source, v_new.rating, v_new.parent_id) THEN
+      INSERT INTO post_metadata_change (
+        post_id,
+        user_id,
+        old_title,
+        new_title,
+        old_description,
+        new_description,
+        old_source,
+        new_source,
+        old_rating,
+        new_rating,
+        old_parent_id,
+        new_parent_id
+      ) VALUES (
+        p_update_post.id,
+        p_user_id,
+        v_old.title,
+        v_new.title,
+        v_old.description,
+        v_new.description,
+        v_old.source,
+        v_new.source,
+        v_old.rating,
+        v_new.rating,
+        v_old.parent_id,
+        v_new.parent_id
+      );
+    END IF;
+  END IF;
+
+  -- Update post tags
+  PERFORM update_post_tags(p_update_post.id, p_update_post.add_tags, p_update_post.remove_tags, p_user_id, false);
+
+  RETURN true;
+END;
+$BODY$;
+
+---- MIGRATE ----
+
+-- Add rating pseudo tag IDs to post_tag_id_cache records of existing posts
+UPDATE post_tag_id_cache AS ptic
+SET tag_ids = ptic.tag_ids | rating_tag_id(p.rating)
+FROM post AS p
+WHERE p.id = ptic.post_id;
@@ -0,0 +1,10 @@
+---- TABLES ----
+
+-- Votes should not count as updates of the post,
+-- so skip managing updated_at when the cached vote score changes
+DROP TRIGGER set_updated_at ON post;
+
+CREATE TRIGGER set_updated_at BEFORE UPDATE ON post
+  FOR EACH ROW
+  WHEN (OLD.score IS NOT DISTINCT FROM NEW.score)
+  EXECUTE FUNCTION set_updated_at();
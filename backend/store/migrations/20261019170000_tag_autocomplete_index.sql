@@ -0,0 +1,8 @@
+-- Enable pg_trgm extension, so that tags can be matched by contained text using an index
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+---- INDEXES ----
+
+CREATE INDEX tag_tag_trgm_idx ON tag
+  USING gin
+  (tag gin_trgm_ops);
@@ -0,0 +1,36 @@
+-- Show a post hidden by remove_post_from_search in searches and tag post counts again
+CREATE FUNCTION add_post_to_search(
+  IN p_post_id integer
+)
+RETURNS void
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_tag_ids integer[];
+BEGIN
+  v_tag_ids := compute_post_tag_ids(array(SELECT tag_id FROM post_tag AS pt WHERE pt.post_id = p_post_id ORDER BY tag_id ASC))
+    | (SELECT rating_tag_id(rating) FROM post WHERE id = p_post_id);
+
+  -- Restore post_tag_id_cache so that the post
+  -- will be scanned for tag matches again
+  INSERT INTO post_tag_id_cache (post_id, tag_ids)
+  VALUES (p_post_id, v_tag_ids);
+
+  -- Update tag post counts to reflect restored post
+  UPDATE tag
+  SET post_count = post_count + 1
+  WHERE id = ANY(v_tag_ids);
+
+  -- Update search cache to reflect restored post
+  UPDATE search_cache
+  SET post_count = post_count + 1,
+      first_post_id = (CASE WHEN p_post_id > first_post_id THEN p_post_id ELSE first_post_id END),
+      last_page_post_ids = (CASE WHEN p_post_id < (SELECT MAX(id) FROM unnest(last_page_post_ids) AS id)
+                            THEN last_page_post_ids | p_post_id
+                            ELSE last_page_post_ids
+                            END)
+  WHERE v_tag_ids @> tag_ids
+    AND NOT v_tag_ids && exclude_tag_ids;
+END;
+$BODY$;
@@ -0,0 +1,24 @@
+CREATE FUNCTION approve_post(
+  IN p_post_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  UPDATE post
+  SET is_pending = false
+  WHERE id = p_post_id
+    AND is_pending
+    AND NOT is_deleted;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Make the post public
+  PERFORM add_post_to_search(p_post_id);
+
+  RETURN true;
+END;
+$BODY$;
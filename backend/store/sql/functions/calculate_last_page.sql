@@ -1,6 +1,7 @@
 CREATE FUNCTION calculate_last_page(
   IN p_include_tags text[],
   IN p_exclude_tags text[],
+  IN p_exclude_ratings text[],
   IN p_posts_per_page integer
 )
 RETURNS page_info
@@ -16,7 +17,7 @@ DECLARE
   v_last_page_start_id integer;
   v_last_page_post_ids integer[];
 BEGIN
-  SELECT * INTO v_tag_ids, v_exclude_tag_ids, v_valid FROM resolve_search_tags(p_include_tags, p_exclude_tags);
+  SELECT * INTO v_tag_ids, v_exclude_tag_ids, v_valid FROM resolve_search_tags(p_include_tags, p_exclude_tags, p_exclude_ratings);
   IF NOT v_valid THEN
     RETURN (1, 0)::page_info;
   END IF;
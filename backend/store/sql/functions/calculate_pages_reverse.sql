@@ -3,6 +3,7 @@
 CREATE FUNCTION calculate_pages_reverse(
   IN p_include_tags text[],
   IN p_exclude_tags text[],
+  IN p_exclude_ratings text[],
   IN p_posts_per_page integer,
   IN p_page_count integer,
   IN p_origin_page page_info
@@ -19,7 +20,7 @@ DECLARE
   v_start_id integer;
   v_last_id integer;
 BEGIN
-  SELECT * INTO v_tag_ids, v_exclude_tag_ids, v_valid FROM resolve_search_tags(p_include_tags, p_exclude_tags);
+  SELECT * INTO v_tag_ids, v_exclude_tag_ids, v_valid FROM resolve_search_tags(p_include_tags, p_exclude_tags, p_exclude_ratings);
   IF NOT v_valid THEN
     RETURN v_pages;
   END IF;
@@ -0,0 +1,22 @@
+CREATE FUNCTION create_pool(
+  IN p_pool new_pool,
+  IN p_user_id integer
+)
+RETURNS integer
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_pool_id integer;
+BEGIN
+  -- Insert pool
+  INSERT INTO pool (user_id, name, description)
+  VALUES (p_user_id, p_pool.name, p_pool.description)
+  RETURNING id INTO v_pool_id;
+
+  -- Insert pool posts
+  PERFORM set_pool_posts(v_pool_id, COALESCE(p_pool.post_ids, '{}'), p_user_id);
+
+  RETURN v_pool_id;
+END;
+$BODY$;
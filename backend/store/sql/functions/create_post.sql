@@ -1,6 +1,7 @@
 CREATE FUNCTION create_post(
   IN p_post new_post,
-  IN p_tags text[]
+  IN p_tags text[],
+  IN p_phashes bigint[]
 )
 RETURNS integer
 LANGUAGE plpgsql
@@ -21,7 +22,10 @@ BEGIN
     height,
     hash,
     ext,
-    tn_ext
+    tn_ext,
+    renditions,
+    rating,
+    is_pending
   )
   SELECT
     p_post.user_id, -- user_id
@@ -34,15 +38,28 @@ BEGIN
     p_post.height, -- height
     p_post.hash, -- hash
     p_post.ext, -- ext
-    p_post.tn_ext -- tn_ext
+    p_post.tn_ext, -- tn_ext
+    COALESCE(p_post.renditions, '{}'), -- renditions
+    COALESCE(p_post.rating, 'general'), -- rating
+    COALESCE(p_post.is_pending, false) -- is_pending
   RETURNING id INTO v_post_id;
 
   -- Create post_tag_id_cache
   INSERT INTO post_tag_id_cache (post_id) VALUES (v_post_id);
 
+  -- Store perceptual hashes
+  INSERT INTO post_phash (post_id, phash)
+    SELECT DISTINCT v_post_id, phash
+    FROM unnest(p_phashes) AS phash;
+
   -- Add post tags
   PERFORM update_post_tags(v_post_id, p_tags, '{}', p_post.user_id, true);
 
+  -- Hide the post until it is approved
+  IF p_post.is_pending THEN
+    PERFORM remove_post_from_search(v_post_id);
+  END IF;
+
   RETURN v_post_id;
 END;
 $BODY$;
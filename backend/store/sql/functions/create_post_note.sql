@@ -0,0 +1,30 @@
+CREATE FUNCTION create_post_note(
+  IN p_note new_post_note,
+  IN p_user_id integer
+)
+RETURNS integer
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_note_id integer;
+BEGIN
+  -- Notes can only be added to posts that are not deleted or awaiting approval
+  IF NOT EXISTS (SELECT 1 FROM post WHERE id = p_note.post_id AND NOT is_deleted AND NOT is_pending) THEN
+    RETURN NULL;
+  END IF;
+
+  -- Insert note
+  INSERT INTO post_note (post_id, user_id, x, y, width, height, body)
+  VALUES (p_note.post_id, p_user_id, p_note.x, p_note.y, p_note.width, p_note.height, p_note.body)
+  RETURNING id INTO v_note_id;
+
+  -- Track first version
+  INSERT INTO post_note_version (note_id, post_id, user_id, version, x, y, width, height, body, is_deleted)
+    SELECT id, post_id, p_user_id, version, x, y, width, height, body, is_deleted
+    FROM post_note
+    WHERE id = v_note_id;
+
+  RETURN v_note_id;
+END;
+$BODY$;
@@ -0,0 +1,30 @@
+CREATE FUNCTION create_report(
+  IN p_report new_report,
+  IN p_user_id integer
+)
+RETURNS integer
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_report_id integer;
+BEGIN
+  -- The reported item must exist
+  IF NOT (CASE p_report.target_type
+          WHEN 'post' THEN EXISTS (SELECT 1 FROM post WHERE id = p_report.target_id AND NOT is_deleted AND NOT is_pending)
+          WHEN 'comment' THEN EXISTS (SELECT 1 FROM post_comment WHERE id = p_report.target_id AND NOT is_deleted)
+          WHEN 'wiki' THEN EXISTS (SELECT 1 FROM wiki_pages WHERE id = p_report.target_id AND NOT deleted)
+          WHEN 'user' THEN EXISTS (SELECT 1 FROM users WHERE id = p_report.target_id)
+          ELSE false
+          END) THEN
+    RETURN NULL;
+  END IF;
+
+  -- Insert report
+  INSERT INTO report (user_id, target_type, target_id, reason)
+  VALUES (p_user_id, p_report.target_type, p_report.target_id, p_report.reason)
+  RETURNING id INTO v_report_id;
+
+  RETURN v_report_id;
+END;
+$BODY$;
@@ -0,0 +1,32 @@
+CREATE FUNCTION create_tag_edit_job(
+  IN p_job new_tag_edit_job,
+  IN p_post_ids integer[],
+  IN p_user_id integer
+)
+RETURNS integer
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_job_id integer;
+BEGIN
+  INSERT INTO tag_edit_job (
+    user_id,
+    include_tags,
+    exclude_tags,
+    add_tags,
+    remove_tags,
+    post_ids
+  ) VALUES (
+    p_user_id,
+    COALESCE(p_job.include_tags, '{}'),
+    COALESCE(p_job.exclude_tags, '{}'),
+    COALESCE(p_job.add_tags, '{}'),
+    COALESCE(p_job.remove_tags, '{}'),
+    p_post_ids
+  )
+  RETURNING id INTO v_job_id;
+
+  RETURN v_job_id;
+END;
+$BODY$;
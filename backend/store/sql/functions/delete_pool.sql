@@ -0,0 +1,19 @@
+CREATE FUNCTION delete_pool(
+  IN p_pool_id integer,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  -- Only the creator of the pool or some sort of admin can delete it
+  UPDATE pool
+  SET is_deleted = true
+  WHERE id = p_pool_id
+    AND NOT is_deleted
+    AND (user_id = p_user_id OR (SELECT rank FROM users WHERE id = p_user_id) > 0);
+
+  RETURN FOUND;
+END;
+$BODY$;
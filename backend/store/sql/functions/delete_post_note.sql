@@ -0,0 +1,31 @@
+CREATE FUNCTION delete_post_note(
+  IN p_note_id integer,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  -- Only the creator of the note or some sort of admin can delete it
+  UPDATE post_note
+  SET
+    is_deleted = true,
+    version = version + 1
+  WHERE id = p_note_id
+    AND NOT is_deleted
+    AND (user_id = p_user_id OR (SELECT rank FROM users WHERE id = p_user_id) > 0);
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Track deletion as a new version
+  INSERT INTO post_note_version (note_id, post_id, user_id, version, x, y, width, height, body, is_deleted)
+    SELECT id, post_id, p_user_id, version, x, y, width, height, body, is_deleted
+    FROM post_note
+    WHERE id = p_note_id;
+
+  RETURN true;
+END;
+$BODY$;
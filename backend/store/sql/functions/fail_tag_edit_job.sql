@@ -0,0 +1,14 @@
+CREATE FUNCTION fail_tag_edit_job(
+  IN p_job_id integer
+)
+RETURNS VOID
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  UPDATE tag_edit_job
+  SET status = 'failed'
+  WHERE id = p_job_id
+    AND status = 'running';
+END;
+$BODY$;
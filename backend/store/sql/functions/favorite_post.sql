@@ -0,0 +1,28 @@
+CREATE FUNCTION favorite_post(
+  IN p_post_id integer,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  -- Deleted posts and posts awaiting approval cannot be favorited
+  IF NOT EXISTS (SELECT 1 FROM post WHERE id = p_post_id AND NOT is_deleted AND NOT is_pending) THEN
+    RETURN false;
+  END IF;
+
+  INSERT INTO post_favorite (post_id, user_id)
+  VALUES (p_post_id, p_user_id)
+  ON CONFLICT DO NOTHING;
+
+  -- Only count the favorite if it did not already exist
+  IF FOUND THEN
+    UPDATE post
+    SET fav_count = fav_count + 1
+    WHERE id = p_post_id;
+  END IF;
+
+  RETURN true;
+END;
+$BODY$;
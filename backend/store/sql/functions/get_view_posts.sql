@@ -1,6 +1,7 @@
 CREATE FUNCTION get_view_posts(
   IN p_include_tags text[],
   IN p_exclude_tags text[],
+  IN p_exclude_ratings text[],
   IN p_start_id integer,
   IN p_limit integer
 )
@@ -13,7 +14,7 @@ DECLARE
   v_exclude_tag_ids integer[];
   v_valid boolean;
 BEGIN
-  SELECT * INTO v_tag_ids, v_exclude_tag_ids, v_valid FROM resolve_search_tags(p_include_tags, p_exclude_tags);
+  SELECT * INTO v_tag_ids, v_exclude_tag_ids, v_valid FROM resolve_search_tags(p_include_tags, p_exclude_tags, p_exclude_ratings);
   IF NOT v_valid THEN
     RETURN;
   END IF;
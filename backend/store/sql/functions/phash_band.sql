@@ -0,0 +1,10 @@
+CREATE FUNCTION phash_band(
+  IN p_phash bigint,
+  IN p_band integer
+)
+RETURNS integer
+LANGUAGE sql
+
+AS $BODY$
+  SELECT ((p_phash >> (p_band * 16)) & 65535)::integer;
+$BODY$ IMMUTABLE;
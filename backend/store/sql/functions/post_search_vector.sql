@@ -0,0 +1,11 @@
+CREATE FUNCTION post_search_vector(
+  IN p_title text,
+  IN p_description text
+)
+RETURNS tsvector
+LANGUAGE sql
+
+AS $BODY$
+  SELECT setweight(to_tsvector('english', COALESCE(p_title, '')), 'A')
+      || setweight(to_tsvector('english', COALESCE(p_description, '')), 'B');
+$BODY$ IMMUTABLE;
@@ -0,0 +1,24 @@
+CREATE FUNCTION purge_post(
+  IN p_post_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  -- Only deleted posts can be purged
+  IF NOT EXISTS (SELECT 1 FROM post WHERE id = p_post_id AND is_deleted) THEN
+    RETURN false;
+  END IF;
+
+  -- Tag changes are not linked to the post by a foreign key
+  DELETE FROM post_tag_change
+  WHERE post_id = p_post_id;
+
+  -- Everything else linked to the post is removed by cascade
+  DELETE FROM post
+  WHERE id = p_post_id;
+
+  RETURN true;
+END;
+$BODY$;
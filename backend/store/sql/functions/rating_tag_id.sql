@@ -0,0 +1,12 @@
+-- Pseudo tag ID of a content rating, stored in post_tag_id_cache alongside the post's tag IDs
+-- so that rating filters can use the cached tag searches.
+-- Negative, so that it can never collide with an actual tag ID.
+CREATE FUNCTION rating_tag_id(
+  IN p_rating text
+)
+RETURNS integer
+LANGUAGE sql
+
+AS $BODY$
+  SELECT -array_position(ARRAY['general', 'sensitive', 'questionable', 'explicit'], p_rating);
+$BODY$ IMMUTABLE;
@@ -0,0 +1,21 @@
+CREATE FUNCTION reject_post(
+  IN p_post_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  -- Rejected posts are deleted, so that they can be purged later.
+  -- They are no longer pending, so undeleting one makes it public.
+  UPDATE post
+  SET is_pending = false,
+      is_deleted = true,
+      deleted_at = CURRENT_TIMESTAMP
+  WHERE id = p_post_id
+    AND is_pending
+    AND NOT is_deleted;
+
+  RETURN FOUND;
+END;
+$BODY$;
@@ -0,0 +1,91 @@
+CREATE FUNCTION replace_post_file(
+  IN p_post_id integer,
+  IN p_file post_file,
+  IN p_phashes bigint[],
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_post post;
+BEGIN
+  SELECT * INTO v_post
+  FROM post
+  WHERE id = p_post_id
+    AND NOT is_deleted
+  FOR UPDATE;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Track file replacement, keeping the old file details to allow reverting
+  INSERT INTO post_file_replacement (
+    post_id,
+    user_id,
+    old_filename,
+    new_filename,
+    old_size,
+    new_size,
+    old_width,
+    new_width,
+    old_height,
+    new_height,
+    old_hash,
+    new_hash,
+    old_ext,
+    new_ext,
+    old_tn_ext,
+    new_tn_ext,
+    old_renditions,
+    new_renditions,
+    old_phashes,
+    new_phashes
+  ) VALUES (
+    p_post_id,
+    p_user_id,
+    v_post.filename,
+    p_file.filename,
+    v_post.size,
+    p_file.size,
+    v_post.width,
+    p_file.width,
+    v_post.height,
+    p_file.height,
+    v_post.hash,
+    p_file.hash,
+    v_post.ext,
+    p_file.ext,
+    v_post.tn_ext,
+    p_file.tn_ext,
+    v_post.renditions,
+    p_file.renditions,
+    array(SELECT phash FROM post_phash WHERE post_id = p_post_id ORDER BY phash ASC),
+    p_phashes
+  );
+
+  -- Update post file
+  UPDATE post
+  SET filename = p_file.filename,
+      size = p_file.size,
+      width = p_file.width,
+      height = p_file.height,
+      hash = p_file.hash,
+      ext = p_file.ext,
+      tn_ext = p_file.tn_ext,
+      renditions = p_file.renditions
+  WHERE id = p_post_id;
+
+  -- Replace perceptual hashes
+  DELETE FROM post_phash
+  WHERE post_id = p_post_id;
+
+  INSERT INTO post_phash (post_id, phash)
+    SELECT DISTINCT p_post_id, phash
+    FROM unnest(p_phashes) AS phash;
+
+  RETURN true;
+END;
+$BODY$;
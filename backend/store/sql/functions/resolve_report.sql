@@ -0,0 +1,75 @@
+CREATE FUNCTION resolve_report(
+  IN p_resolve_report resolve_report,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_report report;
+  v_target_deleted boolean := false;
+BEGIN
+  -- Only moderators can resolve reports
+  IF NOT EXISTS (SELECT 1 FROM users WHERE id = p_user_id AND rank > 0) THEN
+    RETURN false;
+  END IF;
+
+  SELECT * INTO v_report
+  FROM report
+  WHERE id = p_resolve_report.id
+    AND status = 'open'
+  FOR UPDATE;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Delete the reported item along with resolving the report.
+  -- Items that are already deleted are not marked as deleted by the report.
+  IF p_resolve_report.delete_target THEN
+    CASE v_report.target_type
+    WHEN 'post' THEN
+      UPDATE post
+      SET is_deleted = true,
+          deleted_at = CURRENT_TIMESTAMP
+      WHERE id = v_report.target_id
+        AND NOT is_deleted;
+
+      v_target_deleted := FOUND;
+
+      -- Hide the post from searches and tag post counts
+      IF v_target_deleted THEN
+        PERFORM remove_post_from_search(v_report.target_id);
+      END IF;
+    WHEN 'comment' THEN
+      UPDATE comment
+      SET is_deleted = true
+      WHERE id = v_report.target_id
+        AND NOT is_deleted;
+
+      v_target_deleted := FOUND;
+    WHEN 'wiki' THEN
+      SELECT delete_wiki(title) INTO v_target_deleted
+      FROM wiki_pages
+      WHERE id = v_report.target_id
+        AND NOT deleted;
+
+      v_target_deleted := COALESCE(v_target_deleted, false);
+    ELSE
+      RAISE EXCEPTION 'Reported % can not be deleted through a report', v_report.target_type;
+    END CASE;
+  END IF;
+
+  -- Update report
+  UPDATE report
+  SET status = p_resolve_report.status,
+      resolver_id = p_user_id,
+      resolved_at = CURRENT_TIMESTAMP,
+      resolution_note = p_resolve_report.resolution_note,
+      target_deleted = v_target_deleted
+  WHERE id = v_report.id;
+
+  RETURN true;
+END;
+$BODY$;
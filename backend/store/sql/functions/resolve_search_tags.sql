@@ -1,6 +1,7 @@
 CREATE FUNCTION resolve_search_tags(
   IN p_include_tags text[],
   IN p_exclude_tags text[],
+  IN p_exclude_ratings text[],
   OUT p_include_tag_ids integer[],
   OUT p_exclude_tag_ids integer[],
   OUT p_valid boolean
@@ -16,5 +17,8 @@ BEGIN
 
   p_include_tag_ids := compute_search_tag_ids(p_include_tag_ids);
   p_exclude_tag_ids := compute_search_tag_ids(p_exclude_tag_ids);
+
+  -- Excluded ratings are excluded as their pseudo tag IDs
+  p_exclude_tag_ids := p_exclude_tag_ids | array(SELECT rating_tag_id(r) FROM unnest(p_exclude_ratings) AS r);
 END;
 $BODY$ STABLE;
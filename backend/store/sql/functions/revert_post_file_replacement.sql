@@ -0,0 +1,39 @@
+CREATE FUNCTION revert_post_file_replacement(
+  IN p_post_id integer,
+  IN p_replacement_id integer,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_replacement post_file_replacement;
+BEGIN
+  SELECT * INTO v_replacement
+  FROM post_file_replacement
+  WHERE id = p_replacement_id
+    AND post_id = p_post_id;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Restore the file from before the replacement, recording it as a new replacement
+  RETURN replace_post_file(
+    p_post_id,
+    ROW(
+      v_replacement.old_filename,
+      v_replacement.old_size,
+      v_replacement.old_width,
+      v_replacement.old_height,
+      v_replacement.old_hash,
+      v_replacement.old_ext,
+      v_replacement.old_tn_ext,
+      v_replacement.old_renditions
+    )::post_file,
+    v_replacement.old_phashes,
+    p_user_id
+  );
+END;
+$BODY$;
@@ -0,0 +1,42 @@
+CREATE FUNCTION revert_post_tag_change(
+  IN p_change_id integer,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_change post_tag_change;
+BEGIN
+  SELECT * INTO v_change
+  FROM post_tag_change
+  WHERE id = p_change_id;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Tags of deleted posts and posts awaiting approval are not changed
+  IF NOT EXISTS (SELECT 1 FROM post WHERE id = v_change.post_id AND NOT is_deleted AND NOT is_pending) THEN
+    RETURN false;
+  END IF;
+
+  -- Nothing to revert
+  IF COALESCE(icount(v_change.tag_ids_added), 0) = 0 AND COALESCE(icount(v_change.tag_ids_removed), 0) = 0 THEN
+    RETURN true;
+  END IF;
+
+  -- Re-add the removed tags and remove the added tags.
+  -- This is tracked as a new change by the reverting user.
+  PERFORM update_post_tags(
+    v_change.post_id,
+    ARRAY(SELECT tag FROM tag WHERE id = ANY(v_change.tag_ids_removed)),
+    ARRAY(SELECT tag FROM tag WHERE id = ANY(v_change.tag_ids_added)),
+    p_user_id,
+    false
+  );
+
+  RETURN true;
+END;
+$BODY$;
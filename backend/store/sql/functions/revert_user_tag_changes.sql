@@ -0,0 +1,33 @@
+CREATE FUNCTION revert_user_tag_changes(
+  IN p_change_user_id integer,
+  IN p_since timestamp with time zone,
+  IN p_until timestamp with time zone,
+  IN p_user_id integer
+)
+RETURNS integer
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_change_id integer;
+  v_reverted_count integer := 0;
+BEGIN
+  -- Revert newest changes first, so that each post ends up
+  -- with the tags it had before the user's first change
+  FOR v_change_id IN
+    SELECT id
+    FROM post_tag_change
+    WHERE user_id = p_change_user_id
+      AND created_at >= p_since
+      AND created_at < p_until
+      AND (COALESCE(icount(tag_ids_added), 0) > 0 OR COALESCE(icount(tag_ids_removed), 0) > 0)
+    ORDER BY id DESC
+  LOOP
+    IF revert_post_tag_change(v_change_id, p_user_id) THEN
+      v_reverted_count := v_reverted_count + 1;
+    END IF;
+  END LOOP;
+
+  RETURN v_reverted_count;
+END;
+$BODY$;
@@ -0,0 +1,43 @@
+CREATE FUNCTION run_tag_edit_job_batch(
+  IN p_job_id integer,
+  IN p_batch_size integer
+)
+RETURNS integer
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_job tag_edit_job;
+  v_post_ids integer[];
+  v_post_id integer;
+  v_processed_count integer;
+BEGIN
+  SELECT * INTO v_job
+  FROM tag_edit_job
+  WHERE id = p_job_id
+    AND status = 'running'
+  FOR UPDATE;
+
+  IF NOT FOUND THEN
+    RETURN 0;
+  END IF;
+
+  v_post_ids := v_job.post_ids[v_job.processed_count + 1 : v_job.processed_count + p_batch_size];
+
+  FOREACH v_post_id IN ARRAY v_post_ids LOOP
+    -- Skip posts deleted since the job was created, and posts awaiting approval
+    IF EXISTS (SELECT 1 FROM post WHERE id = v_post_id AND NOT is_deleted AND NOT is_pending) THEN
+      PERFORM update_post_tags(v_post_id, v_job.add_tags, v_job.remove_tags, v_job.user_id, false);
+    END IF;
+  END LOOP;
+
+  v_processed_count := v_job.processed_count + cardinality(v_post_ids);
+
+  UPDATE tag_edit_job
+  SET processed_count = v_processed_count,
+      status = (CASE WHEN v_processed_count >= cardinality(post_ids) THEN 'completed' ELSE status END)
+  WHERE id = p_job_id;
+
+  RETURN cardinality(v_post_ids);
+END;
+$BODY$;
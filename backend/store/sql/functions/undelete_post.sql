@@ -0,0 +1,27 @@
+CREATE FUNCTION undelete_post(
+  IN p_post_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  -- Update post
+  UPDATE post
+  SET is_deleted = false,
+      deleted_at = NULL
+  WHERE id = p_post_id
+    AND is_deleted;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  -- Show the post in searches and tag post counts again, unless it still awaits approval
+  IF NOT (SELECT is_pending FROM post WHERE id = p_post_id) THEN
+    PERFORM add_post_to_search(p_post_id);
+  END IF;
+
+  RETURN true;
+END;
+$BODY$;
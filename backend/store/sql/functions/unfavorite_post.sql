@@ -0,0 +1,26 @@
+CREATE FUNCTION unfavorite_post(
+  IN p_post_id integer,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  IF NOT EXISTS (SELECT 1 FROM post WHERE id = p_post_id AND NOT is_deleted AND NOT is_pending) THEN
+    RETURN false;
+  END IF;
+
+  DELETE FROM post_favorite
+  WHERE post_id = p_post_id
+    AND user_id = p_user_id;
+
+  IF FOUND THEN
+    UPDATE post
+    SET fav_count = fav_count - 1
+    WHERE id = p_post_id;
+  END IF;
+
+  RETURN true;
+END;
+$BODY$;
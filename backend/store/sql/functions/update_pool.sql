@@ -0,0 +1,22 @@
+CREATE FUNCTION update_pool(
+  IN p_update_pool update_pool,
+  IN p_user_id integer
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  -- Only the creator of the pool or some sort of admin can edit it.
+  -- Omitted fields are kept, and an empty description clears it.
+  UPDATE pool
+  SET
+    name = COALESCE(p_update_pool.name, name),
+    description = NULLIF(COALESCE(p_update_pool.description, description), '')
+  WHERE id = p_update_pool.id
+    AND NOT is_deleted
+    AND (user_id = p_user_id OR (SELECT rank FROM users WHERE id = p_user_id) > 0);
+
+  RETURN FOUND;
+END;
+$BODY$;
@@ -7,23 +7,73 @@ LANGUAGE plpgsql
 
 AS $BODY$
 DECLARE
-  v_success boolean;
+  v_can_edit boolean;
+  v_old post;
+  v_new post;
 BEGIN
-  -- Update post
-  UPDATE post
-  SET
-    title = p_update_post.title,
-    description = p_update_post.description,
-    source = p_update_post.source
-  WHERE id = p_update_post.id
-    AND user_id = p_user_id
-  RETURNING true INTO v_success;
+  -- Check if user is allowed to edit the post
+  v_can_edit := can_user_edit_post(p_update_post.id, p_user_id);
 
-  IF v_success THEN
-    -- Update post tags
-    PERFORM update_post_tags(p_update_post.id, p_update_post.add_tags, p_update_post.remove_tags, p_user_id, false);
+  -- If user is allowed to edit, update post
+  IF v_can_edit THEN
+    SELECT * INTO v_old FROM post WHERE id = p_update_post.id FOR UPDATE;
+
+    UPDATE post
+    SET
+      title = p_update_post.title,
+      description = p_update_post.description,
+      source = p_update_post.source,
+      rating = COALESCE(p_update_post.rating, rating),
+      parent_id = CASE
+        WHEN p_update_post.remove_parent THEN NULL
+        ELSE COALESCE(p_update_post.parent_id, parent_id)
+      END
+    WHERE id = p_update_post.id
+    RETURNING * INTO v_new;
+
+    -- Re-add the post to the search with its new rating pseudo tag ID,
+    -- unless the post is deleted or awaiting approval
+    IF v_old.rating <> v_new.rating AND EXISTS (SELECT 1 FROM post_tag_id_cache WHERE post_id = v_new.id) THEN
+      PERFORM remove_post_from_search(v_new.id);
+      PERFORM add_post_to_search(v_new.id);
+    END IF;
+
+    -- Track metadata changes
+    IF (v_old.title, v_old.description, v_old.source, v_old.rating, v_old.parent_id)
+       IS DISTINCT FROM (v_new.title, v_new.description, v_new.source, v_new.rating, v_new.parent_id) THEN
+      INSERT INTO post_metadata_change (
+        post_id,
+        user_id,
+        old_title,
+        new_title,
+        old_description,
+        new_description,
+        old_source,
+        new_source,
+        old_rating,
+        new_rating,
+        old_parent_id,
+        new_parent_id
+      ) VALUES (
+        p_update_post.id,
+        p_user_id,
+        v_old.title,
+        v_new.title,
+        v_old.description,
+        v_new.description,
+        v_old.source,
+        v_new.source,
+        v_old.rating,
+        v_new.rating,
+        v_old.parent_id,
+        v_new.parent_id
+      );
+    END IF;
   END IF;
 
-  RETURN COALESCE(v_success, false);
+  -- Update post tags
+  PERFORM update_post_tags(p_update_post.id, p_update_post.add_tags, p_update_post.remove_tags, p_user_id, false);
+
+  RETURN true;
 END;
 $BODY$;
@@ -0,0 +1,35 @@
+CREATE FUNCTION update_post_file_metadata(
+  IN p_post_id integer,
+  IN p_width integer,
+  IN p_height integer,
+  IN p_tn_ext text,
+  IN p_renditions text[],
+  IN p_phashes bigint[]
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+BEGIN
+  UPDATE post
+  SET width = p_width,
+      height = p_height,
+      tn_ext = p_tn_ext,
+      renditions = p_renditions
+  WHERE id = p_post_id;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  IF cardinality(p_phashes) > 0 THEN
+    DELETE FROM post_phash WHERE post_id = p_post_id;
+
+    INSERT INTO post_phash (post_id, phash)
+      SELECT DISTINCT p_post_id, phash
+      FROM unnest(p_phashes) AS phash;
+  END IF;
+
+  RETURN true;
+END;
+$BODY$;
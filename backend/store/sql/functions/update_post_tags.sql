@@ -14,6 +14,7 @@ DECLARE
   v_remove_tag_ids integer[];
   v_old_tag_ids integer[];
   v_new_tag_ids integer[];
+  v_rating_tag_id integer;
 BEGIN
   -- Create missing tags
   PERFORM create_missing_tags(p_add_tags);
@@ -47,44 +48,59 @@ BEGIN
                    ORDER BY tag ASC)
   WHERE id = p_post_id;
 
-  v_old_tag_ids := compute_post_tag_ids(v_old_tag_ids);
-  v_new_tag_ids := compute_post_tag_ids(v_new_tag_ids);
+  -- Include the rating pseudo tag ID, so that rating filters can use the search cache
+  SELECT rating_tag_id(rating) INTO v_rating_tag_id FROM post WHERE id = p_post_id;
+
+  v_old_tag_ids := compute_post_tag_ids(v_old_tag_ids) | v_rating_tag_id;
+  v_new_tag_ids := compute_post_tag_ids(v_new_tag_ids) | v_rating_tag_id;
 
   -- Update post_tag_id_cache
   UPDATE post_tag_id_cache
   SET tag_ids = v_new_tag_ids
   WHERE post_id = p_post_id;
 
-  -- Update search cache to reflect added post
-  UPDATE search_cache
-  SET post_count = post_count + 1,
-      first_post_id = (CASE WHEN p_post_id > first_post_id THEN p_post_id ELSE first_post_id END),
-      last_page_post_ids = (CASE WHEN p_post_id < (SELECT MAX(id) FROM unnest(last_page_post_ids) AS id)
-                            THEN last_page_post_ids | p_post_id
-                            ELSE last_page_post_ids
-                            END)
-  WHERE v_new_tag_ids @> tag_ids
-    AND NOT v_new_tag_ids && exclude_tag_ids
-    AND (p_new_post OR (NOT v_old_tag_ids @> tag_ids) OR v_old_tag_ids && exclude_tag_ids);
+  -- Update tag post counts and search cache,
+  -- unless the post is deleted or awaiting approval
+  IF FOUND THEN
+    UPDATE tag
+    SET post_count = post_count + 1
+    WHERE id = ANY(v_new_tag_ids - v_old_tag_ids);
+
+    UPDATE tag
+    SET post_count = post_count - 1
+    WHERE id = ANY(v_old_tag_ids - v_new_tag_ids);
+
+    -- Update search cache to reflect added post
+    UPDATE search_cache
+    SET post_count = post_count + 1,
+        first_post_id = (CASE WHEN p_post_id > first_post_id THEN p_post_id ELSE first_post_id END),
+        last_page_post_ids = (CASE WHEN p_post_id < (SELECT MAX(id) FROM unnest(last_page_post_ids) AS id)
+                              THEN last_page_post_ids | p_post_id
+                              ELSE last_page_post_ids
+                              END)
+    WHERE v_new_tag_ids @> tag_ids
+      AND NOT v_new_tag_ids && exclude_tag_ids
+      AND (p_new_post OR (NOT v_old_tag_ids @> tag_ids) OR v_old_tag_ids && exclude_tag_ids);
 
-  -- Update search cache to reflect removed post
-  UPDATE search_cache AS sc
-  SET post_count = post_count - 1,
-      first_post_id = (CASE WHEN p_post_id = first_post_id
-                       THEN (SELECT COALESCE(MAX(post_id), 0)
-                             FROM post_tag_id_cache AS ptic
-                             WHERE ptic.tag_ids @> sc.tag_ids
-                               AND NOT ptic.tag_ids && sc.exclude_tag_ids)
-                       ELSE first_post_id
-                       END),
-      last_page_post_ids = (CASE WHEN (SELECT p_post_id BETWEEN MIN(id) AND MAX(id) FROM unnest(last_page_post_ids) AS id)
-                            THEN last_page_post_ids - p_post_id
-                            ELSE last_page_post_ids
-                            END)
-  WHERE NOT p_new_post
-    AND ((NOT v_new_tag_ids @> tag_ids) OR v_new_tag_ids && exclude_tag_ids)
-    AND v_old_tag_ids @> tag_ids
-    AND NOT v_old_tag_ids && exclude_tag_ids;
+    -- Update search cache to reflect removed post
+    UPDATE search_cache AS sc
+    SET post_count = post_count - 1,
+        first_post_id = (CASE WHEN p_post_id = first_post_id
+                         THEN (SELECT COALESCE(MAX(post_id), 0)
+                               FROM post_tag_id_cache AS ptic
+                               WHERE ptic.tag_ids @> sc.tag_ids
+                                 AND NOT ptic.tag_ids && sc.exclude_tag_ids)
+                         ELSE first_post_id
+                         END),
+        last_page_post_ids = (CASE WHEN (SELECT p_post_id BETWEEN MIN(id) AND MAX(id) FROM unnest(last_page_post_ids) AS id)
+                              THEN last_page_post_ids - p_post_id
+                              ELSE last_page_post_ids
+                              END)
+    WHERE NOT p_new_post
+      AND ((NOT v_new_tag_ids @> tag_ids) OR v_new_tag_ids && exclude_tag_ids)
+      AND v_old_tag_ids @> tag_ids
+      AND NOT v_old_tag_ids && exclude_tag_ids;
+  END IF;
 
   -- Track tag changes
   INSERT INTO post_tag_change (
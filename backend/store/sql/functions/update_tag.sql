@@ -90,8 +90,16 @@ BEGIN
     -- Update pre-calculated post tag ID cache
     UPDATE post_tag_id_cache AS ptic
     SET tag_ids = compute_post_tag_ids(array(SELECT tag_id FROM post_tag AS pt WHERE pt.post_id = ptic.post_id))
+                  | (SELECT rating_tag_id(rating) FROM post WHERE id = ptic.post_id)
     WHERE tag_ids && v_affected_tag_ids;
 
+    -- Recalculate post counts of affected tags
+    UPDATE tag AS t
+    SET post_count = (SELECT COUNT(*)
+                      FROM post_tag_id_cache AS ptic
+                      WHERE ptic.tag_ids @> ARRAY[t.id])
+    WHERE id = ANY(v_affected_tag_ids);
+
     -- Delete cached searches affected by alias change
     DELETE FROM search_cache
     WHERE tag_ids && v_affected_tag_ids
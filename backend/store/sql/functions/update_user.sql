@@ -12,7 +12,8 @@ BEGIN
     name = COALESCE(p_update_user.name, name),
     rank = COALESCE(p_update_user.rank, rank),
     biography = COALESCE(p_update_user.biography, biography),
-    css = COALESCE(p_update_user.css, css)
+    css = COALESCE(p_update_user.css, css),
+    max_rating = COALESCE(p_update_user.max_rating, max_rating)
   WHERE id = p_update_user.id;
 
   RETURN true;
@@ -0,0 +1,45 @@
+CREATE FUNCTION vote_post(
+  IN p_post_id integer,
+  IN p_user_id integer,
+  IN p_score smallint
+)
+RETURNS boolean
+LANGUAGE plpgsql
+
+AS $BODY$
+DECLARE
+  v_old_score smallint;
+BEGIN
+  -- Deleted posts and posts awaiting approval cannot be voted on
+  PERFORM 1 FROM post WHERE id = p_post_id AND NOT is_deleted AND NOT is_pending FOR UPDATE;
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  SELECT score INTO v_old_score
+  FROM post_vote
+  WHERE post_id = p_post_id
+    AND user_id = p_user_id;
+
+  IF p_score = 0 THEN
+    -- A score of 0 removes the vote
+    DELETE FROM post_vote
+    WHERE post_id = p_post_id
+      AND user_id = p_user_id;
+  ELSIF v_old_score IS DISTINCT FROM p_score THEN
+    -- Each user has at most one vote per post
+    INSERT INTO post_vote (post_id, user_id, score)
+    VALUES (p_post_id, p_user_id, p_score)
+    ON CONFLICT (post_id, user_id) DO UPDATE
+    SET score = EXCLUDED.score;
+  END IF;
+
+  -- Update cached score with the difference
+  UPDATE post
+  SET score = score + p_score - COALESCE(v_old_score, 0)
+  WHERE id = p_post_id
+    AND p_score IS DISTINCT FROM COALESCE(v_old_score, 0);
+
+  RETURN true;
+END;
+$BODY$;
@@ -0,0 +1,11 @@
+CREATE FUNCTION wiki_search_vector(
+  IN p_title text,
+  IN p_body text
+)
+RETURNS tsvector
+LANGUAGE sql
+
+AS $BODY$
+  SELECT setweight(to_tsvector('english', p_title), 'A')
+      || setweight(to_tsvector('english', p_body), 'B');
+$BODY$ IMMUTABLE;
@@ -7,6 +7,7 @@ CREATE TABLE comment
   user_id integer,
   user_name text,
   comment text NOT NULL,
+  is_deleted boolean NOT NULL DEFAULT false,
 
   PRIMARY KEY (id),
 
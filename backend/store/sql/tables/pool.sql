@@ -0,0 +1,26 @@
+CREATE TABLE pool
+(
+  id serial NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  user_id integer NOT NULL,
+  name text NOT NULL,
+  description text,
+  is_deleted boolean NOT NULL DEFAULT false,
+
+  PRIMARY KEY (id),
+
+  FOREIGN KEY (user_id)
+    REFERENCES users (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID
+);
+
+SELECT manage_updated_at('pool'); -- Automatically manage updated_at
+
+-- Pool names only need to be unique among pools that are not deleted
+CREATE UNIQUE INDEX pool_name_idx ON pool
+  USING btree
+  (name ASC NULLS LAST)
+  WHERE NOT is_deleted;
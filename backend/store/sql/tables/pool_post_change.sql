@@ -0,0 +1,12 @@
+CREATE TABLE pool_post_change
+(
+  id serial NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+  pool_id integer NOT NULL,
+  user_id integer NOT NULL,
+  post_ids_added integer[],
+  post_ids_removed integer[],
+
+  PRIMARY KEY (id)
+);
@@ -16,6 +16,13 @@ CREATE TABLE post
   tn_ext text NOT NULL,
   tags text[] NOT NULL DEFAULT '{}',
   is_deleted boolean NOT NULL DEFAULT false,
+  deleted_at timestamp with time zone,
+  rating text NOT NULL DEFAULT 'general' CHECK (rating IN ('general', 'sensitive', 'questionable', 'explicit')),
+  fav_count integer NOT NULL DEFAULT 0,
+  score integer NOT NULL DEFAULT 0,
+  parent_id integer,
+  is_pending boolean NOT NULL DEFAULT false,
+  renditions text[] NOT NULL DEFAULT '{}',
 
   PRIMARY KEY (id),
 
@@ -23,11 +30,39 @@ CREATE TABLE post
     REFERENCES users (id) MATCH SIMPLE
     ON UPDATE NO ACTION
     ON DELETE CASCADE
+    NOT VALID,
+
+  FOREIGN KEY (parent_id)
+    REFERENCES post (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE SET NULL
     NOT VALID
 );
 
-SELECT manage_updated_at('post'); -- Automatically manage updated_at
+-- Automatically manage updated_at, except for updates of the cached vote score
+CREATE TRIGGER set_updated_at BEFORE UPDATE ON post
+  FOR EACH ROW
+  WHEN (OLD.score IS NOT DISTINCT FROM NEW.score)
+  EXECUTE FUNCTION set_updated_at();
 
 CREATE INDEX post_is_deleted_idx ON post
   USING btree
   (is_deleted ASC NULLS LAST);
+
+CREATE INDEX post_score_idx ON post
+  USING btree
+  (score DESC NULLS LAST, id DESC NULLS LAST);
+
+CREATE INDEX post_parent_id_idx ON post
+  USING btree
+  (parent_id ASC NULLS LAST);
+
+CREATE INDEX post_deleted_at_idx ON post
+  USING btree
+  (deleted_at ASC NULLS LAST)
+  WHERE is_deleted;
+
+CREATE INDEX post_is_pending_idx ON post
+  USING btree
+  (id DESC NULLS LAST)
+  WHERE is_pending;
@@ -0,0 +1,43 @@
+CREATE TABLE post_file_replacement
+(
+  id serial NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+  post_id integer NOT NULL,
+  user_id integer NOT NULL,
+
+  old_filename text NOT NULL,
+  new_filename text NOT NULL,
+  old_size integer NOT NULL,
+  new_size integer NOT NULL,
+  old_width integer NOT NULL,
+  new_width integer NOT NULL,
+  old_height integer NOT NULL,
+  new_height integer NOT NULL,
+  old_hash text NOT NULL,
+  new_hash text NOT NULL,
+  old_ext text NOT NULL,
+  new_ext text NOT NULL,
+  old_tn_ext text NOT NULL,
+  new_tn_ext text NOT NULL,
+  old_phashes bigint[] NOT NULL DEFAULT '{}',
+  new_phashes bigint[] NOT NULL DEFAULT '{}',
+  old_renditions text[] NOT NULL DEFAULT '{}',
+  new_renditions text[] NOT NULL DEFAULT '{}',
+
+  PRIMARY KEY (id),
+
+  FOREIGN KEY (post_id)
+    REFERENCES post (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID
+);
+
+CREATE INDEX post_file_replacement_post_id_idx ON post_file_replacement
+  USING btree
+  (post_id ASC NULLS LAST);
+
+CREATE INDEX post_file_replacement_old_hash_idx ON post_file_replacement
+  USING btree
+  (old_hash ASC NULLS LAST);
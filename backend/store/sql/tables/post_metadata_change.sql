@@ -0,0 +1,31 @@
+CREATE TABLE post_metadata_change
+(
+  id serial NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+  post_id integer NOT NULL,
+  user_id integer NOT NULL,
+
+  old_title text,
+  new_title text,
+  old_description text,
+  new_description text,
+  old_source text,
+  new_source text,
+  old_rating text,
+  new_rating text,
+  old_parent_id integer,
+  new_parent_id integer,
+
+  PRIMARY KEY (id),
+
+  FOREIGN KEY (post_id)
+    REFERENCES post (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID
+);
+
+CREATE INDEX post_metadata_change_post_id_idx ON post_metadata_change
+  USING btree
+  (post_id ASC NULLS LAST);
@@ -0,0 +1,29 @@
+CREATE TABLE post_note_version
+(
+  id serial NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+  note_id integer NOT NULL,
+  post_id integer NOT NULL,
+  user_id integer NOT NULL,
+
+  version integer NOT NULL,
+  x integer NOT NULL,
+  y integer NOT NULL,
+  width integer NOT NULL,
+  height integer NOT NULL,
+  body text NOT NULL,
+  is_deleted boolean NOT NULL,
+
+  PRIMARY KEY (id),
+
+  FOREIGN KEY (note_id)
+    REFERENCES post_note (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID
+);
+
+CREATE INDEX post_note_version_note_id_idx ON post_note_version
+  USING btree
+  (note_id ASC NULLS LAST);
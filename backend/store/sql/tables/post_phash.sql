@@ -0,0 +1,29 @@
+CREATE TABLE post_phash
+(
+  post_id integer NOT NULL,
+  phash bigint NOT NULL,
+
+  PRIMARY KEY (post_id, phash),
+
+  FOREIGN KEY (post_id)
+    REFERENCES post (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID
+);
+
+CREATE INDEX post_phash_band_0_idx ON post_phash
+  USING btree
+  (phash_band(phash, 0));
+
+CREATE INDEX post_phash_band_1_idx ON post_phash
+  USING btree
+  (phash_band(phash, 1));
+
+CREATE INDEX post_phash_band_2_idx ON post_phash
+  USING btree
+  (phash_band(phash, 2));
+
+CREATE INDEX post_phash_band_3_idx ON post_phash
+  USING btree
+  (phash_band(phash, 3));
@@ -10,3 +10,11 @@ CREATE TABLE post_tag_change
 
   PRIMARY KEY (id)
 );
+
+CREATE INDEX post_tag_change_post_id_idx ON post_tag_change
+  USING btree
+  (post_id ASC NULLS LAST);
+
+CREATE INDEX post_tag_change_user_id_idx ON post_tag_change
+  USING btree
+  (user_id ASC NULLS LAST, id DESC NULLS LAST);
@@ -0,0 +1,28 @@
+CREATE TABLE post_vote
+(
+  post_id integer NOT NULL,
+  user_id integer NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  score smallint NOT NULL CHECK (score IN (-1, 1)),
+
+  PRIMARY KEY (post_id, user_id),
+
+  FOREIGN KEY (post_id)
+    REFERENCES post (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID,
+
+  FOREIGN KEY (user_id)
+    REFERENCES users (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID
+);
+
+SELECT manage_updated_at('post_vote'); -- Automatically manage updated_at
+
+CREATE INDEX post_vote_updated_at_idx ON post_vote
+  USING btree
+  (updated_at DESC NULLS LAST);
@@ -0,0 +1,37 @@
+CREATE TABLE report
+(
+  id serial NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  user_id integer NOT NULL,
+
+  target_type text NOT NULL CHECK (target_type IN ('post', 'comment', 'wiki', 'user')),
+  target_id integer NOT NULL,
+  reason text NOT NULL,
+
+  status text NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'resolved', 'dismissed')),
+  resolver_id integer,
+  resolved_at timestamp with time zone,
+  resolution_note text,
+  target_deleted boolean NOT NULL DEFAULT false,
+
+  PRIMARY KEY (id),
+
+  FOREIGN KEY (user_id)
+    REFERENCES users (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID,
+
+  FOREIGN KEY (resolver_id)
+    REFERENCES users (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE SET NULL
+    NOT VALID
+);
+
+SELECT manage_updated_at('report'); -- Automatically manage updated_at
+
+CREATE INDEX report_status_idx ON report
+  USING btree
+  (status ASC NULLS LAST, id DESC NULLS LAST);
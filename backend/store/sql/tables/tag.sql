@@ -6,9 +6,14 @@ CREATE TABLE tag
   tag text NOT NULL,
   alias_of_tag_id integer REFERENCES tag(id),
   implied_tag_ids integer[] NOT NULL DEFAULT '{}',
+  post_count integer NOT NULL DEFAULT 0,
 
   PRIMARY KEY (id),
   UNIQUE (tag)
 );
 
 SELECT manage_updated_at('tag'); -- Automatically manage updated_at
+
+CREATE INDEX tag_tag_trgm_idx ON tag
+  USING gin
+  (tag gin_trgm_ops);
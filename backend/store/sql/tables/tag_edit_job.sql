@@ -0,0 +1,24 @@
+CREATE TABLE tag_edit_job
+(
+  id serial NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at timestamp with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  user_id integer NOT NULL,
+  include_tags text[] NOT NULL DEFAULT '{}',
+  exclude_tags text[] NOT NULL DEFAULT '{}',
+  add_tags text[] NOT NULL DEFAULT '{}',
+  remove_tags text[] NOT NULL DEFAULT '{}',
+  post_ids integer[] NOT NULL DEFAULT '{}',
+  processed_count integer NOT NULL DEFAULT 0,
+  status text NOT NULL DEFAULT 'running' CHECK (status IN ('running', 'completed', 'failed')),
+
+  PRIMARY KEY (id),
+
+  FOREIGN KEY (user_id)
+    REFERENCES users (id) MATCH SIMPLE
+    ON UPDATE NO ACTION
+    ON DELETE CASCADE
+    NOT VALID
+);
+
+SELECT manage_updated_at('tag_edit_job'); -- Automatically manage updated_at
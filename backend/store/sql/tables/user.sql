@@ -8,6 +8,7 @@ CREATE TABLE users
   rank smallint NOT NULL DEFAULT 0,
   biography text,
   css text,
+  max_rating text NOT NULL DEFAULT 'explicit' CHECK (max_rating IN ('general', 'sensitive', 'questionable', 'explicit')),
 
   PRIMARY KEY (id),
   UNIQUE (name)
@@ -9,5 +9,8 @@ CREATE TYPE new_post AS (
   height integer,
   hash text,
   ext text,
-  tn_ext text
+  tn_ext text,
+  renditions text[],
+  rating text,
+  is_pending boolean
 );
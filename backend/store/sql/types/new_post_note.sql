@@ -0,0 +1,9 @@
+CREATE TYPE new_post_note AS (
+  post_id integer,
+
+  x integer,
+  y integer,
+  width integer,
+  height integer,
+  body text
+);
@@ -0,0 +1,5 @@
+CREATE TYPE new_report AS (
+  target_type text,
+  target_id integer,
+  reason text
+);
@@ -0,0 +1,6 @@
+CREATE TYPE new_tag_edit_job AS (
+  include_tags text[],
+  exclude_tags text[],
+  add_tags text[],
+  remove_tags text[]
+);
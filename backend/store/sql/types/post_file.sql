@@ -0,0 +1,10 @@
+CREATE TYPE post_file AS (
+  filename text,
+  size integer,
+  width integer,
+  height integer,
+  hash text,
+  ext text,
+  tn_ext text,
+  renditions text[]
+);
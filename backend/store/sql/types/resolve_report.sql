@@ -0,0 +1,7 @@
+CREATE TYPE resolve_report AS (
+  id integer,
+
+  status text,
+  resolution_note text,
+  delete_target boolean
+);
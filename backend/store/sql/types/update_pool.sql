@@ -0,0 +1,6 @@
+CREATE TYPE update_pool AS (
+  id integer,
+
+  name text,
+  description text
+);
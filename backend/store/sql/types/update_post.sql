@@ -5,5 +5,8 @@ CREATE TYPE update_post AS (
   description text,
   source text,
   add_tags text[],
-  remove_tags text[]
+  remove_tags text[],
+  rating text,
+  parent_id integer,
+  remove_parent boolean
 );
@@ -0,0 +1,9 @@
+CREATE TYPE update_post_note AS (
+  id integer,
+
+  x integer,
+  y integer,
+  width integer,
+  height integer,
+  body text
+);
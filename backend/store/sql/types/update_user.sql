@@ -4,5 +4,6 @@ CREATE TYPE update_user AS (
   name text,
   rank smallint,
   biography text,
-  css text
+  css text,
+  max_rating text
 );
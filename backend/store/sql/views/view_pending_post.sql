@@ -0,0 +1,18 @@
+CREATE VIEW view_pending_post
+AS
+SELECT
+  p.id,
+  p.created_at,
+  p.user_id,
+  u.name AS user_name,
+  p.title,
+  p.filename,
+  p.hash,
+  p.ext,
+  p.tn_ext,
+  p.tags,
+  p.rating
+FROM post AS p
+JOIN users AS u ON u.id = p.user_id
+WHERE p.is_pending
+  AND NOT p.is_deleted;
@@ -15,7 +15,35 @@ SELECT
   p.hash,
   p.ext,
   p.tn_ext,
-  p.tags
+  p.renditions,
+  p.tags,
+  p.rating,
+  p.fav_count,
+  p.score,
+  ARRAY(
+    SELECT pp.pool_id
+    FROM pool_post AS pp
+    JOIN pool AS pl ON pl.id = pp.pool_id
+    WHERE pp.post_id = p.id
+      AND NOT pl.is_deleted
+    ORDER BY pp.pool_id ASC
+  ) AS pools,
+  (
+    SELECT pa.id
+    FROM post AS pa
+    WHERE pa.id = p.parent_id
+      AND NOT pa.is_deleted
+      AND NOT pa.is_pending
+  ) AS parent_id,
+  ARRAY(
+    SELECT c.id
+    FROM post AS c
+    WHERE c.parent_id = p.id
+      AND NOT c.is_deleted
+      AND NOT c.is_pending
+    ORDER BY c.id ASC
+  ) AS child_ids
 FROM post AS p
 JOIN users AS u ON u.id = p.user_id
-WHERE NOT is_deleted;
+WHERE NOT p.is_deleted
+  AND NOT p.is_pending;
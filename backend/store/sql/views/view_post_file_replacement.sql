@@ -0,0 +1,24 @@
+CREATE VIEW view_post_file_replacement
+AS
+SELECT
+  pfr.id,
+  pfr.created_at,
+  pfr.post_id,
+  pfr.user_id,
+  u.name AS user_name,
+  pfr.old_filename,
+  pfr.new_filename,
+  pfr.old_size,
+  pfr.new_size,
+  pfr.old_width,
+  pfr.new_width,
+  pfr.old_height,
+  pfr.new_height,
+  pfr.old_hash,
+  pfr.new_hash,
+  pfr.old_ext,
+  pfr.new_ext,
+  pfr.old_tn_ext,
+  pfr.new_tn_ext
+FROM post_file_replacement AS pfr
+JOIN users AS u ON u.id = pfr.user_id;
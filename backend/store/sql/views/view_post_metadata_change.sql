@@ -0,0 +1,20 @@
+CREATE VIEW view_post_metadata_change
+AS
+SELECT
+  pmc.id,
+  pmc.created_at,
+  pmc.post_id,
+  pmc.user_id,
+  u.name AS user_name,
+  pmc.old_title,
+  pmc.new_title,
+  pmc.old_description,
+  pmc.new_description,
+  pmc.old_source,
+  pmc.new_source,
+  pmc.old_rating,
+  pmc.new_rating,
+  pmc.old_parent_id,
+  pmc.new_parent_id
+FROM post_metadata_change AS pmc
+JOIN users AS u ON u.id = pmc.user_id;
@@ -0,0 +1,18 @@
+CREATE VIEW view_post_note
+AS
+SELECT
+  n.id,
+  n.created_at,
+  n.updated_at,
+  n.post_id,
+  n.user_id,
+  u.name AS user_name,
+  n.x,
+  n.y,
+  n.width,
+  n.height,
+  n.body,
+  n.version
+FROM post_note AS n
+JOIN users AS u ON u.id = n.user_id
+WHERE NOT n.is_deleted;
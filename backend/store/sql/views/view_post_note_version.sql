@@ -0,0 +1,18 @@
+CREATE VIEW view_post_note_version
+AS
+SELECT
+  nv.id,
+  nv.created_at,
+  nv.note_id,
+  nv.post_id,
+  nv.user_id,
+  u.name AS user_name,
+  nv.version,
+  nv.x,
+  nv.y,
+  nv.width,
+  nv.height,
+  nv.body,
+  nv.is_deleted
+FROM post_note_version AS nv
+JOIN users AS u ON u.id = nv.user_id;
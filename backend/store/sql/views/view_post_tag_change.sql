@@ -0,0 +1,12 @@
+CREATE VIEW view_post_tag_change
+AS
+SELECT
+  ptc.id,
+  ptc.created_at,
+  ptc.post_id,
+  ptc.user_id,
+  u.name AS user_name,
+  ARRAY(SELECT t.tag FROM tag AS t WHERE t.id = ANY(ptc.tag_ids_added) ORDER BY t.tag ASC) AS tags_added,
+  ARRAY(SELECT t.tag FROM tag AS t WHERE t.id = ANY(ptc.tag_ids_removed) ORDER BY t.tag ASC) AS tags_removed
+FROM post_tag_change AS ptc
+JOIN users AS u ON u.id = ptc.user_id;
@@ -0,0 +1,30 @@
+CREATE VIEW view_report
+AS
+SELECT
+  r.id,
+  r.created_at,
+  r.updated_at,
+  r.user_id,
+  u.name AS user_name,
+  r.target_type,
+  r.target_id,
+  -- Post of the reported post or comment
+  (CASE r.target_type
+   WHEN 'post' THEN r.target_id
+   WHEN 'comment' THEN (SELECT pc.post_id FROM post_comment AS pc WHERE pc.id = r.target_id)
+   END) AS target_post_id,
+  -- Title of the reported wiki page, or name of the reported user
+  (CASE r.target_type
+   WHEN 'wiki' THEN (SELECT w.title FROM wiki_pages AS w WHERE w.id = r.target_id)
+   WHEN 'user' THEN (SELECT tu.name FROM users AS tu WHERE tu.id = r.target_id)
+   END) AS target_name,
+  r.reason,
+  r.status,
+  r.resolver_id,
+  ru.name AS resolver_name,
+  r.resolved_at,
+  r.resolution_note,
+  r.target_deleted
+FROM report AS r
+JOIN users AS u ON u.id = r.user_id
+LEFT JOIN users AS ru ON ru.id = r.resolver_id;
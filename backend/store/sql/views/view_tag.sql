@@ -4,6 +4,7 @@ SELECT
   t.id,
   t.tag,
   aot.tag AS alias_of_tag,
+  COALESCE(aot.post_count, t.post_count) AS post_count,
   array(SELECT tag FROM tag WHERE tag.alias_of_tag_id = t.id) AS aliases,
   array(SELECT tag FROM tag AS t1 JOIN unnest(t.implied_tag_ids) AS itid ON t1.id = itid) AS implied_tags
 FROM tag AS t
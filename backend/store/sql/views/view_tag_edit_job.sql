@@ -0,0 +1,17 @@
+CREATE VIEW view_tag_edit_job
+AS
+SELECT
+  j.id,
+  j.created_at,
+  j.updated_at,
+  j.user_id,
+  u.name AS user_name,
+  j.include_tags,
+  j.exclude_tags,
+  j.add_tags,
+  j.remove_tags,
+  cardinality(j.post_ids) AS post_count,
+  j.processed_count,
+  j.status
+FROM tag_edit_job AS j
+JOIN users AS u ON u.id = j.user_id;
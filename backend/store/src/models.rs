@@ -12,6 +12,7 @@ pub struct User {
     pub rank: i16,
     pub biography: Option<String>,
     pub css: Option<String>,
+    pub max_rating: String,
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -32,6 +33,13 @@ pub struct Post {
     pub tn_ext: String,
     pub tags: Vec<String>,
     pub is_deleted: bool,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub rating: String,
+    pub fav_count: i32,
+    pub score: i32,
+    pub parent_id: Option<i32>,
+    pub is_pending: bool,
+    pub renditions: Vec<String>,
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -50,7 +58,67 @@ pub struct ViewPost {
     pub hash: Option<String>,
     pub ext: Option<String>,
     pub tn_ext: Option<String>,
+    pub renditions: Option<Vec<String>>,
     pub tags: Option<Vec<String>>,
+    pub rating: Option<String>,
+    pub fav_count: Option<i32>,
+    pub score: Option<i32>,
+    pub pools: Option<Vec<i32>>,
+    pub parent_id: Option<i32>,
+    pub child_ids: Option<Vec<i32>>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct DeletedPost {
+    pub id: Option<i32>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub user_id: Option<i32>,
+    pub user_name: Option<String>,
+    pub title: Option<String>,
+    pub filename: Option<String>,
+    pub hash: Option<String>,
+    pub ext: Option<String>,
+    pub tn_ext: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub rating: Option<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct PendingPost {
+    pub id: Option<i32>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub user_id: Option<i32>,
+    pub user_name: Option<String>,
+    pub title: Option<String>,
+    pub filename: Option<String>,
+    pub hash: Option<String>,
+    pub ext: Option<String>,
+    pub tn_ext: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub rating: Option<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct PopularPost {
+    #[sqlx(flatten)]
+    pub post: ViewPost,
+    pub period_score: i32,
+}
+
+/// Post with the name of its user, including posts awaiting approval
+#[derive(Debug, sqlx::FromRow)]
+pub struct ExportPost {
+    #[sqlx(flatten)]
+    pub post: Post,
+    pub user_name: String,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct SimilarPost {
+    #[sqlx(flatten)]
+    pub post: ViewPost,
+    pub distance: i32,
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -80,6 +148,7 @@ pub struct PostComment {
     pub user_name: Option<String>,
     pub post_id: i32,
     pub comment: String,
+    pub is_deleted: bool,
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -87,10 +156,27 @@ pub struct ViewTag {
     pub id: Option<i32>,
     pub tag: Option<String>,
     pub alias_of_tag: Option<String>,
+    pub post_count: Option<i32>,
     pub aliases: Option<Vec<String>>,
     pub implied_tags: Option<Vec<String>>,
 }
 
+#[derive(Debug, sqlx::FromRow)]
+pub struct AutocompleteTag {
+    pub id: i32,
+    pub tag: String,
+    pub alias_of_tag: Option<String>,
+    pub post_count: i32,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct SearchTag {
+    pub id: i32,
+    pub tag: String,
+    pub post_count: i32,
+    pub search_post_count: i32,
+}
+
 #[derive(Debug, sqlx::Type)]
 #[sqlx(type_name = "new_post")]
 pub struct NewPost {
@@ -105,6 +191,22 @@ pub struct NewPost {
     pub hash: Option<String>,
     pub ext: Option<String>,
     pub tn_ext: Option<String>,
+    pub renditions: Option<Vec<String>>,
+    pub rating: Option<String>,
+    pub is_pending: Option<bool>,
+}
+
+#[derive(Debug, sqlx::Type)]
+#[sqlx(type_name = "post_file")]
+pub struct PostFile {
+    pub filename: String,
+    pub size: i32,
+    pub width: i32,
+    pub height: i32,
+    pub hash: String,
+    pub ext: String,
+    pub tn_ext: String,
+    pub renditions: Vec<String>,
 }
 
 #[derive(Debug, sqlx::Type)]
@@ -118,6 +220,10 @@ pub struct UpdatePost {
 
     pub add_tags: Vec<String>,
     pub remove_tags: Vec<String>,
+
+    pub rating: Option<String>,
+    pub parent_id: Option<i32>,
+    pub remove_parent: bool,
 }
 
 #[derive(Debug, sqlx::Type)]
@@ -144,6 +250,7 @@ pub struct UpdateUser {
     pub rank: Option<i16>,
     pub biography: Option<String>,
     pub css: Option<String>,
+    pub max_rating: Option<String>,
 }
 
 #[derive(Debug, sqlx::Type)]
@@ -160,6 +267,13 @@ pub struct PageInfo {
     pub start_id: Option<i32>,
 }
 
+#[derive(Debug, sqlx::FromRow)]
+pub struct KeysetPageInfo {
+    pub no: i32,
+    pub start_id: i32,
+    pub start_key: i64,
+}
+
 #[derive(Debug, sqlx::Type)]
 #[sqlx(type_name = "refresh_refresh_token_result")]
 pub struct CreateRefreshTokenResult {
@@ -194,3 +308,215 @@ pub struct UpdateWikiPage {
     pub deleted: bool,
     pub reason: String
 }
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct Pool {
+    pub id: Option<i32>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub user_id: Option<i32>,
+    pub user_name: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub post_ids: Option<Vec<i32>>,
+}
+
+#[derive(Debug, sqlx::Type)]
+#[sqlx(type_name = "new_pool")]
+pub struct NewPool {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub post_ids: Vec<i32>,
+}
+
+#[derive(Debug, sqlx::Type)]
+#[sqlx(type_name = "update_pool")]
+pub struct UpdatePool {
+    pub id: Option<i32>,
+
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct PostNote {
+    pub id: Option<i32>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub post_id: Option<i32>,
+    pub user_id: Option<i32>,
+    pub user_name: Option<String>,
+    pub x: Option<i32>,
+    pub y: Option<i32>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub body: Option<String>,
+    pub version: Option<i32>,
+}
+
+#[derive(Debug, sqlx::Type)]
+#[sqlx(type_name = "new_post_note")]
+pub struct NewPostNote {
+    pub post_id: i32,
+
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    pub body: String,
+}
+
+/// Note created along with its post
+#[derive(Debug)]
+pub struct PostNoteContent {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    pub body: String,
+}
+
+#[derive(Debug, sqlx::Type)]
+#[sqlx(type_name = "update_post_note")]
+pub struct UpdatePostNote {
+    pub id: i32,
+
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    pub body: String,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct PostNoteVersion {
+    pub id: Option<i32>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub note_id: Option<i32>,
+    pub post_id: Option<i32>,
+    pub user_id: Option<i32>,
+    pub user_name: Option<String>,
+    pub version: Option<i32>,
+    pub x: Option<i32>,
+    pub y: Option<i32>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub body: Option<String>,
+    pub is_deleted: Option<bool>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct Report {
+    pub id: Option<i32>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub user_id: Option<i32>,
+    pub user_name: Option<String>,
+    pub target_type: Option<String>,
+    pub target_id: Option<i32>,
+    pub target_post_id: Option<i32>,
+    pub target_name: Option<String>,
+    pub reason: Option<String>,
+    pub status: Option<String>,
+    pub resolver_id: Option<i32>,
+    pub resolver_name: Option<String>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub resolution_note: Option<String>,
+    pub target_deleted: Option<bool>,
+}
+
+#[derive(Debug, sqlx::Type)]
+#[sqlx(type_name = "new_report")]
+pub struct NewReport {
+    pub target_type: String,
+    pub target_id: i32,
+    pub reason: String,
+}
+
+#[derive(Debug, sqlx::Type)]
+#[sqlx(type_name = "resolve_report")]
+pub struct ResolveReport {
+    pub id: i32,
+
+    pub status: String,
+    pub resolution_note: Option<String>,
+    pub delete_target: bool,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct PostMetadataChange {
+    pub id: Option<i32>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub post_id: Option<i32>,
+    pub user_id: Option<i32>,
+    pub user_name: Option<String>,
+    pub old_title: Option<String>,
+    pub new_title: Option<String>,
+    pub old_description: Option<String>,
+    pub new_description: Option<String>,
+    pub old_source: Option<String>,
+    pub new_source: Option<String>,
+    pub old_rating: Option<String>,
+    pub new_rating: Option<String>,
+    pub old_parent_id: Option<i32>,
+    pub new_parent_id: Option<i32>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct PostFileReplacement {
+    pub id: Option<i32>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub post_id: Option<i32>,
+    pub user_id: Option<i32>,
+    pub user_name: Option<String>,
+    pub old_filename: Option<String>,
+    pub new_filename: Option<String>,
+    pub old_size: Option<i32>,
+    pub new_size: Option<i32>,
+    pub old_width: Option<i32>,
+    pub new_width: Option<i32>,
+    pub old_height: Option<i32>,
+    pub new_height: Option<i32>,
+    pub old_hash: Option<String>,
+    pub new_hash: Option<String>,
+    pub old_ext: Option<String>,
+    pub new_ext: Option<String>,
+    pub old_tn_ext: Option<String>,
+    pub new_tn_ext: Option<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct PostTagChange {
+    pub id: Option<i32>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub post_id: Option<i32>,
+    pub user_id: Option<i32>,
+    pub user_name: Option<String>,
+    pub tags_added: Option<Vec<String>>,
+    pub tags_removed: Option<Vec<String>>,
+}
+
+#[derive(Debug, sqlx::Type)]
+#[sqlx(type_name = "new_tag_edit_job")]
+pub struct NewTagEditJob {
+    pub include_tags: Vec<String>,
+    pub exclude_tags: Vec<String>,
+    pub add_tags: Vec<String>,
+    pub remove_tags: Vec<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct TagEditJob {
+    pub id: Option<i32>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub user_id: Option<i32>,
+    pub user_name: Option<String>,
+    pub include_tags: Option<Vec<String>>,
+    pub exclude_tags: Option<Vec<String>>,
+    pub add_tags: Option<Vec<String>>,
+    pub remove_tags: Option<Vec<String>>,
+    pub post_count: Option<i32>,
+    pub processed_count: Option<i32>,
+    pub status: Option<String>,
+}
@@ -24,7 +24,7 @@ impl PgStore {
     pub async fn get_post_comments(&self, post_id: i32) -> Result<Vec<dbm::PostComment>, StoreError> {
         let comments = sqlx::query_as!(
             dbm::PostComment,
-            r#"SELECT * FROM post_comment WHERE post_id = $1 ORDER BY id ASC;"#,
+            r#"SELECT * FROM post_comment WHERE post_id = $1 AND NOT is_deleted ORDER BY id ASC;"#,
             Some(post_id)
         )
         .fetch_all(&self.pool)
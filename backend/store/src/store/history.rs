@@ -0,0 +1,130 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+
+use crate::{PgStore, StoreError, models as dbm};
+
+impl PgStore {
+    pub async fn get_post_metadata_changes(&self, post_id: i32) -> Result<Vec<dbm::PostMetadataChange>, StoreError> {
+        let changes = sqlx::query_as!(
+            dbm::PostMetadataChange,
+            r#"SELECT * FROM view_post_metadata_change WHERE post_id = $1 ORDER BY id DESC;"#,
+            post_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Error getting post metadata changes from database")?;
+
+        Ok(changes)
+    }
+
+    pub async fn get_post_metadata_change(&self, id: i32) -> Result<Option<dbm::PostMetadataChange>, StoreError> {
+        let change = sqlx::query_as!(
+            dbm::PostMetadataChange,
+            r#"SELECT * FROM view_post_metadata_change WHERE id = $1;"#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .context("Error getting post metadata change from database")?;
+
+        Ok(change)
+    }
+
+    pub async fn get_post_file_replacements(&self, post_id: i32) -> Result<Vec<dbm::PostFileReplacement>, StoreError> {
+        let replacements = sqlx::query_as!(
+            dbm::PostFileReplacement,
+            r#"SELECT * FROM view_post_file_replacement WHERE post_id = $1 ORDER BY id DESC;"#,
+            post_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Error getting post file replacements from database")?;
+
+        Ok(replacements)
+    }
+
+    /// Get the tag changes of a post, excluding updates that did not add or remove any tags
+    pub async fn get_post_tag_changes(&self, post_id: i32) -> Result<Vec<dbm::PostTagChange>, StoreError> {
+        let changes = sqlx::query_as!(
+            dbm::PostTagChange,
+            r#"
+SELECT *
+FROM view_post_tag_change
+WHERE post_id = $1
+  AND (cardinality(tags_added) > 0 OR cardinality(tags_removed) > 0)
+ORDER BY id DESC;
+"#,
+            post_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Error getting post tag changes from database")?;
+
+        Ok(changes)
+    }
+
+    /// Get tag changes, optionally only of a post and/or user, newest first.
+    /// Updates that did not add or remove any tags are excluded.
+    pub async fn get_tag_changes(
+        &self,
+        post_id: Option<i32>,
+        user_id: Option<i32>,
+        start_id: Option<i32>,
+        limit: i32,
+    ) -> Result<Vec<dbm::PostTagChange>, StoreError> {
+        let changes = sqlx::query_as!(
+            dbm::PostTagChange,
+            r#"
+SELECT *
+FROM view_post_tag_change
+WHERE ($1::integer IS NULL OR post_id = $1)
+  AND ($2::integer IS NULL OR user_id = $2)
+  AND id <= $3
+  AND (cardinality(tags_added) > 0 OR cardinality(tags_removed) > 0)
+ORDER BY id DESC
+LIMIT $4;
+"#,
+            post_id,
+            user_id,
+            start_id.unwrap_or(i32::MAX),
+            limit as i64
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Error getting tag changes from database")?;
+
+        Ok(changes)
+    }
+
+    pub async fn revert_post_tag_change(&self, change_id: i32, user_id: i32) -> Result<bool, StoreError> {
+        let success = sqlx::query_scalar_unchecked!(r#"SELECT revert_post_tag_change($1, $2);"#, change_id, user_id)
+            .fetch_one(&self.pool)
+            .await
+            .context("Error reverting tag change in database")?;
+
+        Ok(success.unwrap())
+    }
+
+    /// Revert all tag changes of a user made within a time window.
+    /// Returns the number of reverted changes.
+    pub async fn revert_user_tag_changes(
+        &self,
+        change_user_id: i32,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        user_id: i32,
+    ) -> Result<i32, StoreError> {
+        let reverted_count = sqlx::query_scalar_unchecked!(
+            r#"SELECT revert_user_tag_changes($1, $2, $3, $4);"#,
+            change_user_id,
+            since,
+            until,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Error reverting user tag changes in database")?;
+
+        Ok(reverted_count.unwrap())
+    }
+}
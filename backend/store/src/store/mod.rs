@@ -1,6 +1,11 @@
 mod auth;
 mod comment;
+mod history;
+mod note;
+mod pool;
 mod post;
+mod report;
+mod search;
 mod tag;
 mod user;
 mod wiki;
@@ -12,6 +17,18 @@ use thiserror::Error;
 pub enum StoreError {
     #[error(transparent)]
     Anyhow(#[from] anyhow::Error),
+    /// A unique constraint was violated, such as by a name that is already taken
+    #[error("Unique constraint violation")]
+    UniqueViolation,
+}
+
+/// Convert a database error into a store error with context,
+/// keeping unique constraint violations distinguishable
+fn map_unique_violation(err: sqlx::Error, context: &'static str) -> StoreError {
+    match &err {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => StoreError::UniqueViolation,
+        _ => anyhow::Error::new(err).context(context).into(),
+    }
 }
 
 pub struct PgStore {
@@ -0,0 +1,92 @@
+use anyhow::Context;
+
+use crate::{PgStore, StoreError, models as dbm};
+
+impl PgStore {
+    /// Create a note on a post. Returns `None` if the post does not exist or is deleted.
+    pub async fn create_post_note(&self, note: &dbm::NewPostNote, user_id: i32) -> Result<Option<i32>, StoreError> {
+        let new_note_id = sqlx::query_scalar_unchecked!(r#"SELECT create_post_note($1, $2);"#, note, user_id)
+            .fetch_one(&self.pool)
+            .await
+            .context("Error creating post note in database")?;
+
+        Ok(new_note_id)
+    }
+
+    pub async fn update_post_note(&self, note: &dbm::UpdatePostNote, user_id: i32) -> Result<bool, StoreError> {
+        let success = sqlx::query_scalar_unchecked!(r#"SELECT update_post_note($1, $2);"#, note, user_id)
+            .fetch_one(&self.pool)
+            .await
+            .context("Error updating post note in database")?;
+
+        Ok(success.unwrap())
+    }
+
+    pub async fn delete_post_note(&self, note_id: i32, user_id: i32) -> Result<bool, StoreError> {
+        let success = sqlx::query_scalar_unchecked!(r#"SELECT delete_post_note($1, $2);"#, note_id, user_id)
+            .fetch_one(&self.pool)
+            .await
+            .context("Error deleting post note in database")?;
+
+        Ok(success.unwrap())
+    }
+
+    pub async fn get_post_note(&self, id: i32) -> Result<Option<dbm::PostNote>, StoreError> {
+        let note = sqlx::query_as!(dbm::PostNote, r#"SELECT * FROM view_post_note WHERE id = $1;"#, id)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Error getting post note from database")?;
+
+        Ok(note)
+    }
+
+    /// Get the notes of a post. Notes of deleted posts and posts awaiting approval are not returned.
+    pub async fn get_post_notes(&self, post_id: i32) -> Result<Vec<dbm::PostNote>, StoreError> {
+        let notes = sqlx::query_as!(
+            dbm::PostNote,
+            r#"
+SELECT n.*
+FROM view_post_note AS n
+JOIN post AS p ON p.id = n.post_id
+WHERE n.post_id = $1
+  AND NOT p.is_deleted
+  AND NOT p.is_pending
+ORDER BY n.id ASC;
+"#,
+            post_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Error getting post notes from database")?;
+
+        Ok(notes)
+    }
+
+    /// Get the notes of multiple posts at once, ordered by post
+    pub async fn get_notes_by_post_ids(&self, post_ids: &[i32]) -> Result<Vec<dbm::PostNote>, StoreError> {
+        let notes = sqlx::query_as!(
+            dbm::PostNote,
+            r#"SELECT * FROM view_post_note WHERE post_id = ANY($1) ORDER BY post_id ASC, id ASC;"#,
+            post_ids
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Error getting post notes from database")?;
+
+        Ok(notes)
+    }
+
+    /// Get all versions of a note, newest first
+    pub async fn get_post_note_versions(&self, note_id: i32) -> Result<Vec<dbm::PostNoteVersion>, StoreError> {
+        let versions = sqlx::query_as!(
+            dbm::PostNoteVersion,
+            r#"SELECT * FROM view_post_note_version WHERE note_id = $1 ORDER BY version DESC;"#,
+            note_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Error getting post note versions from database")?;
+
+        Ok(versions)
+    }
+}
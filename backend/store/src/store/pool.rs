@@ -0,0 +1,85 @@
+use anyhow::Context;
+
+use crate::{PgStore, StoreError, models as dbm};
+
+use super::map_unique_violation;
+
+impl PgStore {
+    pub async fn create_pool(&self, pool: &dbm::NewPool, user_id: i32) -> Result<i32, StoreError> {
+        let new_pool_id = sqlx::query_scalar_unchecked!(r#"SELECT create_pool($1, $2);"#, pool, user_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|err| map_unique_violation(err, "Error creating pool in database"))?;
+
+        Ok(new_pool_id.unwrap())
+    }
+
+    pub async fn update_pool(&self, pool: &dbm::UpdatePool, user_id: i32) -> Result<bool, StoreError> {
+        let success = sqlx::query_scalar_unchecked!(r#"SELECT update_pool($1, $2);"#, pool, user_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|err| map_unique_violation(err, "Error updating pool in database"))?;
+
+        Ok(success.unwrap())
+    }
+
+    /// Replace the posts of a pool with the specified posts, in order
+    pub async fn set_pool_posts(&self, pool_id: i32, post_ids: &[i32], user_id: i32) -> Result<bool, StoreError> {
+        let success =
+            sqlx::query_scalar_unchecked!(r#"SELECT set_pool_posts($1, $2, $3);"#, pool_id, post_ids, user_id)
+                .fetch_one(&self.pool)
+                .await
+                .context("Error setting pool posts in database")?;
+
+        Ok(success.unwrap())
+    }
+
+    pub async fn delete_pool(&self, pool_id: i32, user_id: i32) -> Result<bool, StoreError> {
+        let success = sqlx::query_scalar_unchecked!(r#"SELECT delete_pool($1, $2);"#, pool_id, user_id)
+            .fetch_one(&self.pool)
+            .await
+            .context("Error deleting pool in database")?;
+
+        Ok(success.unwrap())
+    }
+
+    pub async fn get_pool(&self, id: i32) -> Result<Option<dbm::Pool>, StoreError> {
+        let pool = sqlx::query_as!(dbm::Pool, r#"SELECT * FROM view_pool WHERE id = $1;"#, id)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Error getting pool from database")?;
+
+        Ok(pool)
+    }
+
+    pub async fn get_all_pools(&self) -> Result<Vec<dbm::Pool>, StoreError> {
+        let pools = sqlx::query_as!(dbm::Pool, r#"SELECT * FROM view_pool ORDER BY name ASC;"#)
+            .fetch_all(&self.pool)
+            .await
+            .context("Error getting pools from database")?;
+
+        Ok(pools)
+    }
+
+    /// Get the posts of a pool with one of the specified ratings, in pool order
+    pub async fn get_pool_posts(&self, pool_id: i32, ratings: &[&str]) -> Result<Vec<dbm::ViewPost>, StoreError> {
+        let posts = sqlx::query_as!(
+            dbm::ViewPost,
+            r#"
+SELECT p.*
+FROM pool_post AS pp
+JOIN view_post AS p ON p.id = pp.post_id
+WHERE pp.pool_id = $1
+  AND p.rating = ANY($2)
+ORDER BY pp.position ASC;
+"#,
+            pool_id,
+            ratings as &[&str]
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Error getting pool posts from database")?;
+
+        Ok(posts)
+    }
+}
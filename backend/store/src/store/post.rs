@@ -1,7 +1,19 @@
 use anyhow::Context;
+use chrono::{DateTime, Utc};
+use sqlx::QueryBuilder;
+
+use blazebooru_models::search as sm;
 
 use crate::{PgStore, StoreError, models as dbm};
 
+use super::search;
+
+/// Number of 16-bit bands perceptual hashes are indexed by
+const PHASH_BANDS: usize = 4;
+
+/// Maximum distance of band values looked up when finding similar perceptual hashes
+const MAX_PHASH_BAND_DISTANCE: u32 = 2;
+
 impl PgStore {
     pub async fn get_post(&self, id: i32) -> Result<Option<dbm::Post>, StoreError> {
         let post = sqlx::query_as!(dbm::Post, r#"SELECT * FROM post WHERE id = $1;"#, id)
@@ -12,10 +24,19 @@ impl PgStore {
         Ok(post)
     }
 
+    /// Get the posts that have, or had before a file replacement, a file with the specified hash.
+    /// Posts currently having the file are returned first.
     pub async fn get_posts_by_hash(&self, hash: &str) -> Result<Vec<dbm::Post>, StoreError> {
         let posts = sqlx::query_as!(
             dbm::Post,
-            r#"SELECT * FROM post WHERE hash = $1 AND NOT is_deleted;"#,
+            r#"
+SELECT p.*
+FROM post AS p
+WHERE NOT p.is_deleted
+  AND (p.hash = $1
+       OR EXISTS (SELECT 1 FROM post_file_replacement AS r WHERE r.post_id = p.id AND r.old_hash = $1))
+ORDER BY p.hash = $1 DESC, p.id ASC;
+"#,
             hash
         )
         .fetch_all(&self.pool)
@@ -25,8 +46,8 @@ impl PgStore {
         Ok(posts)
     }
 
-    pub async fn create_post(&self, post: &dbm::NewPost, tags: &[&str]) -> Result<i32, StoreError> {
-        let new_post_id = sqlx::query_scalar_unchecked!(r#"SELECT create_post($1, $2);"#, post, tags)
+    pub async fn create_post(&self, post: &dbm::NewPost, tags: &[&str], phashes: &[i64]) -> Result<i32, StoreError> {
+        let new_post_id = sqlx::query_scalar_unchecked!(r#"SELECT create_post($1, $2, $3);"#, post, tags, phashes)
             .fetch_one(&self.pool)
             .await
             .context("Error creating post in database")?;
@@ -34,6 +55,120 @@ impl PgStore {
         Ok(new_post_id.unwrap())
     }
 
+    /// Create a post along with its notes in a single transaction
+    pub async fn create_post_with_notes(
+        &self,
+        post: &dbm::NewPost,
+        tags: &[&str],
+        phashes: &[i64],
+        notes: &[dbm::PostNoteContent],
+    ) -> Result<i32, StoreError> {
+        let mut tx = self.pool.begin().await.context("Error starting database transaction")?;
+
+        let new_post_id = sqlx::query_scalar_unchecked!(r#"SELECT create_post($1, $2, $3);"#, post, tags, phashes)
+            .fetch_one(&mut *tx)
+            .await
+            .context("Error creating post in database")?
+            .unwrap();
+
+        for note in notes {
+            sqlx::query!(
+                r#"
+WITH n AS (
+  INSERT INTO post_note (post_id, user_id, x, y, width, height, body)
+  VALUES ($1, $2, $3, $4, $5, $6, $7)
+  RETURNING *
+)
+INSERT INTO post_note_version (note_id, post_id, user_id, version, x, y, width, height, body, is_deleted)
+  SELECT id, post_id, user_id, version, x, y, width, height, body, is_deleted
+  FROM n;
+"#,
+                new_post_id,
+                post.user_id,
+                note.x,
+                note.y,
+                note.width,
+                note.height,
+                note.body,
+            )
+            .execute(&mut *tx)
+            .await
+            .context("Error creating post note in database")?;
+        }
+
+        tx.commit().await.context("Error committing database transaction")?;
+
+        Ok(new_post_id)
+    }
+
+    pub async fn get_post_phashes(&self, post_id: i32) -> Result<Vec<i64>, StoreError> {
+        let phashes = sqlx::query_scalar!(r#"SELECT phash FROM post_phash WHERE post_id = $1;"#, post_id)
+            .fetch_all(&self.pool)
+            .await
+            .context("Error getting post perceptual hashes from database")?;
+
+        Ok(phashes)
+    }
+
+    /// Get posts with a perceptual hash within the specified Hamming distance
+    /// of any of the specified hashes, nearest first
+    pub async fn get_similar_posts(
+        &self,
+        phashes: &[i64],
+        max_distance: i32,
+        exclude_post_id: Option<i32>,
+        limit: i32,
+    ) -> Result<Vec<dbm::SimilarPost>, StoreError> {
+        let mut qb = QueryBuilder::new(
+            r#"
+SELECT p.*, x.distance
+FROM (
+  SELECT ph.post_id, MIN(bit_count((ph.phash # q.phash)::bit(64)))::integer AS distance
+  FROM post_phash AS ph, unnest("#,
+        );
+        qb.push_bind(phashes);
+        qb.push("::bigint[]) AS q(phash)");
+
+        // Similar hashes have at least one band within a quarter of the distance,
+        // so only hashes with such a band need to be compared, which are found using indexes.
+        // For large distances there are too many band values to look up, so all hashes are compared.
+        let band_distance = max_distance.max(0) as u32 / PHASH_BANDS as u32;
+        if band_distance <= MAX_PHASH_BAND_DISTANCE {
+            qb.push("\n  WHERE ");
+
+            for (band, values) in phash_band_candidates(phashes, band_distance).into_iter().enumerate() {
+                if band > 0 {
+                    qb.push(" OR ");
+                }
+
+                qb.push(format!("phash_band(ph.phash, {band}) = ANY("));
+                qb.push_bind(values);
+                qb.push(")");
+            }
+        }
+
+        qb.push(
+            r#"
+  GROUP BY ph.post_id
+) AS x
+JOIN view_post AS p ON p.id = x.post_id
+WHERE x.distance <= "#,
+        );
+        qb.push_bind(max_distance);
+        qb.push("\n  AND p.id IS DISTINCT FROM ");
+        qb.push_bind(exclude_post_id);
+        qb.push("\nORDER BY x.distance ASC, p.id DESC\nLIMIT ");
+        qb.push_bind(limit);
+
+        let posts = qb
+            .build_query_as::<dbm::SimilarPost>()
+            .fetch_all(&self.pool)
+            .await
+            .context("Error getting similar posts from database")?;
+
+        Ok(posts)
+    }
+
     pub async fn update_post(&self, post: &dbm::UpdatePost, user_id: i32) -> Result<bool, StoreError> {
         let success = sqlx::query_scalar_unchecked!(r#"SELECT update_post($1, $2);"#, post, user_id)
             .fetch_one(&self.pool)
@@ -43,6 +178,15 @@ impl PgStore {
         Ok(success.unwrap())
     }
 
+    pub async fn can_user_edit_post(&self, post_id: i32, user_id: i32) -> Result<bool, StoreError> {
+        let can_edit = sqlx::query_scalar!(r#"SELECT can_user_edit_post($1, $2);"#, post_id, user_id)
+            .fetch_one(&self.pool)
+            .await
+            .context("Error checking post edit permission in database")?;
+
+        Ok(can_edit.unwrap_or(false))
+    }
+
     pub async fn delete_post(&self, post_id: i32, user_id: i32) -> Result<bool, StoreError> {
         let success = sqlx::query_scalar_unchecked!(r#"SELECT delete_post($1, $2);"#, post_id, user_id)
             .fetch_one(&self.pool)
@@ -52,6 +196,287 @@ impl PgStore {
         Ok(success.unwrap())
     }
 
+    /// Get posts awaiting approval, newest first
+    pub async fn get_pending_posts(
+        &self,
+        start_id: Option<i32>,
+        limit: i32,
+    ) -> Result<Vec<dbm::PendingPost>, StoreError> {
+        let posts = sqlx::query_as!(
+            dbm::PendingPost,
+            r#"SELECT * FROM view_pending_post WHERE id <= $1 ORDER BY id DESC LIMIT $2;"#,
+            start_id.unwrap_or(i32::MAX),
+            limit as i64
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Error getting pending posts from database")?;
+
+        Ok(posts)
+    }
+
+    pub async fn approve_post(&self, post_id: i32) -> Result<bool, StoreError> {
+        let success = sqlx::query_scalar_unchecked!(r#"SELECT approve_post($1);"#, post_id)
+            .fetch_one(&self.pool)
+            .await
+            .context("Error approving post in database")?;
+
+        Ok(success.unwrap())
+    }
+
+    pub async fn reject_post(&self, post_id: i32) -> Result<bool, StoreError> {
+        let success = sqlx::query_scalar_unchecked!(r#"SELECT reject_post($1);"#, post_id)
+            .fetch_one(&self.pool)
+            .await
+            .context("Error rejecting post in database")?;
+
+        Ok(success.unwrap())
+    }
+
+    pub async fn get_deleted_posts(
+        &self,
+        start_id: Option<i32>,
+        limit: i32,
+    ) -> Result<Vec<dbm::DeletedPost>, StoreError> {
+        let posts = sqlx::query_as!(
+            dbm::DeletedPost,
+            r#"SELECT * FROM view_deleted_post WHERE id <= $1 ORDER BY id DESC LIMIT $2;"#,
+            start_id.unwrap_or(i32::MAX),
+            limit as i64
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Error getting deleted posts from database")?;
+
+        Ok(posts)
+    }
+
+    pub async fn undelete_post(&self, post_id: i32) -> Result<bool, StoreError> {
+        let success = sqlx::query_scalar_unchecked!(r#"SELECT undelete_post($1);"#, post_id)
+            .fetch_one(&self.pool)
+            .await
+            .context("Error undeleting post in database")?;
+
+        Ok(success.unwrap())
+    }
+
+    /// Get the IDs of all posts, including deleted and pending ones, within an ID range,
+    /// having all the specified tags and one of the specified extensions, in ascending order.
+    /// Empty tag or extension lists match all posts.
+    pub async fn get_post_ids_by_filter(
+        &self,
+        tags: &[String],
+        exts: &[String],
+        min_id: i32,
+        max_id: i32,
+    ) -> Result<Vec<i32>, StoreError> {
+        let post_ids = sqlx::query_scalar!(
+            r#"
+SELECT id
+FROM post
+WHERE id BETWEEN $1 AND $2
+  AND tags @> $3
+  AND (cardinality($4::text[]) = 0 OR ext = ANY($4))
+ORDER BY id ASC;
+"#,
+            min_id,
+            max_id,
+            tags,
+            exts
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Error getting filtered post IDs from database")?;
+
+        Ok(post_ids)
+    }
+
+    pub async fn update_post_file_metadata(
+        &self,
+        post_id: i32,
+        width: i32,
+        height: i32,
+        tn_ext: &str,
+        renditions: &[String],
+        phashes: &[i64],
+    ) -> Result<bool, StoreError> {
+        let success = sqlx::query_scalar_unchecked!(
+            r#"SELECT update_post_file_metadata($1, $2, $3, $4, $5, $6);"#,
+            post_id,
+            width,
+            height,
+            tn_ext,
+            renditions,
+            phashes
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Error updating post file metadata in database")?;
+
+        Ok(success.unwrap())
+    }
+
+    /// Get posts that were deleted before the specified time.
+    pub async fn get_posts_deleted_before(&self, deleted_before: DateTime<Utc>) -> Result<Vec<dbm::Post>, StoreError> {
+        let posts = sqlx::query_as!(
+            dbm::Post,
+            r#"SELECT * FROM post WHERE is_deleted AND deleted_at < $1 ORDER BY id ASC;"#,
+            deleted_before
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Error getting deleted posts from database")?;
+
+        Ok(posts)
+    }
+
+    /// Permanently delete a deleted post.
+    pub async fn purge_post(&self, post_id: i32) -> Result<bool, StoreError> {
+        let success = sqlx::query_scalar_unchecked!(r#"SELECT purge_post($1);"#, post_id)
+            .fetch_one(&self.pool)
+            .await
+            .context("Error purging post in database")?;
+
+        Ok(success.unwrap())
+    }
+
+    /// Get the files a post had before its file was replaced
+    pub async fn get_replaced_post_files(&self, post_id: i32) -> Result<Vec<dbm::PostFile>, StoreError> {
+        let files = sqlx::query_scalar::<_, dbm::PostFile>(
+            r#"
+SELECT ROW(old_filename, old_size, old_width, old_height, old_hash, old_ext, old_tn_ext, old_renditions)::post_file
+FROM post_file_replacement
+WHERE post_id = $1
+ORDER BY id ASC;
+"#,
+        )
+        .bind(post_id)
+        .fetch_all(&self.pool)
+        .await
+        .context("Error getting replaced post files from database")?;
+
+        Ok(files)
+    }
+
+    /// Check if any post, including deleted ones, uses the file with the specified hash,
+    /// or had it before a file replacement that may be reverted.
+    pub async fn is_post_file_in_use(&self, hash: &str) -> Result<bool, StoreError> {
+        let in_use = sqlx::query_scalar!(
+            r#"
+SELECT EXISTS (SELECT 1 FROM post WHERE hash = $1)
+    OR EXISTS (SELECT 1 FROM post_file_replacement WHERE old_hash = $1);
+"#,
+            hash
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Error checking post file usage in database")?;
+
+        Ok(in_use.unwrap_or(false))
+    }
+
+    pub async fn replace_post_file(
+        &self,
+        post_id: i32,
+        file: &dbm::PostFile,
+        phashes: &[i64],
+        user_id: i32,
+    ) -> Result<bool, StoreError> {
+        let success = sqlx::query_scalar_unchecked!(
+            r#"SELECT replace_post_file($1, $2, $3, $4);"#,
+            post_id,
+            file,
+            phashes,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Error replacing post file in database")?;
+
+        Ok(success.unwrap())
+    }
+
+    pub async fn revert_post_file_replacement(
+        &self,
+        post_id: i32,
+        replacement_id: i32,
+        user_id: i32,
+    ) -> Result<bool, StoreError> {
+        let success = sqlx::query_scalar_unchecked!(
+            r#"SELECT revert_post_file_replacement($1, $2, $3);"#,
+            post_id,
+            replacement_id,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Error reverting post file replacement in database")?;
+
+        Ok(success.unwrap())
+    }
+
+    pub async fn favorite_post(&self, post_id: i32, user_id: i32) -> Result<bool, StoreError> {
+        let success = sqlx::query_scalar_unchecked!(r#"SELECT favorite_post($1, $2);"#, post_id, user_id)
+            .fetch_one(&self.pool)
+            .await
+            .context("Error favoriting post in database")?;
+
+        Ok(success.unwrap())
+    }
+
+    pub async fn unfavorite_post(&self, post_id: i32, user_id: i32) -> Result<bool, StoreError> {
+        let success = sqlx::query_scalar_unchecked!(r#"SELECT unfavorite_post($1, $2);"#, post_id, user_id)
+            .fetch_one(&self.pool)
+            .await
+            .context("Error unfavoriting post in database")?;
+
+        Ok(success.unwrap())
+    }
+
+    /// Set the vote of a user on a post, replacing any previous vote.
+    /// A score of 0 removes the vote.
+    pub async fn vote_post(&self, post_id: i32, user_id: i32, score: i16) -> Result<bool, StoreError> {
+        let success = sqlx::query_scalar_unchecked!(r#"SELECT vote_post($1, $2, $3);"#, post_id, user_id, score)
+            .fetch_one(&self.pool)
+            .await
+            .context("Error voting on post in database")?;
+
+        Ok(success.unwrap())
+    }
+
+    /// Get the posts with the highest sum of votes cast since the specified time
+    pub async fn get_popular_posts(
+        &self,
+        since: DateTime<Utc>,
+        ratings: &[&str],
+        limit: i32,
+    ) -> Result<Vec<dbm::PopularPost>, StoreError> {
+        let posts = sqlx::query_as::<_, dbm::PopularPost>(
+            r#"
+SELECT p.*, x.period_score
+FROM (
+  SELECT pv.post_id, SUM(pv.score)::integer AS period_score
+  FROM post_vote AS pv
+  WHERE pv.updated_at >= $1
+  GROUP BY pv.post_id
+) AS x
+JOIN view_post AS p ON p.id = x.post_id
+WHERE x.period_score > 0
+  AND p.rating = ANY($2)
+ORDER BY x.period_score DESC, p.id DESC
+LIMIT $3;
+"#,
+        )
+        .bind(since)
+        .bind(ratings)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Error getting popular posts from database")?;
+
+        Ok(posts)
+    }
+
     pub async fn get_view_post(&self, id: i32) -> Result<Option<dbm::ViewPost>, StoreError> {
         let post = sqlx::query_as!(dbm::ViewPost, r#"SELECT * FROM view_post WHERE id = $1;"#, id)
             .fetch_optional(&self.pool)
@@ -61,93 +486,375 @@ impl PgStore {
         Ok(post)
     }
 
-    pub async fn get_export_posts(&self) -> Result<Vec<dbm::ViewPost>, StoreError> {
-        let posts = sqlx::query_as!(dbm::ViewPost, r#"SELECT * FROM view_post ORDER BY id ASC;"#)
-            .fetch_all(&self.pool)
-            .await
-            .context("Error getting export posts from database")?;
+    /// Get all posts that are not deleted, including posts awaiting approval
+    pub async fn get_export_posts(&self) -> Result<Vec<dbm::ExportPost>, StoreError> {
+        let posts = sqlx::query_as::<_, dbm::ExportPost>(
+            r#"
+SELECT p.*, u.name AS user_name
+FROM post AS p
+JOIN users AS u ON u.id = p.user_id
+WHERE NOT p.is_deleted
+ORDER BY p.id ASC;
+"#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Error getting export posts from database")?;
 
         Ok(posts)
     }
 
     pub async fn get_view_posts(
         &self,
-        include_tags: &[String],
-        exclude_tags: &[String],
-        start_id: i32,
+        query: &sm::SearchQuery,
+        start: Option<sm::Cursor>,
         limit: i32,
     ) -> Result<Vec<dbm::ViewPost>, StoreError> {
-        let posts = sqlx::query_as!(
-            dbm::ViewPost,
-            r#"SELECT * FROM get_view_posts($1, $2, $3, $4);"#,
-            include_tags,
-            exclude_tags,
-            start_id,
-            limit
-        )
-        .fetch_all(&self.pool)
-        .await
-        .context("Error getting view posts from database")?;
+        if let Some(lists) = search::as_tag_lists(query) {
+            let start_id = start.map_or(i32::MAX, |c| c.id);
+
+            let posts = sqlx::query_as!(
+                dbm::ViewPost,
+                r#"SELECT * FROM get_view_posts($1, $2, $3, $4, $5);"#,
+                &lists.include_tags,
+                &lists.exclude_tags,
+                &lists.exclude_ratings,
+                start_id,
+                limit
+            )
+            .fetch_all(&self.pool)
+            .await
+            .context("Error getting view posts from database")?;
+
+            return Ok(posts);
+        }
+
+        let tag_ids = self.get_search_tag_ids(query).await?;
+        let ascending = query.order.ascending;
+
+        let mut qb = QueryBuilder::new("SELECT p.*");
+        search::push_search_from(&mut qb, query, &tag_ids);
+        if let Some(start) = &start {
+            search::push_keyset(&mut qb, query, start, ascending);
+        }
+        search::push_order_by(&mut qb, query, ascending);
+        qb.push(" LIMIT ").push_bind(limit);
+
+        let posts = qb
+            .build_query_as::<dbm::ViewPost>()
+            .fetch_all(&self.pool)
+            .await
+            .context("Error searching view posts in database")?;
 
         Ok(posts)
     }
 
+    /// Get the IDs of all posts matching a search query, in ascending order
+    pub async fn get_search_post_ids(&self, query: &sm::SearchQuery) -> Result<Vec<i32>, StoreError> {
+        let tag_ids = self.get_search_tag_ids(query).await?;
+
+        let mut qb = QueryBuilder::new("SELECT p.id");
+        search::push_search_from(&mut qb, query, &tag_ids);
+        qb.push(" ORDER BY p.id ASC");
+
+        let post_ids = qb
+            .build_query_scalar::<i32>()
+            .fetch_all(&self.pool)
+            .await
+            .context("Error searching post IDs in database")?;
+
+        Ok(post_ids)
+    }
+
+    /// Get a random post matching a search query
+    pub async fn get_random_view_post(&self, query: &sm::SearchQuery) -> Result<Option<dbm::ViewPost>, StoreError> {
+        let tag_ids = self.get_search_tag_ids(query).await?;
+
+        let mut qb = QueryBuilder::new("SELECT min(p.id), max(p.id)");
+        search::push_search_from(&mut qb, query, &tag_ids);
+
+        let id_range = qb
+            .build_query_as::<(Option<i32>, Option<i32>)>()
+            .fetch_one(&self.pool)
+            .await
+            .context("Error getting post ID range from database")?;
+
+        let (Some(min_id), Some(max_id)) = id_range else {
+            return Ok(None);
+        };
+
+        // Pick a random ID within the range, and take the first matching post from there,
+        // instead of sorting all matching posts randomly
+        let mut qb = QueryBuilder::new("SELECT p.*");
+        search::push_search_from(&mut qb, query, &tag_ids);
+        qb.push(" AND p.id >= (SELECT ")
+            .push_bind(min_id)
+            .push(" + floor(random() * (")
+            .push_bind(max_id - min_id + 1)
+            .push("))::integer)");
+        qb.push(" ORDER BY p.id ASC LIMIT 1");
+
+        let post = qb
+            .build_query_as::<dbm::ViewPost>()
+            .fetch_optional(&self.pool)
+            .await
+            .context("Error getting random view post from database")?;
+
+        Ok(post)
+    }
+
+    /// Get the IDs of the posts before and after a post in the results of a search query.
+    /// Returns `None` if the post does not exist.
+    pub async fn get_post_neighbors(
+        &self,
+        query: &sm::SearchQuery,
+        id: i32,
+    ) -> Result<Option<(Option<i32>, Option<i32>)>, StoreError> {
+        let mut qb = QueryBuilder::new("SELECT ");
+        search::push_sort_key(&mut qb, query);
+        qb.push(" FROM view_post AS p WHERE p.id = ").push_bind(id);
+
+        let key = qb
+            .build_query_scalar::<i64>()
+            .fetch_optional(&self.pool)
+            .await
+            .context("Error getting post sort key from database")?;
+
+        let Some(key) = key else {
+            return Ok(None);
+        };
+
+        let tag_ids = self.get_search_tag_ids(query).await?;
+        let cursor = sm::Cursor { key, id };
+        let ascending = query.order.ascending;
+
+        let mut qb = QueryBuilder::new("SELECT ");
+        for (i, scan_ascending) in [!ascending, ascending].into_iter().enumerate() {
+            if i > 0 {
+                qb.push(", ");
+            }
+
+            qb.push("(SELECT p.id");
+            search::push_search_from(&mut qb, query, &tag_ids);
+            search::push_keyset(&mut qb, query, &cursor, scan_ascending);
+            qb.push(" AND p.id <> ").push_bind(id);
+            search::push_order_by(&mut qb, query, scan_ascending);
+            qb.push(" LIMIT 1)");
+        }
+
+        let neighbors = qb
+            .build_query_as::<(Option<i32>, Option<i32>)>()
+            .fetch_one(&self.pool)
+            .await
+            .context("Error getting post neighbors from database")?;
+
+        Ok(Some(neighbors))
+    }
+
     pub async fn calculate_pages(
         &self,
-        include_tags: &[&str],
-        exclude_tags: &[&str],
+        query: &sm::SearchQuery,
         posts_per_page: i32,
         page_count: i32,
-        origin_page: Option<dbm::PageInfo>,
-    ) -> Result<Vec<dbm::PageInfo>, StoreError> {
-        let pages = if page_count < 0 {
-            sqlx::query_as_unchecked!(
-                dbm::PageInfo,
-                r#"SELECT * FROM unnest(calculate_pages_reverse($1, $2, $3, $4, $5));"#,
-                include_tags,
-                exclude_tags,
-                posts_per_page,
-                -page_count,
-                origin_page
-            )
-            .fetch_all(&self.pool)
-            .await
-            .context("Error calculating last page")?
+        origin_page: Option<dbm::KeysetPageInfo>,
+    ) -> Result<Vec<dbm::KeysetPageInfo>, StoreError> {
+        if let Some(lists) = search::as_tag_lists(query) {
+            let origin_page = origin_page.map(dbm::PageInfo::from);
+
+            let pages = if page_count < 0 {
+                sqlx::query_as_unchecked!(
+                    dbm::PageInfo,
+                    r#"SELECT * FROM unnest(calculate_pages_reverse($1, $2, $3, $4, $5, $6));"#,
+                    lists.include_tags,
+                    lists.exclude_tags,
+                    lists.exclude_ratings,
+                    posts_per_page,
+                    -page_count,
+                    origin_page
+                )
+                .fetch_all(&self.pool)
+                .await
+                .context("Error calculating last page")?
+            } else {
+                sqlx::query_as_unchecked!(
+                    dbm::PageInfo,
+                    r#"SELECT * FROM unnest(calculate_pages($1, $2, $3, $4, $5, $6));"#,
+                    lists.include_tags,
+                    lists.exclude_tags,
+                    lists.exclude_ratings,
+                    posts_per_page,
+                    page_count,
+                    origin_page
+                )
+                .fetch_all(&self.pool)
+                .await
+                .context("Error calculating last page")?
+            };
+
+            return Ok(pages.into_iter().map(dbm::KeysetPageInfo::from).collect());
+        }
+
+        let tag_ids = self.get_search_tag_ids(query).await?;
+        let ascending = query.order.ascending;
+
+        // When calculating previous pages, scan backwards from the origin page
+        let reverse = page_count < 0;
+        let scan_ascending = ascending != reverse;
+
+        let origin_no = origin_page.as_ref().map_or(if reverse { 0 } else { 1 }, |p| p.no);
+        let origin_cursor = origin_page.as_ref().map(|p| sm::Cursor {
+            key: p.start_key,
+            id: p.start_id,
+        });
+
+        let mut qb = QueryBuilder::new("SELECT x.no, x.start_id, x.start_key FROM (SELECT (");
+        qb.push_bind(origin_no)
+            .push(if reverse { " - " } else { " + " })
+            .push("(x.rn - 1) / ")
+            .push_bind(posts_per_page)
+            .push(")::integer AS no, x.id AS start_id, x.key AS start_key FROM (")
+            .push("SELECT p.id, ");
+        search::push_sort_key(&mut qb, query);
+        qb.push(" AS key, ROW_NUMBER() OVER (");
+        search::push_order_by(&mut qb, query, scan_ascending);
+        qb.push(")::integer AS rn");
+        search::push_search_from(&mut qb, query, &tag_ids);
+        if let Some(origin_cursor) = &origin_cursor {
+            search::push_keyset(&mut qb, query, origin_cursor, scan_ascending);
+        }
+        search::push_order_by(&mut qb, query, scan_ascending);
+        qb.push(" LIMIT ")
+            .push_bind((page_count.abs() + i32::from(reverse)) * posts_per_page)
+            .push(") AS x WHERE MOD(x.rn - 1, ")
+            .push_bind(posts_per_page)
+            .push(") = 0) AS x WHERE ");
+
+        if reverse {
+            qb.push("x.no < ").push_bind(origin_no);
         } else {
-            sqlx::query_as_unchecked!(
-                dbm::PageInfo,
-                r#"SELECT * FROM unnest(calculate_pages($1, $2, $3, $4, $5));"#,
-                include_tags,
-                exclude_tags,
-                posts_per_page,
-                page_count,
-                origin_page
-            )
+            qb.push("x.no > ").push_bind(origin_page.as_ref().map_or(0, |p| p.no));
+        }
+
+        let pages = qb
+            .build_query_as::<dbm::KeysetPageInfo>()
             .fetch_all(&self.pool)
             .await
-            .context("Error calculating last page")?
-        };
+            .context("Error calculating pages")?;
 
         Ok(pages)
     }
 
     pub async fn calculate_last_page(
         &self,
-        include_tags: &[&str],
-        exclude_tags: &[&str],
+        query: &sm::SearchQuery,
         posts_per_page: i32,
-    ) -> Result<dbm::PageInfo, StoreError> {
-        let page = sqlx::query_as_unchecked!(
-            dbm::PageInfo,
-            r#"SELECT * FROM calculate_last_page($1, $2, $3);"#,
-            include_tags,
-            exclude_tags,
-            posts_per_page
-        )
-        .fetch_one(&self.pool)
-        .await
-        .context("Error calculating last page")?;
+    ) -> Result<dbm::KeysetPageInfo, StoreError> {
+        if let Some(lists) = search::as_tag_lists(query) {
+            let page = sqlx::query_as_unchecked!(
+                dbm::PageInfo,
+                r#"SELECT * FROM calculate_last_page($1, $2, $3, $4);"#,
+                lists.include_tags,
+                lists.exclude_tags,
+                lists.exclude_ratings,
+                posts_per_page
+            )
+            .fetch_one(&self.pool)
+            .await
+            .context("Error calculating last page")?;
+
+            return Ok(dbm::KeysetPageInfo::from(page));
+        }
+
+        let tag_ids = self.get_search_tag_ids(query).await?;
+
+        let mut qb = QueryBuilder::new("SELECT COUNT(*)::integer");
+        search::push_search_from(&mut qb, query, &tag_ids);
+
+        let post_count: i32 = qb
+            .build_query_scalar()
+            .fetch_one(&self.pool)
+            .await
+            .context("Error counting posts")?;
+
+        // If no posts exist in the search, return.
+        if post_count == 0 {
+            return Ok(dbm::KeysetPageInfo {
+                no: 1,
+                start_id: 0,
+                start_key: 0,
+            });
+        }
+
+        let page_count = (post_count + posts_per_page - 1) / posts_per_page;
+
+        let mut qb = QueryBuilder::new("SELECT ");
+        qb.push_bind(page_count).push(" AS no, p.id AS start_id, ");
+        search::push_sort_key(&mut qb, query);
+        qb.push(" AS start_key");
+        search::push_search_from(&mut qb, query, &tag_ids);
+        search::push_order_by(&mut qb, query, query.order.ascending);
+        qb.push(" OFFSET ")
+            .push_bind((page_count - 1) * posts_per_page)
+            .push(" LIMIT 1");
+
+        let page = qb
+            .build_query_as::<dbm::KeysetPageInfo>()
+            .fetch_one(&self.pool)
+            .await
+            .context("Error calculating last page")?;
 
         Ok(page)
     }
 }
+
+/// Get the values of each band of the perceptual hashes, and all values within the specified distance of them
+fn phash_band_candidates(phashes: &[i64], distance: u32) -> [Vec<i32>; PHASH_BANDS] {
+    let masks: Vec<u16> = (0..=u16::MAX).filter(|m| m.count_ones() <= distance).collect();
+
+    std::array::from_fn(|band| {
+        let mut values: Vec<i32> = phashes
+            .iter()
+            .map(|phash| (*phash as u64 >> (band * 16)) as u16)
+            .flat_map(|value| masks.iter().map(move |mask| (value ^ mask) as i32))
+            .collect();
+
+        values.sort_unstable();
+        values.dedup();
+
+        values
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn band(phash: i64, band: usize) -> i32 {
+        (phash as u64 >> (band * 16)) as u16 as i32
+    }
+
+    #[test]
+    fn phash_band_candidates_contain_own_bands() {
+        let phash = 0x0123_4567_89ab_cdef_u64 as i64;
+        let candidates = phash_band_candidates(&[phash], 0);
+
+        for (i, values) in candidates.iter().enumerate() {
+            assert_eq!(values, &[band(phash, i)]);
+        }
+    }
+
+    #[test]
+    fn phash_band_candidates_find_similar_hashes() {
+        let phash = 0x0123_4567_89ab_cdef_u64 as i64;
+
+        // Flip 2 bits in each band, for a total distance of 8
+        let similar = phash ^ 0x0003_0300_0030_3000_u64 as i64;
+        assert_eq!((phash ^ similar).count_ones(), 8);
+
+        let candidates = phash_band_candidates(&[phash], 8 / PHASH_BANDS as u32);
+        assert!((0..PHASH_BANDS).all(|i| candidates[i].contains(&band(similar, i))));
+
+        let candidates = phash_band_candidates(&[phash], 1);
+        assert!((0..PHASH_BANDS).all(|i| !candidates[i].contains(&band(similar, i))));
+    }
+}
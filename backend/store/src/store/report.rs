@@ -0,0 +1,55 @@
+use anyhow::Context;
+
+use crate::{PgStore, StoreError, models as dbm};
+
+impl PgStore {
+    /// Create a report. Returns `None` if the reported item does not exist.
+    pub async fn create_report(&self, report: &dbm::NewReport, user_id: i32) -> Result<Option<i32>, StoreError> {
+        let new_report_id = sqlx::query_scalar_unchecked!(r#"SELECT create_report($1, $2);"#, report, user_id)
+            .fetch_one(&self.pool)
+            .await
+            .context("Error creating report in database")?;
+
+        Ok(new_report_id)
+    }
+
+    /// Resolve or dismiss an open report, deleting the reported item if requested
+    pub async fn resolve_report(&self, resolution: &dbm::ResolveReport, user_id: i32) -> Result<bool, StoreError> {
+        let success = sqlx::query_scalar_unchecked!(r#"SELECT resolve_report($1, $2);"#, resolution, user_id)
+            .fetch_one(&self.pool)
+            .await
+            .context("Error resolving report in database")?;
+
+        Ok(success.unwrap())
+    }
+
+    pub async fn get_report(&self, id: i32) -> Result<Option<dbm::Report>, StoreError> {
+        let report = sqlx::query_as!(dbm::Report, r#"SELECT * FROM view_report WHERE id = $1;"#, id)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Error getting report from database")?;
+
+        Ok(report)
+    }
+
+    /// Get reports with the specified status, newest first
+    pub async fn get_reports(
+        &self,
+        status: &str,
+        start_id: Option<i32>,
+        limit: i32,
+    ) -> Result<Vec<dbm::Report>, StoreError> {
+        let reports = sqlx::query_as!(
+            dbm::Report,
+            r#"SELECT * FROM view_report WHERE status = $1 AND id <= $2 ORDER BY id DESC LIMIT $3;"#,
+            status,
+            start_id.unwrap_or(i32::MAX),
+            limit as i64
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Error getting reports from database")?;
+
+        Ok(reports)
+    }
+}
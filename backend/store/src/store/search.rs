@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use sqlx::{Postgres, QueryBuilder};
+
+use blazebooru_models::search as sm;
+use blazebooru_models::view as vm;
+
+use crate::{PgStore, StoreError};
+
+/// Search tag IDs (with aliases resolved) by tag name
+pub(crate) type SearchTagIds = HashMap<String, Vec<i32>>;
+
+impl PgStore {
+    /// Resolve all tags referenced in a search query into search tag IDs
+    pub(crate) async fn get_search_tag_ids(&self, query: &sm::SearchQuery) -> Result<SearchTagIds, StoreError> {
+        let mut tags = Vec::new();
+        if let Some(expr) = &query.expr {
+            collect_tags(expr, &mut tags);
+        }
+
+        if tags.is_empty() {
+            return Ok(SearchTagIds::new());
+        }
+
+        let rows = sqlx::query!(
+            r#"SELECT t.tag, compute_search_tag_ids(ARRAY[t.id]) AS "tag_ids!" FROM tag AS t WHERE t.tag = ANY($1);"#,
+            &tags as &[String]
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Error resolving search tags in database")?;
+
+        Ok(rows.into_iter().map(|r| (r.tag, r.tag_ids)).collect())
+    }
+}
+
+fn collect_tags(expr: &sm::Expr, tags: &mut Vec<String>) {
+    match expr {
+        sm::Expr::And(exprs) | sm::Expr::Or(exprs) => exprs.iter().for_each(|e| collect_tags(e, tags)),
+        sm::Expr::Not(expr) => collect_tags(expr, tags),
+        sm::Expr::Term(sm::Term::Tag(tag)) => tags.push(tag.clone()),
+        sm::Expr::Term(_) => {}
+    }
+}
+
+/// Included and excluded tags and excluded ratings of a query, for the (cached) tag search functions in the database
+pub(crate) struct TagLists {
+    pub include_tags: Vec<String>,
+    pub exclude_tags: Vec<String>,
+    pub exclude_ratings: Vec<String>,
+}
+
+/// If the query consists only of included and excluded tags and ratings in the default order,
+/// return them as lists, so that the (cached) tag search functions in the database can be used.
+pub(crate) fn as_tag_lists(query: &sm::SearchQuery) -> Option<TagLists> {
+    fn collect(expr: &sm::Expr, lists: &mut TagLists) -> bool {
+        match expr {
+            sm::Expr::And(exprs) => exprs.iter().all(|e| collect(e, lists)),
+            sm::Expr::Term(sm::Term::Tag(tag)) => {
+                lists.include_tags.push(tag.clone());
+                true
+            }
+            sm::Expr::Term(sm::Term::Rating(ratings)) => {
+                let excluded = vm::Rating::ALL.into_iter().filter(|r| !ratings.contains(r));
+                lists.exclude_ratings.extend(excluded.map(|r| r.as_str().to_string()));
+                true
+            }
+            sm::Expr::Not(expr) => match expr.as_ref() {
+                sm::Expr::Term(sm::Term::Tag(tag)) => {
+                    lists.exclude_tags.push(tag.clone());
+                    true
+                }
+                sm::Expr::Term(sm::Term::Rating(ratings)) => {
+                    lists
+                        .exclude_ratings
+                        .extend(ratings.iter().map(|r| r.as_str().to_string()));
+                    true
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    if query.order != sm::Order::default() {
+        return None;
+    }
+
+    let mut lists = TagLists {
+        include_tags: Vec::new(),
+        exclude_tags: Vec::new(),
+        exclude_ratings: Vec::new(),
+    };
+
+    if let Some(expr) = &query.expr
+        && !collect(expr, &mut lists)
+    {
+        return None;
+    }
+
+    Some(lists)
+}
+
+/// Push the FROM and WHERE clauses for posts matching a search query.
+/// Matching posts are available as `p` (view_post) and `ptic` (post_tag_id_cache).
+pub(crate) fn push_search_from(qb: &mut QueryBuilder<'_, Postgres>, query: &sm::SearchQuery, tag_ids: &SearchTagIds) {
+    qb.push(" FROM post_tag_id_cache AS ptic JOIN view_post AS p ON p.id = ptic.post_id WHERE ");
+
+    match &query.expr {
+        Some(expr) => push_condition(qb, expr, tag_ids),
+        None => {
+            qb.push("TRUE");
+        }
+    }
+}
+
+/// Push the SQL expression for the sort key of the query's order
+pub(crate) fn push_sort_key(qb: &mut QueryBuilder<'_, Postgres>, query: &sm::SearchQuery) {
+    match query.order.by {
+        sm::SortBy::Id => {
+            qb.push("p.id::bigint");
+        }
+        sm::SortBy::Date => {
+            qb.push("(EXTRACT(EPOCH FROM p.created_at) * 1000000)::bigint");
+        }
+        sm::SortBy::Size => {
+            qb.push("p.size::bigint");
+        }
+        sm::SortBy::Resolution => {
+            qb.push("(p.width::bigint * p.height)");
+        }
+        sm::SortBy::Score => {
+            qb.push("p.score::bigint");
+        }
+        sm::SortBy::Random(seed) => {
+            qb.push("hashint4extended(p.id, ").push_bind(seed).push(")");
+        }
+        sm::SortBy::Rank => {
+            let mut texts = Vec::new();
+            if let Some(expr) = &query.expr {
+                collect_texts(expr, &mut texts);
+            }
+
+            if texts.is_empty() {
+                qb.push("0::bigint");
+                return;
+            }
+
+            qb.push("(ts_rank(post_search_vector(p.title, p.description), ");
+            for (i, text) in texts.into_iter().enumerate() {
+                if i > 0 {
+                    qb.push(" && ");
+                }
+
+                qb.push("websearch_to_tsquery('english', ").push_bind(text).push(")");
+            }
+            qb.push(") * 1000000)::bigint");
+        }
+    }
+}
+
+/// Push a condition limiting posts to the ones from the cursor onwards,
+/// scanning in the specified direction
+pub(crate) fn push_keyset(
+    qb: &mut QueryBuilder<'_, Postgres>,
+    query: &sm::SearchQuery,
+    cursor: &sm::Cursor,
+    ascending: bool,
+) {
+    let op = if ascending { ">=" } else { "<=" };
+
+    if query.order.by == sm::SortBy::Id {
+        qb.push(format!(" AND ptic.post_id {op} ")).push_bind(cursor.id);
+    } else {
+        qb.push(" AND (");
+        push_sort_key(qb, query);
+        qb.push(format!(", p.id) {op} ("))
+            .push_bind(cursor.key)
+            .push(", ")
+            .push_bind(cursor.id)
+            .push(")");
+    }
+}
+
+/// Push the ORDER BY clause for scanning posts in the specified direction
+pub(crate) fn push_order_by(qb: &mut QueryBuilder<'_, Postgres>, query: &sm::SearchQuery, ascending: bool) {
+    let direction = if ascending { "ASC" } else { "DESC" };
+
+    if query.order.by == sm::SortBy::Id {
+        qb.push(format!(" ORDER BY ptic.post_id {direction}"));
+    } else {
+        qb.push(" ORDER BY ");
+        push_sort_key(qb, query);
+        qb.push(format!(" {direction}, p.id {direction}"));
+    }
+}
+
+/// Collect the text of all full-text search terms that are not negated
+fn collect_texts(expr: &sm::Expr, texts: &mut Vec<String>) {
+    match expr {
+        sm::Expr::And(exprs) | sm::Expr::Or(exprs) => exprs.iter().for_each(|e| collect_texts(e, texts)),
+        sm::Expr::Not(_) => {}
+        sm::Expr::Term(sm::Term::Text(text)) => texts.push(text.clone()),
+        sm::Expr::Term(_) => {}
+    }
+}
+
+fn push_condition(qb: &mut QueryBuilder<'_, Postgres>, expr: &sm::Expr, tag_ids: &SearchTagIds) {
+    match expr {
+        sm::Expr::And(exprs) | sm::Expr::Or(exprs) => {
+            let separator = if matches!(expr, sm::Expr::And(_)) {
+                " AND "
+            } else {
+                " OR "
+            };
+
+            qb.push("(");
+            for (i, expr) in exprs.iter().enumerate() {
+                if i > 0 {
+                    qb.push(separator);
+                }
+
+                push_condition(qb, expr, tag_ids);
+            }
+            qb.push(")");
+        }
+        sm::Expr::Not(expr) => {
+            qb.push("NOT (");
+            push_condition(qb, expr, tag_ids);
+            qb.push(")");
+        }
+        sm::Expr::Term(term) => push_term(qb, term, tag_ids),
+    }
+}
+
+fn push_term(qb: &mut QueryBuilder<'_, Postgres>, term: &sm::Term, tag_ids: &SearchTagIds) {
+    match term {
+        sm::Term::Tag(tag) => match tag_ids.get(tag).filter(|ids| !ids.is_empty()) {
+            Some(ids) => {
+                qb.push("ptic.tag_ids @> ").push_bind(ids.clone());
+            }
+            // Nonexistent tags can never match
+            None => {
+                qb.push("FALSE");
+            }
+        },
+        sm::Term::User(name) => {
+            qb.push("p.user_name = ").push_bind(name.clone());
+        }
+        sm::Term::Fav(name) => {
+            qb.push(
+                "EXISTS (SELECT 1 FROM post_favorite AS pf JOIN users AS u ON u.id = pf.user_id \
+                 WHERE pf.post_id = p.id AND u.name = ",
+            )
+            .push_bind(name.clone())
+            .push(")");
+        }
+        sm::Term::Pool(pool) => {
+            qb.push(
+                "EXISTS (SELECT 1 FROM pool_post AS pp JOIN pool AS pl ON pl.id = pp.pool_id \
+                 WHERE pp.post_id = p.id AND NOT pl.is_deleted AND ",
+            );
+            match pool.parse::<i32>() {
+                Ok(id) => qb.push("pl.id = ").push_bind(id),
+                Err(_) => qb.push("pl.name = ").push_bind(pool.clone()),
+            };
+            qb.push(")");
+        }
+        sm::Term::Parent(id) => {
+            qb.push("(p.id = ")
+                .push_bind(*id)
+                .push(" OR p.parent_id = ")
+                .push_bind(*id)
+                .push(")");
+        }
+        sm::Term::HasParent(has_parent) => {
+            qb.push(if *has_parent {
+                "p.parent_id IS NOT NULL"
+            } else {
+                "p.parent_id IS NULL"
+            });
+        }
+        sm::Term::HasChildren(has_children) => {
+            qb.push(if *has_children { "" } else { "NOT " }).push(
+                "EXISTS (SELECT 1 FROM post AS c WHERE c.parent_id = p.id AND NOT c.is_deleted AND NOT c.is_pending)",
+            );
+        }
+        sm::Term::HasNotes(has_notes) => {
+            qb.push(if *has_notes { "" } else { "NOT " })
+                .push("EXISTS (SELECT 1 FROM post_note AS n WHERE n.post_id = p.id AND NOT n.is_deleted)");
+        }
+        sm::Term::Ext(ext) => {
+            qb.push("p.ext = ").push_bind(ext.clone());
+        }
+        sm::Term::Id(range) => push_num_range(qb, "p.id", range),
+        sm::Term::Width(range) => push_num_range(qb, "p.width", range),
+        sm::Term::Height(range) => push_num_range(qb, "p.height", range),
+        sm::Term::Size(range) => push_num_range(qb, "p.size", range),
+        sm::Term::Score(range) => push_num_range(qb, "p.score", range),
+        sm::Term::Text(text) => {
+            qb.push("post_search_vector(p.title, p.description) @@ websearch_to_tsquery('english', ")
+                .push_bind(text.clone())
+                .push(")");
+        }
+        sm::Term::Rating(ratings) => {
+            let ratings: Vec<_> = ratings.iter().map(|r| r.as_str()).collect();
+            qb.push("p.rating = ANY(").push_bind(ratings).push(")");
+        }
+        sm::Term::Date(range) => {
+            qb.push("(TRUE");
+            if let Some(start) = range.start {
+                qb.push(" AND p.created_at >= ").push_bind(start);
+            }
+            if let Some(end) = range.end {
+                qb.push(" AND p.created_at < ").push_bind(end);
+            }
+            qb.push(")");
+        }
+    }
+}
+
+fn push_num_range(qb: &mut QueryBuilder<'_, Postgres>, column: &str, range: &sm::NumRange) {
+    qb.push("(TRUE");
+    if let Some(min) = range.min {
+        qb.push(format!(" AND {column} >= ")).push_bind(min);
+    }
+    if let Some(max) = range.max {
+        qb.push(format!(" AND {column} <= ")).push_bind(max);
+    }
+    qb.push(")");
+}
@@ -1,7 +1,12 @@
 use anyhow::Context;
+use sqlx::QueryBuilder;
+
+use blazebooru_models::search as sm;
 
 use crate::{PgStore, StoreError, models as dbm};
 
+use super::search;
+
 impl PgStore {
     pub async fn get_view_tag(&self, id: i32) -> Result<Option<dbm::ViewTag>, StoreError> {
         let tag = sqlx::query_as!(dbm::ViewTag, r#"SELECT * FROM view_tag WHERE id = $1;"#, id)
@@ -21,6 +26,56 @@ impl PgStore {
         Ok(tags)
     }
 
+    /// Get the most used tags starting with or containing the query text, ignoring case.
+    /// Tags starting with the query text are listed first.
+    pub async fn autocomplete_tags(&self, query: &str, limit: i32) -> Result<Vec<dbm::AutocompleteTag>, StoreError> {
+        let query = escape_like(query);
+
+        let tags = sqlx::query_as!(
+            dbm::AutocompleteTag,
+            r#"
+SELECT
+  t.id,
+  t.tag,
+  aot.tag AS "alias_of_tag?",
+  COALESCE(aot.post_count, t.post_count) AS "post_count!"
+FROM tag AS t
+LEFT JOIN tag AS aot ON aot.id = t.alias_of_tag_id
+WHERE t.tag ILIKE '%' || $1 || '%'
+ORDER BY t.tag ILIKE $1 || '%' DESC, 4 DESC, t.tag ASC
+LIMIT $2;
+"#,
+            query,
+            limit as i64
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Error getting autocomplete tags from database")?;
+
+        Ok(tags)
+    }
+
+    /// Get the most frequent tags among the posts matching a search query
+    pub async fn get_search_tags(&self, query: &sm::SearchQuery, limit: i32) -> Result<Vec<dbm::SearchTag>, StoreError> {
+        let tag_ids = self.get_search_tag_ids(query).await?;
+
+        let mut qb = QueryBuilder::new(
+            "SELECT t.id, t.tag, t.post_count, COUNT(*)::integer AS search_post_count FROM (SELECT ptic.tag_ids",
+        );
+        search::push_search_from(&mut qb, query, &tag_ids);
+        qb.push(") AS x CROSS JOIN unnest(x.tag_ids) AS tid JOIN tag AS t ON t.id = tid")
+            .push(" GROUP BY t.id ORDER BY search_post_count DESC, t.tag ASC LIMIT ")
+            .push_bind(limit);
+
+        let tags = qb
+            .build_query_as::<dbm::SearchTag>()
+            .fetch_all(&self.pool)
+            .await
+            .context("Error getting search tags from database")?;
+
+        Ok(tags)
+    }
+
     pub async fn update_tag(&self, id: i32, tag: &dbm::UpdateTag, user_id: i32) -> Result<bool, StoreError> {
         let success = sqlx::query_scalar_unchecked!(r#"SELECT update_tag($1, $2, $3);"#, id, tag, user_id)
             .fetch_one(&self.pool)
@@ -29,4 +84,64 @@ impl PgStore {
 
         Ok(success.unwrap())
     }
+
+    pub async fn create_tag_edit_job(
+        &self,
+        job: &dbm::NewTagEditJob,
+        post_ids: &[i32],
+        user_id: i32,
+    ) -> Result<i32, StoreError> {
+        let job_id =
+            sqlx::query_scalar_unchecked!(r#"SELECT create_tag_edit_job($1, $2, $3);"#, job, post_ids, user_id)
+                .fetch_one(&self.pool)
+                .await
+                .context("Error creating tag edit job in database")?;
+
+        Ok(job_id.unwrap())
+    }
+
+    pub async fn get_tag_edit_job(&self, id: i32) -> Result<Option<dbm::TagEditJob>, StoreError> {
+        let job = sqlx::query_as!(dbm::TagEditJob, r#"SELECT * FROM view_tag_edit_job WHERE id = $1;"#, id)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Error getting tag edit job from database")?;
+
+        Ok(job)
+    }
+
+    /// Get the IDs of the tag edit jobs that have not completed or failed
+    pub async fn get_running_tag_edit_job_ids(&self) -> Result<Vec<i32>, StoreError> {
+        let job_ids = sqlx::query_scalar!(r#"SELECT id FROM tag_edit_job WHERE status = 'running' ORDER BY id ASC;"#)
+            .fetch_all(&self.pool)
+            .await
+            .context("Error getting running tag edit jobs from database")?;
+
+        Ok(job_ids)
+    }
+
+    /// Apply a tag edit job to its next batch of posts.
+    /// Returns the number of posts processed, which is 0 once the job is no longer running.
+    pub async fn run_tag_edit_job_batch(&self, id: i32, batch_size: i32) -> Result<i32, StoreError> {
+        let processed_count =
+            sqlx::query_scalar_unchecked!(r#"SELECT run_tag_edit_job_batch($1, $2);"#, id, batch_size)
+                .fetch_one(&self.pool)
+                .await
+                .context("Error running tag edit job batch in database")?;
+
+        Ok(processed_count.unwrap())
+    }
+
+    pub async fn fail_tag_edit_job(&self, id: i32) -> Result<(), StoreError> {
+        sqlx::query_unchecked!(r#"SELECT fail_tag_edit_job($1);"#, id)
+            .execute(&self.pool)
+            .await
+            .context("Error failing tag edit job in database")?;
+
+        Ok(())
+    }
+}
+
+/// Escape the pattern characters of a LIKE pattern, so that the text is matched literally
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
 }
@@ -76,4 +76,24 @@ impl PgStore {
 
         Ok(pages)
     }
+
+    pub async fn search_wiki_pages(&self, query: &str, limit: i32) -> Result<Vec<dbm::WikiPage>, StoreError> {
+        let pages = sqlx::query_as!(
+            dbm::WikiPage,
+            r#"
+SELECT w.*
+FROM view_wiki AS w, websearch_to_tsquery('english', $1) AS q
+WHERE NOT w.deleted AND wiki_search_vector(w.title, w.body) @@ q
+ORDER BY ts_rank(wiki_search_vector(w.title, w.body), q) DESC, w.id ASC
+LIMIT $2;
+"#,
+            query,
+            limit as i64
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Error searching wiki pages in database")?;
+
+        Ok(pages)
+    }
 }
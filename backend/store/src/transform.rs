@@ -24,16 +24,19 @@ impl From<dbm::User> for lm::User {
     }
 }
 
-impl From<dbm::User> for vm::User {
-    fn from(u: dbm::User) -> Self {
-        vm::User {
+impl TryFrom<dbm::User> for vm::User {
+    type Error = anyhow::Error;
+
+    fn try_from(u: dbm::User) -> Result<Self, Self::Error> {
+        Ok(vm::User {
             id: u.id,
             created_at: u.created_at,
             name: u.name,
             rank: u.rank,
             biography: u.biography,
-            css: u.css
-        }
+            css: u.css,
+            max_rating: parse_rating(&u.max_rating)?,
+        })
     }
 }
 
@@ -49,10 +52,42 @@ impl From<dbm::User> for vm::PublicUser {
     }
 }
 
-impl From<dbm::ViewPost> for em::Post {
-    fn from(p: dbm::ViewPost) -> Self {
-        em::Post {
+impl TryFrom<dbm::ExportPost> for em::Post {
+    type Error = anyhow::Error;
+
+    fn try_from(p: dbm::ExportPost) -> Result<Self, Self::Error> {
+        let dbm::ExportPost { post: p, user_name } = p;
+
+        Ok(em::Post {
+            created_at: p.created_at,
+            user_name,
+            title: p.title,
+            description: p.description,
+            source: p.source,
+            filename: p.filename,
+            size: p.size,
+            width: p.width,
+            height: p.height,
+            hash: p.hash,
+            ext: p.ext,
+            tn_ext: p.tn_ext,
+            renditions: p.renditions,
+            tags: p.tags,
+            rating: parse_rating(&p.rating)?,
+            is_pending: p.is_pending,
+            notes: Vec::new(),
+        })
+    }
+}
+
+impl TryFrom<dbm::ViewPost> for vm::Post {
+    type Error = anyhow::Error;
+
+    fn try_from(p: dbm::ViewPost) -> Result<Self, Self::Error> {
+        Ok(vm::Post {
+            id: p.id.unwrap(),
             created_at: p.created_at.unwrap(),
+            user_id: p.user_id.unwrap(),
             user_name: p.user_name.unwrap(),
             title: p.title,
             description: p.description,
@@ -64,29 +99,149 @@ impl From<dbm::ViewPost> for em::Post {
             hash: p.hash.unwrap(),
             ext: p.ext.unwrap(),
             tn_ext: p.tn_ext.unwrap(),
+            renditions: p.renditions.unwrap(),
             tags: p.tags.unwrap(),
-        }
+            rating: parse_rating(&p.rating.unwrap())?,
+            fav_count: p.fav_count.unwrap(),
+            score: p.score.unwrap(),
+            pools: p.pools.unwrap(),
+            parent_id: p.parent_id,
+            child_ids: p.child_ids.unwrap(),
+        })
     }
 }
 
-impl From<dbm::ViewPost> for vm::Post {
-    fn from(p: dbm::ViewPost) -> Self {
-        vm::Post {
+impl TryFrom<dbm::DeletedPost> for vm::DeletedPost {
+    type Error = anyhow::Error;
+
+    fn try_from(p: dbm::DeletedPost) -> Result<Self, Self::Error> {
+        Ok(vm::DeletedPost {
+            id: p.id.unwrap(),
+            created_at: p.created_at.unwrap(),
+            deleted_at: p.deleted_at,
+            user_id: p.user_id.unwrap(),
+            user_name: p.user_name.unwrap(),
+            title: p.title,
+            filename: p.filename.unwrap(),
+            hash: p.hash.unwrap(),
+            ext: p.ext.unwrap(),
+            tn_ext: p.tn_ext.unwrap(),
+            tags: p.tags.unwrap(),
+            rating: parse_rating(&p.rating.unwrap())?,
+        })
+    }
+}
+
+impl TryFrom<dbm::PendingPost> for vm::PendingPost {
+    type Error = anyhow::Error;
+
+    fn try_from(p: dbm::PendingPost) -> Result<Self, Self::Error> {
+        Ok(vm::PendingPost {
             id: p.id.unwrap(),
             created_at: p.created_at.unwrap(),
             user_id: p.user_id.unwrap(),
             user_name: p.user_name.unwrap(),
             title: p.title,
-            description: p.description,
-            source: p.source,
             filename: p.filename.unwrap(),
-            size: p.size.unwrap(),
-            width: p.width.unwrap(),
-            height: p.height.unwrap(),
             hash: p.hash.unwrap(),
             ext: p.ext.unwrap(),
             tn_ext: p.tn_ext.unwrap(),
             tags: p.tags.unwrap(),
+            rating: parse_rating(&p.rating.unwrap())?,
+        })
+    }
+}
+
+impl TryFrom<dbm::PopularPost> for vm::PopularPost {
+    type Error = anyhow::Error;
+
+    fn try_from(p: dbm::PopularPost) -> Result<Self, Self::Error> {
+        Ok(vm::PopularPost {
+            period_score: p.period_score,
+            post: p.post.try_into()?,
+        })
+    }
+}
+
+impl TryFrom<dbm::SimilarPost> for vm::SimilarPost {
+    type Error = anyhow::Error;
+
+    fn try_from(p: dbm::SimilarPost) -> Result<Self, Self::Error> {
+        Ok(vm::SimilarPost {
+            distance: p.distance,
+            post: p.post.try_into()?,
+        })
+    }
+}
+
+impl TryFrom<dbm::PostMetadataChange> for vm::PostMetadataChange {
+    type Error = anyhow::Error;
+
+    fn try_from(c: dbm::PostMetadataChange) -> Result<Self, Self::Error> {
+        Ok(vm::PostMetadataChange {
+            id: c.id.unwrap(),
+            created_at: c.created_at.unwrap(),
+            post_id: c.post_id.unwrap(),
+            user_id: c.user_id.unwrap(),
+            user_name: c.user_name.unwrap(),
+            old: vm::PostMetadata {
+                title: c.old_title,
+                description: c.old_description,
+                source: c.old_source,
+                rating: parse_rating(&c.old_rating.unwrap())?,
+                parent_id: c.old_parent_id,
+            },
+            new: vm::PostMetadata {
+                title: c.new_title,
+                description: c.new_description,
+                source: c.new_source,
+                rating: parse_rating(&c.new_rating.unwrap())?,
+                parent_id: c.new_parent_id,
+            },
+        })
+    }
+}
+
+impl From<dbm::PostFileReplacement> for vm::PostFileReplacement {
+    fn from(r: dbm::PostFileReplacement) -> Self {
+        vm::PostFileReplacement {
+            id: r.id.unwrap(),
+            created_at: r.created_at.unwrap(),
+            post_id: r.post_id.unwrap(),
+            user_id: r.user_id.unwrap(),
+            user_name: r.user_name.unwrap(),
+            old: vm::PostFile {
+                filename: r.old_filename.unwrap(),
+                size: r.old_size.unwrap(),
+                width: r.old_width.unwrap(),
+                height: r.old_height.unwrap(),
+                hash: r.old_hash.unwrap(),
+                ext: r.old_ext.unwrap(),
+                tn_ext: r.old_tn_ext.unwrap(),
+            },
+            new: vm::PostFile {
+                filename: r.new_filename.unwrap(),
+                size: r.new_size.unwrap(),
+                width: r.new_width.unwrap(),
+                height: r.new_height.unwrap(),
+                hash: r.new_hash.unwrap(),
+                ext: r.new_ext.unwrap(),
+                tn_ext: r.new_tn_ext.unwrap(),
+            },
+        }
+    }
+}
+
+impl From<dbm::PostTagChange> for vm::PostTagChange {
+    fn from(c: dbm::PostTagChange) -> Self {
+        vm::PostTagChange {
+            id: c.id.unwrap(),
+            created_at: c.created_at.unwrap(),
+            post_id: c.post_id.unwrap(),
+            user_id: c.user_id.unwrap(),
+            user_name: c.user_name.unwrap(),
+            tags_added: c.tags_added.unwrap(),
+            tags_removed: c.tags_removed.unwrap(),
         }
     }
 }
@@ -104,17 +259,71 @@ impl From<dbm::PostComment> for vm::Comment {
     }
 }
 
-impl From<dbm::PageInfo> for vm::PageInfo {
+impl From<dbm::PostNote> for vm::PostNote {
+    fn from(n: dbm::PostNote) -> Self {
+        vm::PostNote {
+            id: n.id.unwrap(),
+            created_at: n.created_at.unwrap(),
+            updated_at: n.updated_at.unwrap(),
+            post_id: n.post_id.unwrap(),
+            user_id: n.user_id.unwrap(),
+            user_name: n.user_name.unwrap(),
+            x: n.x.unwrap(),
+            y: n.y.unwrap(),
+            width: n.width.unwrap(),
+            height: n.height.unwrap(),
+            body: n.body.unwrap(),
+            version: n.version.unwrap(),
+        }
+    }
+}
+
+impl From<dbm::PostNote> for em::Note {
+    fn from(n: dbm::PostNote) -> Self {
+        em::Note {
+            x: n.x.unwrap(),
+            y: n.y.unwrap(),
+            width: n.width.unwrap(),
+            height: n.height.unwrap(),
+            body: n.body.unwrap(),
+        }
+    }
+}
+
+impl From<dbm::PostNoteVersion> for vm::PostNoteVersion {
+    fn from(v: dbm::PostNoteVersion) -> Self {
+        vm::PostNoteVersion {
+            id: v.id.unwrap(),
+            created_at: v.created_at.unwrap(),
+            note_id: v.note_id.unwrap(),
+            post_id: v.post_id.unwrap(),
+            user_id: v.user_id.unwrap(),
+            user_name: v.user_name.unwrap(),
+            version: v.version.unwrap(),
+            x: v.x.unwrap(),
+            y: v.y.unwrap(),
+            width: v.width.unwrap(),
+            height: v.height.unwrap(),
+            body: v.body.unwrap(),
+            is_deleted: v.is_deleted.unwrap(),
+        }
+    }
+}
+
+impl From<dbm::PageInfo> for dbm::KeysetPageInfo {
     fn from(p: dbm::PageInfo) -> Self {
-        vm::PageInfo {
+        let start_id = p.start_id.unwrap();
+
+        dbm::KeysetPageInfo {
             no: p.no.unwrap(),
-            start_id: p.start_id.unwrap(),
+            start_id,
+            start_key: start_id.into(),
         }
     }
 }
 
-impl From<vm::PageInfo> for dbm::PageInfo {
-    fn from(p: vm::PageInfo) -> Self {
+impl From<dbm::KeysetPageInfo> for dbm::PageInfo {
+    fn from(p: dbm::KeysetPageInfo) -> Self {
         dbm::PageInfo {
             no: Some(p.no),
             start_id: Some(p.start_id),
@@ -122,18 +331,61 @@ impl From<vm::PageInfo> for dbm::PageInfo {
     }
 }
 
+impl From<dbm::KeysetPageInfo> for vm::PageInfo {
+    fn from(p: dbm::KeysetPageInfo) -> Self {
+        vm::PageInfo {
+            no: p.no,
+            start_id: p.start_id,
+            start_key: p.start_key,
+        }
+    }
+}
+
+impl From<vm::PageInfo> for dbm::KeysetPageInfo {
+    fn from(p: vm::PageInfo) -> Self {
+        dbm::KeysetPageInfo {
+            no: p.no,
+            start_id: p.start_id,
+            start_key: p.start_key,
+        }
+    }
+}
+
 impl From<dbm::ViewTag> for vm::Tag {
     fn from(t: dbm::ViewTag) -> Self {
         vm::Tag {
             id: t.id.unwrap(),
             tag: t.tag.unwrap(),
             alias_of_tag: t.alias_of_tag,
+            post_count: t.post_count.unwrap(),
             aliases: t.aliases.unwrap(),
             implied_tags: t.implied_tags.unwrap(),
         }
     }
 }
 
+impl From<dbm::AutocompleteTag> for vm::AutocompleteTag {
+    fn from(t: dbm::AutocompleteTag) -> Self {
+        vm::AutocompleteTag {
+            id: t.id,
+            tag: t.tag,
+            alias_of_tag: t.alias_of_tag,
+            post_count: t.post_count,
+        }
+    }
+}
+
+impl From<dbm::SearchTag> for vm::SearchTag {
+    fn from(t: dbm::SearchTag) -> Self {
+        vm::SearchTag {
+            id: t.id,
+            tag: t.tag,
+            post_count: t.post_count,
+            search_post_count: t.search_post_count,
+        }
+    }
+}
+
 impl From<vm::UpdateTag> for dbm::UpdateTag {
     fn from(t: vm::UpdateTag) -> Self {
         dbm::UpdateTag {
@@ -187,6 +439,101 @@ impl From<vm::UpdateWikiPage> for dbm::UpdateWikiPage {
     }
 }
 
+impl From<dbm::Pool> for vm::Pool {
+    fn from(p: dbm::Pool) -> Self {
+        vm::Pool {
+            id: p.id.unwrap(),
+            created_at: p.created_at.unwrap(),
+            updated_at: p.updated_at.unwrap(),
+            user_id: p.user_id.unwrap(),
+            user_name: p.user_name.unwrap(),
+            name: p.name.unwrap(),
+            description: p.description,
+            post_ids: p.post_ids.unwrap(),
+        }
+    }
+}
+
+impl From<vm::NewPool> for dbm::NewPool {
+    fn from(p: vm::NewPool) -> Self {
+        dbm::NewPool {
+            name: Some(p.name),
+            description: p.description.filter(|v| !v.is_empty()),
+            post_ids: p.post_ids,
+        }
+    }
+}
+
+impl From<dbm::Report> for vm::Report {
+    fn from(r: dbm::Report) -> Self {
+        let target_type: vm::ReportTarget = r.target_type.unwrap().parse().unwrap();
+
+        let target_link = match target_type {
+            vm::ReportTarget::Post | vm::ReportTarget::Comment => r.target_post_id.map(|id| format!("/post/{id}")),
+            vm::ReportTarget::Wiki => r.target_name.map(|title| format!("/wiki/{title}")),
+            vm::ReportTarget::User => r.target_name.map(|name| format!("/user/{name}")),
+        };
+
+        vm::Report {
+            id: r.id.unwrap(),
+            created_at: r.created_at.unwrap(),
+            updated_at: r.updated_at.unwrap(),
+            user_id: r.user_id.unwrap(),
+            user_name: r.user_name.unwrap(),
+            target_type,
+            target_id: r.target_id.unwrap(),
+            target_link,
+            reason: r.reason.unwrap(),
+            status: r.status.unwrap().parse().unwrap(),
+            resolver_id: r.resolver_id,
+            resolver_name: r.resolver_name,
+            resolved_at: r.resolved_at,
+            resolution_note: r.resolution_note,
+            target_deleted: r.target_deleted.unwrap(),
+        }
+    }
+}
+
+impl From<vm::NewReport> for dbm::NewReport {
+    fn from(r: vm::NewReport) -> Self {
+        dbm::NewReport {
+            target_type: r.target_type.as_str().to_string(),
+            target_id: r.target_id,
+            reason: r.reason,
+        }
+    }
+}
+
+impl From<vm::NewTagEditJob> for dbm::NewTagEditJob {
+    fn from(j: vm::NewTagEditJob) -> Self {
+        dbm::NewTagEditJob {
+            include_tags: j.include_tags,
+            exclude_tags: j.exclude_tags,
+            add_tags: j.add_tags,
+            remove_tags: j.remove_tags,
+        }
+    }
+}
+
+impl From<dbm::TagEditJob> for vm::TagEditJob {
+    fn from(j: dbm::TagEditJob) -> Self {
+        vm::TagEditJob {
+            id: j.id.unwrap(),
+            created_at: j.created_at.unwrap(),
+            updated_at: j.updated_at.unwrap(),
+            user_id: j.user_id.unwrap(),
+            user_name: j.user_name.unwrap(),
+            include_tags: j.include_tags.unwrap(),
+            exclude_tags: j.exclude_tags.unwrap(),
+            add_tags: j.add_tags.unwrap(),
+            remove_tags: j.remove_tags.unwrap(),
+            post_count: j.post_count.unwrap(),
+            processed_count: j.processed_count.unwrap(),
+            status: j.status.unwrap().parse().unwrap(),
+        }
+    }
+}
+
 pub fn dbm_update_post_from_vm(id: i32, p: vm::UpdatePost) -> dbm::UpdatePost {
     dbm::UpdatePost {
         id: Some(id),
@@ -195,6 +542,9 @@ pub fn dbm_update_post_from_vm(id: i32, p: vm::UpdatePost) -> dbm::UpdatePost {
         source: p.source.filter(|v| !v.is_empty()),
         add_tags: p.add_tags,
         remove_tags: p.remove_tags,
+        rating: p.rating.map(|r| r.as_str().to_string()),
+        parent_id: p.parent_id,
+        remove_parent: p.remove_parent,
     }
 }
 
@@ -204,6 +554,64 @@ pub fn dbm_update_user_from_vm(id: i32, p: vm::UpdateUser) -> dbm::UpdateUser {
         name: p.name,
         rank: p.rank,
         biography: p.biography,
-        css: p.css
+        css: p.css,
+        max_rating: p.max_rating.map(|r| r.as_str().to_string()),
+    }
+}
+
+pub fn dbm_update_pool_from_vm(id: i32, p: vm::UpdatePool) -> dbm::UpdatePool {
+    dbm::UpdatePool {
+        id: Some(id),
+        name: p.name.filter(|v| !v.is_empty()),
+        // An empty description clears it, while an omitted one is kept
+        description: p.description,
+    }
+}
+
+pub fn dbm_new_post_note_from_vm(post_id: i32, n: vm::PostNoteContent) -> dbm::NewPostNote {
+    dbm::NewPostNote {
+        post_id,
+        x: n.x,
+        y: n.y,
+        width: n.width,
+        height: n.height,
+        body: n.body,
     }
-}
\ No newline at end of file
+}
+
+pub fn dbm_post_note_content_from_vm(n: vm::PostNoteContent) -> dbm::PostNoteContent {
+    dbm::PostNoteContent {
+        x: n.x,
+        y: n.y,
+        width: n.width,
+        height: n.height,
+        body: n.body,
+    }
+}
+
+pub fn dbm_update_post_note_from_vm(id: i32, n: vm::PostNoteContent) -> dbm::UpdatePostNote {
+    dbm::UpdatePostNote {
+        id,
+        x: n.x,
+        y: n.y,
+        width: n.width,
+        height: n.height,
+        body: n.body,
+    }
+}
+
+pub fn dbm_resolve_report_from_vm(id: i32, r: vm::ResolveReport) -> dbm::ResolveReport {
+    dbm::ResolveReport {
+        id,
+        status: r.status.as_str().to_string(),
+        resolution_note: r.resolution_note.filter(|v| !v.is_empty()),
+        delete_target: r.delete_target,
+    }
+}
+
+/// Parse a content rating read from the database
+fn parse_rating(rating: &str) -> Result<vm::Rating, anyhow::Error> {
+    rating
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid rating in database: {rating}"))
+}
@@ -0,0 +1,38 @@
+export interface PostNote {
+  id: number;
+  created_at: string;
+  updated_at: string;
+  post_id: number;
+  user_id: number;
+  user_name: string;
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+  body: string;
+  version: number;
+}
+
+export interface PostNoteContent {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+  body: string;
+}
+
+export interface PostNoteVersion {
+  id: number;
+  created_at: string;
+  note_id: number;
+  post_id: number;
+  user_id: number;
+  user_name: string;
+  version: number;
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+  body: string;
+  is_deleted: boolean;
+}
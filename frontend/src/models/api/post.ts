@@ -1,8 +1,16 @@
 export interface PageInfo {
   no: number;
   start_id: number;
+  start_key: number;
 }
 
+export interface PostNeighbors {
+  prev_id?: number;
+  next_id?: number;
+}
+
+export type Rating = "general" | "sensitive" | "questionable" | "explicit";
+
 export interface Post {
   id: number;
   created_at: string;
@@ -18,20 +26,141 @@ export interface Post {
   hash: string;
   ext: string;
   tn_ext: string;
+  renditions: string[];
+  rating: Rating;
+  fav_count: number;
+  score: number;
+  pools: number[];
+  parent_id?: number;
+  child_ids: number[];
+  tags: string[];
+}
+
+export interface DeletedPost {
+  id: number;
+  created_at: string;
+  deleted_at?: string;
+  user_id: number;
+  user_name: string;
+  title?: string;
+  filename: string;
+  hash: string;
+  ext: string;
+  tn_ext: string;
+  tags: string[];
+  rating: Rating;
+}
+
+export interface PendingPost {
+  id: number;
+  created_at: string;
+  user_id: number;
+  user_name: string;
+  title?: string;
+  filename: string;
+  hash: string;
+  ext: string;
+  tn_ext: string;
   tags: string[];
+  rating: Rating;
+}
+
+export interface SimilarPost {
+  distance: number;
+  post: Post;
+}
+
+export interface UploadPostResult {
+  id: number;
+  pending: boolean;
+  similar_posts: SimilarPost[];
+}
+
+export type BatchUploadFileResult = {
+  filename: string;
+  archive?: string;
+} & (
+  | ({ status: "created" } & UploadPostResult)
+  | { status: "duplicate"; id: number }
+  | { status: "error"; message: string }
+);
+
+export interface BatchUploadResult {
+  files: BatchUploadFileResult[];
 }
 
 export interface PostInfo {
   title?: string;
   description?: string;
   source?: string;
+  rating?: Rating;
   tags: string[];
 }
 
+export interface UrlUploadPost extends PostInfo {
+  url: string;
+}
+
 export interface UpdatePost {
   title?: string;
   description?: string;
   source?: string;
+  rating?: Rating;
+  parent_id?: number;
+  remove_parent?: boolean;
   add_tags: string[];
   remove_tags: string[];
 }
+
+export interface PostMetadata {
+  title?: string;
+  description?: string;
+  source?: string;
+  rating: Rating;
+  parent_id?: number;
+}
+
+export interface PostMetadataChange {
+  kind: "metadata";
+  id: number;
+  created_at: string;
+  post_id: number;
+  user_id: number;
+  user_name: string;
+  old: PostMetadata;
+  new: PostMetadata;
+}
+
+export interface PostTagChange {
+  kind: "tags";
+  id: number;
+  created_at: string;
+  post_id: number;
+  user_id: number;
+  user_name: string;
+  tags_added: string[];
+  tags_removed: string[];
+}
+
+export interface PostFile {
+  filename: string;
+  size: number;
+  width: number;
+  height: number;
+  hash: string;
+  ext: string;
+  tn_ext: string;
+}
+
+export interface PostFileReplacement {
+  kind: "file";
+  id: number;
+  created_at: string;
+  post_id: number;
+  user_id: number;
+  user_name: string;
+  old: PostFile;
+  new: PostFile;
+}
+
+export type PostHistoryEntry = PostMetadataChange | PostTagChange | PostFileReplacement;
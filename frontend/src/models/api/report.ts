@@ -0,0 +1,33 @@
+export type ReportTarget = "post" | "comment" | "wiki" | "user";
+
+export type ReportStatus = "open" | "resolved" | "dismissed";
+
+export interface NewReport {
+  target_type: ReportTarget;
+  target_id: number;
+  reason: string;
+}
+
+export interface Report {
+  id: number;
+  created_at: string;
+  updated_at: string;
+  user_id: number;
+  user_name: string;
+  target_type: ReportTarget;
+  target_id: number;
+  target_link?: string;
+  reason: string;
+  status: ReportStatus;
+  resolver_id?: number;
+  resolver_name?: string;
+  resolved_at?: string;
+  resolution_note?: string;
+  target_deleted: boolean;
+}
+
+export interface ResolveReport {
+  status: ReportStatus;
+  resolution_note?: string;
+  delete_target?: boolean;
+}
@@ -1,5 +1,13 @@
 export interface SysConfig {
   max_image_size: number;
+  max_batch_upload_size: number;
   require_login: boolean;
   allow_registration: boolean;
+  renditions: Rendition[];
+}
+
+export interface Rendition {
+  name: string;
+  size: number;
+  sample: boolean;
 }
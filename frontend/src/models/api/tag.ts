@@ -12,3 +12,37 @@ export interface UpdateTag {
   add_implied_tags?: string[];
   remove_implied_tags?: string[];
 }
+
+export interface RevertUserTagChanges {
+  user_id: number;
+  since: string;
+  until?: string;
+}
+
+export interface RevertTagChangesResult {
+  reverted_count: number;
+}
+
+export interface NewTagEditJob {
+  include_tags?: string[];
+  exclude_tags?: string[];
+  add_tags?: string[];
+  remove_tags?: string[];
+}
+
+export type TagEditJobStatus = "running" | "completed" | "failed";
+
+export interface TagEditJob {
+  id: number;
+  created_at: string;
+  updated_at: string;
+  user_id: number;
+  user_name: string;
+  include_tags: string[];
+  exclude_tags: string[];
+  add_tags: string[];
+  remove_tags: string[];
+  post_count: number;
+  processed_count: number;
+  status: TagEditJobStatus;
+}
@@ -1,3 +1,5 @@
+import type { Rating } from "@/models/api/post";
+
 export interface User {
   id: number;
   created_at: string;
@@ -5,12 +7,14 @@ export interface User {
   rank: number;
   biography: string;
   css: string;
+  max_rating: Rating;
 }
 
 export interface UserUpdateUser {
   name: string;
   biography: string;
   css: string;
+  max_rating?: Rating;
 }
 
 export interface AdminUpdateUser extends UserUpdateUser {
@@ -102,7 +102,7 @@ export const useMainStore = defineStore("main", () => {
     return res.data;
   }
 
-  async function fetchPosts(include_tags: string[], exclude_tags: string[], start_id: number) {
+  async function fetchPosts(include_tags: string[], exclude_tags: string[], start_id: number, start_key: number) {
     const t = include_tags.join(",") || undefined;
     const e = exclude_tags.join(",") || undefined;
 
@@ -111,6 +111,7 @@ export const useMainStore = defineStore("main", () => {
         t,
         e,
         sid: start_id,
+        skey: start_key,
         limit: settings.value.posts_per_page,
       },
       headers: await authStore.getAuthHeaders(),
@@ -168,6 +169,7 @@ export const useMainStore = defineStore("main", () => {
         pc: page_count || CALCULATE_PAGES,
         opno: origin_page?.no,
         opsid: origin_page?.start_id,
+        opskey: origin_page?.start_key,
       },
     });
 
@@ -217,13 +219,13 @@ export const useMainStore = defineStore("main", () => {
     return true;
   }
 
-  async function loadPosts(start_id: number) {
+  async function loadPosts(pageInfo: PageInfo) {
     const search = activeSearch.value;
     if (!search) {
       return;
     }
 
-    currentPosts.value = await fetchPosts(search.tags, search.exclude_tags, start_id);
+    currentPosts.value = await fetchPosts(search.tags, search.exclude_tags, pageInfo.start_id, pageInfo.start_key);
   }
 
   function findNearestPage(page: number) {
@@ -282,7 +284,7 @@ export const useMainStore = defineStore("main", () => {
     }
 
     currentPage.value = page;
-    await loadPosts(pageInfo.start_id);
+    await loadPosts(pageInfo);
   }
 
   async function loadLastPage() {
@@ -4,7 +4,7 @@ import { computed, ref } from "vue";
 
 import { useAuthStore } from "./auth";
 
-import type { PostInfo } from "@/models/api/post";
+import type { PostInfo, SimilarPost, UploadPostResult } from "@/models/api/post";
 
 export interface StagedPost {
   file: File;
@@ -22,6 +22,7 @@ export interface QueuedUploadPost extends StagedPost {
   progress: number;
   error_message?: string;
   post_id?: number;
+  similar_posts?: SimilarPost[];
 }
 
 export const useUploadStore = defineStore("upload", () => {
@@ -116,7 +117,7 @@ export const useUploadStore = defineStore("upload", () => {
             formData.append("info", JSON.stringify(info));
             formData.append("file", up.file, up.file.name);
 
-            const res = await axios.post<number>("/api/post/upload", formData, {
+            const res = await axios.post<UploadPostResult>("/api/post/upload", formData, {
               headers: await authStore.getAuthHeaders(),
               onUploadProgress: (e) => {
                 if (e.total) {
@@ -126,7 +127,8 @@ export const useUploadStore = defineStore("upload", () => {
               },
             });
 
-            up.post_id = res.data;
+            up.post_id = res.data.id;
+            up.similar_posts = res.data.similar_posts;
           } catch (err: any) {
             const _err = err as AxiosError;
 
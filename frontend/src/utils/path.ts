@@ -1,4 +1,5 @@
 import type { Post } from "@/models/api/post";
+import type { Rendition } from "@/models/api/sys";
 
 export function make_image_path(post: Post) {
   return `/f/o/${post.hash}.${post.ext}`;
@@ -7,3 +8,29 @@ export function make_image_path(post: Post) {
 export function make_thumbnail_path(post: Post) {
   return `/f/t/${post.hash}.${post.tn_ext}`;
 }
+
+export function make_rendition_path(post: Post, name: string) {
+  return `/f/${name}/${post.hash}.${post.tn_ext}`;
+}
+
+// Lists the thumbnail renditions of a post by their density relative to the 't' thumbnail
+export function make_thumbnail_srcset(post: Post, renditions: Rendition[]) {
+  const thumbnail = renditions.find((r) => r.name === "t");
+  if (!thumbnail) {
+    return;
+  }
+
+  return renditions
+    .filter((r) => !r.sample && r.size >= thumbnail.size && post.renditions.includes(r.name))
+    .map((r) => `${make_rendition_path(post, r.name)} ${+(r.size / thumbnail.size).toFixed(2)}x`)
+    .join(", ");
+}
+
+export function make_sample_path(post: Post, renditions: Rendition[]) {
+  const sample = renditions.find((r) => r.sample && post.renditions.includes(r.name));
+  if (!sample) {
+    return;
+  }
+
+  return make_rendition_path(post, sample.name);
+}